@@ -1,23 +1,171 @@
-use axum::Router;
+use std::sync::Arc;
 
+use axum::extract::{Path, Request, State};
+use axum::http::StatusCode;
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use ulid::Ulid;
+
+use super::s3::auth::Authenticator;
+use super::s3::errors::S3HTTPError;
 use super::s3::mapping::S3Mapping;
 use super::s3::state::S3State;
 use super::state::AppState;
+use crate::domain::storage::errors::BucketStorageError;
+use crate::domain::storage::{BackendDriver, BucketStorage};
+use crate::infrastructure::storage::BackendStorage;
+
+/// Not valid bucket names, so they don't hide a bucket. They are
+/// authenticated like the S3 requests.
+const NOTIFICATION_METRICS_PATH: &str = "/_wasmio/metrics/notifications";
+const BUCKET_METRICS_PATH: &str = "/_wasmio/metrics/buckets/:bucket";
+const BUCKET_METRICS_RECOMPUTE_PATH: &str =
+    "/_wasmio/metrics/buckets/:bucket/recompute";
 
-pub struct AppMapping {
-    state: AppState,
+pub struct AppMapping<T: BackendDriver> {
+    state: AppState<T>,
 }
 
-impl AppMapping {
-    pub fn new(state: AppState) -> Self {
+impl<T> AppMapping<T>
+where
+    T: BackendDriver,
+    BucketStorageError: From<<T as BackendStorage>::Error>,
+{
+    pub fn new(state: AppState<T>) -> Self {
         Self { state }
     }
 
     pub fn into_router(self) -> Router {
-        let s3mapping = S3Mapping::new(S3State::from_state(&self.state));
+        let s3state = S3State::from_state(&self.state);
+        let auth = s3state.auth.clone();
+        let s3mapping = S3Mapping::new(s3state);
 
         Router::new()
+            .route(NOTIFICATION_METRICS_PATH, get(notification_metrics::<T>))
+            .route(BUCKET_METRICS_PATH, get(bucket_metrics::<T>))
+            .route(
+                BUCKET_METRICS_RECOMPUTE_PATH,
+                post(recompute_bucket_metrics::<T>),
+            )
+            .route_layer(middleware::from_fn_with_state(auth, authenticate))
             .with_state(self.state)
             .merge(s3mapping.into_router())
     }
 }
+
+/// Reject the requests which are not signed with the configured credentials,
+/// when there are some.
+async fn authenticate(
+    State(auth): State<Option<Arc<Authenticator>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let (parts, body) = request.into_parts();
+    if let Some(auth) = auth {
+        if let Err(err) = auth.authenticate_admin(&parts) {
+            return S3HTTPError::custom(
+                parts.uri.path(),
+                Ulid::new().to_string(),
+                err,
+            )
+            .into_response();
+        }
+    }
+    next.run(Request::from_parts(parts, body)).await
+}
+
+/// Counters of the event deliveries, as JSON.
+async fn notification_metrics<T: BackendDriver>(
+    State(state): State<AppState<T>>,
+) -> Response {
+    match &state.notifier {
+        Some(notifier) => Json(notifier.metrics()).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Statistics of a bucket, as JSON.
+async fn bucket_metrics<T>(
+    State(state): State<AppState<T>>,
+    Path(bucket): Path<String>,
+) -> Response
+where
+    T: BackendDriver,
+    BucketStorageError: From<<T as BackendStorage>::Error>,
+{
+    match BucketStorage::new(state.storage)
+        .bucket_stats(&bucket)
+        .await
+    {
+        Ok(stats) => Json(stats).into_response(),
+        Err(BucketStorageError::NoBucket) => {
+            StatusCode::NOT_FOUND.into_response()
+        }
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+/// Statistics of a bucket computed again from its objects, as JSON.
+async fn recompute_bucket_metrics<T>(
+    State(state): State<AppState<T>>,
+    Path(bucket): Path<String>,
+) -> Response
+where
+    T: BackendDriver,
+    BucketStorageError: From<<T as BackendStorage>::Error>,
+{
+    match BucketStorage::new(state.storage)
+        .recompute_bucket_stats(&bucket)
+        .await
+    {
+        Ok(stats) => Json(stats).into_response(),
+        Err(BucketStorageError::NoBucket) => {
+            StatusCode::NOT_FOUND.into_response()
+        }
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), not(target_os = "wasi")))]
+mod tests {
+    use axum::body::Body;
+    use tower::Service;
+
+    use super::*;
+    use crate::infrastructure::config::Cfg;
+    use crate::infrastructure::storage::MemoryStorage;
+
+    async fn metrics(auth: Option<serde_json::Value>) -> StatusCode {
+        let cfg: Cfg = serde_json::from_value(serde_json::json!({
+            "bind_addr": "127.0.0.1:0",
+            "storage": { "backend": "memory" },
+            "auth": auth,
+        }))
+        .unwrap();
+        let storage = MemoryStorage::new();
+        storage.new_database("bucket", None).await.unwrap();
+        let state = AppState::new(storage, None, Arc::new(cfg));
+
+        let request = Request::get("/_wasmio/metrics/buckets/bucket")
+            .body(Body::empty())
+            .unwrap();
+        let response = AppMapping::new(state)
+            .into_router()
+            .call(request)
+            .await
+            .unwrap();
+        response.status()
+    }
+
+    #[tokio::test]
+    async fn admin_routes_are_authenticated() {
+        assert_eq!(metrics(None).await, StatusCode::OK);
+
+        let auth = serde_json::json!({
+            "credentials": [{ "access_key": "admin", "secret_key": "secret" }],
+        });
+        assert_eq!(metrics(Some(auth)).await, StatusCode::FORBIDDEN);
+    }
+}
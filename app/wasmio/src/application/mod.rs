@@ -1,4 +1,5 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
 
 use tokio::net::TcpListener;
 use tokio::task::JoinHandle;
@@ -11,22 +12,55 @@ use state::AppState;
 mod mapping;
 use mapping::AppMapping;
 
-use crate::infrastructure::storage::FSStorage;
+use crate::domain::storage::errors::BucketStorageError;
+use crate::domain::storage::BackendDriver;
+use crate::infrastructure::config::{Cfg, WebsiteConfig};
+use crate::infrastructure::notification::Notifier;
+use crate::infrastructure::storage::BackendStorage;
 
 mod s3;
+use s3::state::S3State;
+use s3::website::mapping::WebsiteMapping;
 
 #[derive(Debug)]
-pub struct Application {
-    state: AppState,
+pub struct Application<T: BackendDriver> {
+    state: AppState<T>,
 }
 
-impl Application {
-    pub fn new(storage: FSStorage) -> Self {
+impl<T> Application<T>
+where
+    T: BackendDriver,
+    BucketStorageError: From<<T as BackendStorage>::Error>,
+{
+    pub fn new(storage: T, notifier: Option<Notifier>, cfg: Arc<Cfg>) -> Self {
         Self {
-            state: AppState::new(storage),
+            state: AppState::new(storage, notifier, cfg),
         }
     }
 
+    /// Serve the application until the server stops, alongside the website
+    /// endpoint when it's configured.
+    ///
+    /// It stops as soon as one of them fails, a website endpoint which can't
+    /// be served is not left behind.
+    pub async fn run(self) -> anyhow::Result<()> {
+        let website = self
+            .state
+            .cfg
+            .website
+            .clone()
+            .map(|website| self.serve_website(website));
+        let addr = self.state.cfg.bind_addr;
+        let server = self.serve(addr);
+        match website {
+            Some(website) => {
+                tokio::try_join!(joined(server), joined(website))?;
+            }
+            None => joined(server).await?,
+        }
+        Ok(())
+    }
+
     /// TODO: Proper shutdown process
     pub fn serve(self, addr: SocketAddr) -> JoinHandle<anyhow::Result<()>> {
         let app = AppMapping::new(self.state);
@@ -48,4 +82,34 @@ impl Application {
             Ok::<(), anyhow::Error>(())
         })
     }
+
+    /// Serve the website endpoint, see [WebsiteMapping].
+    pub fn serve_website(
+        &self,
+        WebsiteConfig { bind_addr, domain }: WebsiteConfig,
+    ) -> JoinHandle<anyhow::Result<()>> {
+        let router =
+            WebsiteMapping::new(S3State::from_state(&self.state), domain)
+                .into_router()
+                .layer(TraceLayer::new_for_http().make_span_with(
+                    DefaultMakeSpan::default().include_headers(true),
+                ));
+
+        tokio::spawn(async move {
+            let tcp = TcpListener::bind(&bind_addr).await?;
+            info!("Website endpoint starting at {bind_addr}");
+            axum::serve(
+                tcp,
+                router.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await?;
+
+            Ok::<(), anyhow::Error>(())
+        })
+    }
+}
+
+/// The result of a served endpoint, or why its task stopped.
+async fn joined(handle: JoinHandle<anyhow::Result<()>>) -> anyhow::Result<()> {
+    handle.await?
 }
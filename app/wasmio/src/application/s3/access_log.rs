@@ -0,0 +1,530 @@
+//! Server access logs of the buckets, in the format of S3.
+//!
+//! A line is buffered for every request on a bucket and the buffer is
+//! periodically written as a new object of the target bucket of each logging
+//! configuration. The lines of the buckets without logging configuration are
+//! dropped when the buffer is written.
+//!
+//! See [`Log record format`](https://docs.aws.amazon.com/AmazonS3/latest/userguide/LogFormat.html)
+use std::collections::HashMap;
+use std::mem;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::body::{Body, HttpBody};
+use axum::extract::ConnectInfo;
+use axum::http::{header, HeaderMap};
+use axum::response::Response;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+use tracing::{info, warn};
+use ulid::Ulid;
+use wasmio_aws_types::types::PutObjectRequestBuilder;
+
+use super::context::Context;
+use super::path::S3Path;
+use crate::domain::storage::errors::BucketStorageError;
+use crate::domain::storage::{BackendDriver, BucketStorage};
+use crate::infrastructure::config::AccessLogConfig;
+use crate::infrastructure::storage::BackendStorage;
+
+/// Value of the fields which don't apply to a request.
+const EMPTY: &str = "-";
+
+/// Name of the operation of the requests with these query parameters.
+const SUBRESOURCES: &[(&str, &str)] = &[
+    ("attributes", "OBJECT_ATTRIBUTES"),
+    ("encryption", "ENCRYPTION"),
+    ("location", "LOCATION"),
+    ("logging", "LOGGING_STATUS"),
+    ("notification", "NOTIFICATION"),
+    ("select", "SELECT"),
+    ("website", "WEBSITE"),
+];
+
+#[derive(Debug, Clone)]
+pub struct AccessLog {
+    inner: Arc<AccessLogInner>,
+}
+
+#[derive(Debug)]
+struct AccessLogInner {
+    buffer: Mutex<Buffer>,
+    max_lines: usize,
+    /// Notified when the buffer is full, to write it before the interval.
+    full: Notify,
+}
+
+#[derive(Debug, Default)]
+struct Buffer {
+    /// Lines by source bucket.
+    lines: HashMap<String, Vec<String>>,
+    len: usize,
+}
+
+impl AccessLog {
+    /// Start the periodic writes of the buffered lines with `storage`.
+    pub fn start<T: BackendDriver>(
+        storage: BucketStorage<T>,
+        cfg: &AccessLogConfig,
+    ) -> Self
+    where
+        BucketStorageError: From<<T as BackendStorage>::Error>,
+    {
+        let access_log = Self {
+            inner: Arc::new(AccessLogInner {
+                buffer: Mutex::new(Buffer::default()),
+                max_lines: cfg.max_buffered_lines.max(1),
+                full: Notify::new(),
+            }),
+        };
+        access_log
+            .spawn_flushes(storage, Duration::from_secs(cfg.flush_interval));
+
+        access_log
+    }
+
+    /// Buffer the line of a request, the requests outside of a bucket are
+    /// not logged.
+    pub fn record(
+        &self,
+        entry: AccessLogEntry,
+        response: &Response,
+        error_code: Option<String>,
+    ) {
+        let Some(bucket) = entry.bucket.clone() else {
+            return;
+        };
+        let line = entry.line(response, error_code);
+
+        let mut buffer = self.inner.buffer.lock();
+        buffer.lines.entry(bucket).or_default().push(line);
+        buffer.len += 1;
+        if buffer.len >= self.inner.max_lines {
+            self.inner.full.notify_one();
+        }
+    }
+
+    fn spawn_flushes<T: BackendDriver>(
+        &self,
+        storage: BucketStorage<T>,
+        interval: Duration,
+    ) where
+        BucketStorageError: From<<T as BackendStorage>::Error>,
+    {
+        let access_log = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => {}
+                    _ = access_log.inner.full.notified() => {}
+                }
+                access_log.flush(&storage).await;
+            }
+        });
+    }
+
+    /// Write the buffered lines of every bucket with a logging configuration
+    /// as a new object of its target bucket.
+    pub async fn flush<T: BackendDriver>(&self, storage: &BucketStorage<T>)
+    where
+        BucketStorageError: From<<T as BackendStorage>::Error>,
+    {
+        let lines = mem::take(&mut *self.inner.buffer.lock()).lines;
+
+        for (bucket, lines) in lines {
+            let target = match storage.logging_target(&bucket).await {
+                Ok(Some(target)) => target,
+                Ok(None) => continue,
+                Err(err) => {
+                    warn!(
+                        message = "Can't get the logging configuration",
+                        bucket = %bucket,
+                        error = %err,
+                    );
+                    continue;
+                }
+            };
+
+            let key = log_key(&target.target_prefix, Utc::now());
+            let mut content = lines.join("\n");
+            content.push('\n');
+            let request = PutObjectRequestBuilder::default()
+                .bucket(target.target_bucket.clone())
+                .key(key.clone())
+                .content_type(Some("text/plain".to_string()))
+                .content_length(Some(content.len() as i64))
+                .body(Some(Body::from(content).into_data_stream()))
+                .build()
+                .expect("can't fail");
+
+            match storage.put_object(request, None).await {
+                Ok(_) => info!(
+                    message = "Access logs written",
+                    bucket = %bucket,
+                    target = %target.target_bucket,
+                    key = %key,
+                    lines = lines.len(),
+                ),
+                Err(err) => warn!(
+                    message = "Can't write the access logs",
+                    bucket = %bucket,
+                    target = %target.target_bucket,
+                    error = %err,
+                ),
+            }
+        }
+    }
+}
+
+/// Key of a log object, `{prefix}YYYY-mm-DD-HH-MM-SS-{UniqueString}`.
+fn log_key(prefix: &str, now: DateTime<Utc>) -> String {
+    let unique = Ulid::new().random() as u64;
+    format!("{prefix}{}-{unique:016X}", now.format("%Y-%m-%d-%H-%M-%S"))
+}
+
+/// The request being logged, taken from the [Context] as it's consumed by
+/// the handler.
+#[derive(Debug)]
+pub struct AccessLogEntry {
+    started: Instant,
+    time: DateTime<Utc>,
+    bucket: Option<String>,
+    remote_ip: Option<String>,
+    requester: Option<String>,
+    request_id: String,
+    operation: String,
+    key: Option<String>,
+    request_uri: String,
+    referer: Option<String>,
+    user_agent: Option<String>,
+    host: Option<String>,
+    auth_type: Option<&'static str>,
+}
+
+impl AccessLogEntry {
+    pub fn new(ctx: &Context) -> Self {
+        let parts = ctx.parts();
+        let (bucket, key, resource) = match ctx.path() {
+            S3Path::Root => (None, None, "SERVICE"),
+            S3Path::Bucket { bucket } => (Some(bucket.clone()), None, "BUCKET"),
+            S3Path::Object { bucket, key } => {
+                (Some(bucket.clone()), Some(key.clone()), "OBJECT")
+            }
+        };
+        let resource = parts
+            .uri
+            .query()
+            .and_then(|query| {
+                SUBRESOURCES.iter().find_map(|(param, name)| {
+                    query
+                        .split('&')
+                        .map(|x| x.split('=').next().unwrap_or(x))
+                        .any(|x| x == *param)
+                        .then_some(*name)
+                })
+            })
+            .unwrap_or(resource);
+
+        let headers = &parts.headers;
+        let auth_type = if headers.contains_key(header::AUTHORIZATION) {
+            Some("AuthHeader")
+        } else if parts
+            .uri
+            .query()
+            .is_some_and(|x| x.contains("X-Amz-Signature="))
+        {
+            Some("QueryString")
+        } else {
+            None
+        };
+
+        Self {
+            started: Instant::now(),
+            time: Utc::now(),
+            bucket,
+            remote_ip: parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|x| x.0.ip().to_string()),
+            requester: None,
+            request_id: ctx.request_id().to_string(),
+            operation: format!("REST.{}.{resource}", ctx.method()),
+            key,
+            request_uri: format!(
+                "{} {} {:?}",
+                ctx.method(),
+                parts.uri.path_and_query().map_or("/", |x| x.as_str()),
+                parts.version
+            ),
+            referer: header_value(headers, header::REFERER),
+            user_agent: header_value(headers, header::USER_AGENT),
+            host: header_value(headers, header::HOST),
+            auth_type,
+        }
+    }
+
+    /// Set the requester once the request is authenticated.
+    pub fn authenticated(&mut self, ctx: &Context) {
+        self.requester = ctx.authentication().map(|x| x.access_key.clone());
+    }
+
+    /// The line of the request in the S3 format, the fields which are not
+    /// known are `-`.
+    fn line(&self, response: &Response, error_code: Option<String>) -> String {
+        let elapsed = self.started.elapsed().as_millis().to_string();
+        let bytes_sent = response
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|x| x.to_str().ok())
+            .and_then(|x| x.parse::<u64>().ok())
+            .or_else(|| response.body().size_hint().exact())
+            .filter(|x| *x > 0)
+            .map(|x| x.to_string());
+
+        let fields = [
+            // Bucket owner, there are no accounts.
+            field(None),
+            field(self.bucket.clone()),
+            format!("[{}]", self.time.format("%d/%b/%Y:%H:%M:%S %z")),
+            field(self.remote_ip.clone()),
+            field(self.requester.clone()),
+            self.request_id.clone(),
+            self.operation.clone(),
+            field(
+                self.key
+                    .as_ref()
+                    .map(|x| urlencoding::encode(x).into_owned()),
+            ),
+            quoted(Some(&self.request_uri)),
+            response.status().as_u16().to_string(),
+            field(error_code),
+            field(bytes_sent),
+            // Object size, not known once the request is handled.
+            field(None),
+            // Total time, the body is still being sent.
+            elapsed.clone(),
+            // Turn-around time.
+            elapsed,
+            quoted(self.referer.as_deref()),
+            quoted(self.user_agent.as_deref()),
+            // Version ID.
+            field(None),
+            // Host ID.
+            field(None),
+            field(self.auth_type.map(|_| "SigV4".to_string())),
+            // Cipher suite, the requests are not using TLS.
+            field(None),
+            field(self.auth_type.map(str::to_string)),
+            field(self.host.clone()),
+            // TLS version.
+            field(None),
+            // Access point ARN.
+            field(None),
+            // ACL required.
+            field(None),
+        ];
+
+        fields.join(" ")
+    }
+}
+
+fn header_value(
+    headers: &HeaderMap,
+    name: header::HeaderName,
+) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|x| x.to_str().ok())
+        .map(str::to_string)
+}
+
+fn field(value: Option<String>) -> String {
+    value
+        .filter(|x| !x.is_empty())
+        .unwrap_or_else(|| EMPTY.to_string())
+}
+
+/// A field which can contain spaces.
+fn quoted(value: Option<&str>) -> String {
+    match value {
+        Some(x) => format!("\"{}\"", x.replace('"', "\\\"")),
+        None => format!("\"{EMPTY}\""),
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), not(target_os = "wasi")))]
+mod tests {
+    use axum::body::to_bytes;
+    use axum::http::{Request, StatusCode};
+    use wasmio_aws_types::types::{
+        BucketLoggingStatus, CreateBucketRequestBuilder,
+        GetObjectRequestBuilder, ListObjectsV2RequestBuilder, LoggingEnabled,
+        PutBucketLoggingRequestBuilder,
+    };
+
+    use super::*;
+    use crate::infrastructure::storage::FSStorage;
+
+    fn context(uri: &str) -> Context {
+        let mut request = Request::get(uri)
+            .header(header::HOST, "localhost:8000")
+            .header(header::USER_AGENT, "aws-cli/2.0 \"test\"")
+            .header(header::AUTHORIZATION, "AWS4-HMAC-SHA256 ...")
+            .body(Body::empty())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 4242))));
+        Context::new(request, None).unwrap()
+    }
+
+    fn access_log() -> AccessLog {
+        AccessLog {
+            inner: Arc::new(AccessLogInner {
+                buffer: Mutex::new(Buffer::default()),
+                max_lines: 100,
+                full: Notify::new(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_log_line() {
+        let ctx = context("/bucket/some%20key?attributes");
+        let entry = AccessLogEntry::new(&ctx);
+        let response = Response::new(Body::from("hello"));
+        let line = entry.line(&response, None);
+
+        let request_id = ctx.request_id();
+        assert!(line.starts_with("- bucket ["), "{line}");
+        assert!(
+            line.contains(&format!(
+                "] 127.0.0.1 - {request_id} REST.GET.OBJECT_ATTRIBUTES \
+                 some%20key \"GET /bucket/some%20key?attributes HTTP/1.1\" \
+                 200 - 5 - "
+            )),
+            "{line}"
+        );
+        assert!(
+            line.ends_with(
+                " \"-\" \"aws-cli/2.0 \\\"test\\\"\" - - SigV4 - AuthHeader \
+                 localhost:8000 - - -"
+            ),
+            "{line}"
+        );
+
+        let entry = AccessLogEntry::new(&context("/bucket/"));
+        let response = Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())
+            .unwrap();
+        let line = entry.line(&response, Some("NoSuchBucket".to_string()));
+        assert!(
+            line.contains(
+                " REST.GET.BUCKET - \"GET /bucket/ HTTP/1.1\" 404 \
+                 NoSuchBucket - - "
+            ),
+            "{line}"
+        );
+    }
+
+    #[test]
+    fn test_log_key() {
+        let now = DateTime::parse_from_rfc3339("2024-02-03T04:05:06Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let key = log_key("logs/", now);
+        assert!(key.starts_with("logs/2024-02-03-04-05-06-"), "{key}");
+        assert_eq!(key.len(), "logs/2024-02-03-04-05-06-".len() + 16);
+    }
+
+    #[tokio::test]
+    async fn test_flush() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = BucketStorage::new(FSStorage::new(dir.path().into()));
+        for bucket in ["source", "other", "logs"] {
+            storage
+                .create_new_bucket(
+                    CreateBucketRequestBuilder::default()
+                        .bucket(bucket)
+                        .build()
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let logging = |target: &str| {
+            PutBucketLoggingRequestBuilder::default()
+                .bucket("source")
+                .bucket_logging_status(BucketLoggingStatus {
+                    logging_enabled: Some(LoggingEnabled {
+                        target_bucket: target.to_string(),
+                        target_prefix: "access-".to_string(),
+                    }),
+                })
+                .build()
+                .unwrap()
+        };
+        let err = storage.put_bucket_logging(logging("missing")).await;
+        assert!(matches!(
+            err,
+            Err(BucketStorageError::InvalidTargetBucketForLogging)
+        ));
+        storage.put_bucket_logging(logging("logs")).await.unwrap();
+
+        // The lines of `other` are dropped as it has no logging
+        // configuration.
+        let access_log = access_log();
+        for uri in ["/source/a", "/source/b", "/other/c", "/"] {
+            let response = Response::new(Body::empty());
+            access_log.record(
+                AccessLogEntry::new(&context(uri)),
+                &response,
+                None,
+            );
+        }
+        access_log.flush(&storage).await;
+        assert_eq!(access_log.inner.buffer.lock().len, 0);
+
+        let contents = |bucket: &str| {
+            let storage = storage.clone();
+            let request = ListObjectsV2RequestBuilder::default()
+                .bucket(bucket)
+                .build()
+                .unwrap();
+            async move {
+                storage
+                    .list_object_v2(request)
+                    .await
+                    .unwrap()
+                    .contents
+                    .unwrap_or_default()
+            }
+        };
+        let logs = contents("logs").await;
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].key.as_ref().unwrap().starts_with("access-"));
+        assert!(contents("other").await.is_empty());
+
+        let body = storage
+            .get_object(
+                GetObjectRequestBuilder::default()
+                    .bucket("logs")
+                    .key(logs[0].key.clone().unwrap())
+                    .build()
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .body
+            .unwrap();
+        let content = to_bytes(body, usize::MAX).await.unwrap();
+        let content = String::from_utf8(content.to_vec()).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(content.lines().all(|x| x.starts_with("- source [")));
+    }
+}
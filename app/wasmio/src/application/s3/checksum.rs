@@ -0,0 +1,75 @@
+//! Additional checksums sent with `x-amz-checksum-*` headers or trailers.
+use axum::http::HeaderMap;
+
+use super::axum::header_string_opt;
+use super::chunked::AwsChunked;
+use super::errors::S3Error;
+use super::headers::X_AMZ_SDK_CHECKSUM_ALGORITHM;
+use crate::infrastructure::storage::checksum::{
+    ChecksumAlgorithm, ChecksumRequest, ExpectedChecksum,
+};
+
+const INVALID_ALGORITHM: &str =
+    "Value for x-amz-sdk-checksum-algorithm header is invalid.";
+
+/// Find the checksum requested for the payload, when it's sent as a trailer
+/// the decoder fills the expected value once the payload is read.
+pub fn checksum_request(
+    map: &HeaderMap,
+    chunked: Option<&mut AwsChunked>,
+) -> Result<Option<ChecksumRequest>, S3Error> {
+    let sdk_algorithm = header_string_opt(X_AMZ_SDK_CHECKSUM_ALGORITHM, map)
+        .map(|x| x.parse::<ChecksumAlgorithm>())
+        .transpose()
+        .map_err(|_| S3Error::invalid_request(INVALID_ALGORITHM))?;
+
+    let mut from_headers = map
+        .keys()
+        .filter_map(|x| ChecksumAlgorithm::from_header_name(x.as_str()));
+    let header_algorithm = from_headers.next();
+    if from_headers.next().is_some() {
+        return Err(S3Error::invalid_request(
+            "Expecting a single x-amz-checksum- header. Multiple checksum \
+             Types are not allowed.",
+        ));
+    }
+
+    let trailer_algorithm = chunked.as_ref().and_then(|x| x.trailer_checksum());
+
+    let (algorithm, expected) = match (header_algorithm, trailer_algorithm) {
+        (Some(_), Some(_)) => {
+            return Err(S3Error::invalid_request(
+                "Expecting a single x-amz-checksum- header. Multiple checksum \
+                 Types are not allowed.",
+            ));
+        }
+        (Some(algorithm), None) => (
+            algorithm,
+            ExpectedChecksum::new(header_string_opt(
+                algorithm.header_name(),
+                map,
+            )),
+        ),
+        (None, Some(algorithm)) => {
+            let expected = ExpectedChecksum::default();
+            if let Some(chunked) = chunked {
+                chunked.set_expected_checksum(expected.clone());
+            }
+            (algorithm, expected)
+        }
+        // The checksum is only computed.
+        (None, None) => match sdk_algorithm {
+            Some(algorithm) => (algorithm, ExpectedChecksum::default()),
+            None => return Ok(None),
+        },
+    };
+
+    if sdk_algorithm.is_some_and(|x| x != algorithm) {
+        return Err(S3Error::invalid_request(INVALID_ALGORITHM));
+    }
+
+    Ok(Some(ChecksumRequest {
+        algorithm,
+        expected,
+    }))
+}
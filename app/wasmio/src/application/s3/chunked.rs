@@ -0,0 +1,509 @@
+//! Decoding of the `aws-chunked` content encoding, used by the SDKs to stream
+//! a payload with a signature for each chunk.
+//!
+//! See [`Transferring Payload in Multiple Chunks`](https://docs.aws.amazon.com/AmazonS3/latest/API/sigv4-streaming.html)
+use axum::body::{Body, BodyDataStream};
+use axum::http::header::CONTENT_ENCODING;
+use axum::http::HeaderMap;
+use bytes::{Buf, Bytes, BytesMut};
+use futures::{Stream, StreamExt};
+
+use super::auth::sigv4::{
+    self, STREAMING_PAYLOAD, STREAMING_PAYLOAD_TRAILER,
+    STREAMING_UNSIGNED_PAYLOAD_TRAILER,
+};
+use super::auth::Authentication;
+use super::axum::{header_parse, header_string_opt};
+use super::context::Context;
+use super::errors::S3Error;
+use super::headers::{
+    X_AMZ_CONTENT_SHA256, X_AMZ_DECODED_CONTENT_LENGTH, X_AMZ_TRAILER,
+};
+use crate::domain::storage::errors::BucketStorageError;
+use crate::infrastructure::storage::checksum::{
+    ChecksumAlgorithm, ExpectedChecksum,
+};
+
+const AWS_CHUNKED: &str = "aws-chunked";
+const TRAILER_SIGNATURE: &str = "x-amz-trailer-signature";
+
+/// The SDKs send chunks of 64KiB, bigger chunks are refused as they are kept
+/// in memory until their signature is checked.
+const MAX_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+/// Maximum size of a chunk header or of a trailing header.
+const MAX_LINE_SIZE: usize = 4 * 1024;
+
+/// Verify the signatures of the chunks, each signature is chained to the
+/// previous one, starting from the signature of the request.
+struct ChunkSigner {
+    signing_key: Vec<u8>,
+    amz_date: String,
+    scope: String,
+    previous: String,
+}
+
+impl ChunkSigner {
+    fn new(auth: &Authentication) -> Self {
+        Self {
+            signing_key: auth.signing_key.clone(),
+            amz_date: auth.amz_date.clone(),
+            scope: auth.scope.clone(),
+            previous: auth.signature.clone(),
+        }
+    }
+
+    fn verify(&mut self, string_to_sign: String, signature: &str) -> bool {
+        if sigv4::verify(&self.signing_key, &string_to_sign, signature) {
+            self.previous = signature.to_string();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn verify_chunk(&mut self, chunk: &[u8], signature: &str) -> bool {
+        let string_to_sign = sigv4::chunk_string_to_sign(
+            &self.amz_date,
+            &self.scope,
+            &self.previous,
+            chunk,
+        );
+        self.verify(string_to_sign, signature)
+    }
+
+    fn verify_trailer(&mut self, trailers: &str, signature: &str) -> bool {
+        let string_to_sign = sigv4::trailer_string_to_sign(
+            &self.amz_date,
+            &self.scope,
+            &self.previous,
+            trailers,
+        );
+        self.verify(string_to_sign, signature)
+    }
+}
+
+/// A streamed payload encoded with `aws-chunked`.
+pub struct AwsChunked {
+    /// Whether each chunk carries a signature.
+    signed: bool,
+    /// Set when the signatures are verified, only when the request is
+    /// authenticated.
+    signer: Option<ChunkSigner>,
+    /// The trailing headers announced with `x-amz-trailer`.
+    trailers: Vec<String>,
+    decoded_content_length: Option<u64>,
+    /// Receive the value of the `x-amz-checksum-*` trailer.
+    expected_checksum: Option<ExpectedChecksum>,
+}
+
+impl AwsChunked {
+    /// Returns `None` when the payload of the request is not streamed.
+    pub fn from_context(ctx: &Context) -> Result<Option<Self>, S3Error> {
+        let map = &ctx.parts().headers;
+
+        let signed =
+            match header_string_opt(X_AMZ_CONTENT_SHA256, map).as_deref() {
+                Some(STREAMING_PAYLOAD | STREAMING_PAYLOAD_TRAILER) => true,
+                Some(STREAMING_UNSIGNED_PAYLOAD_TRAILER) => false,
+                Some(x) if x.starts_with("STREAMING-") => {
+                    return Err(S3Error::invalid_request(
+                        "The streaming payload is not supported.",
+                    ));
+                }
+                _ if is_aws_chunked(map) => false,
+                _ => return Ok(None),
+            };
+
+        let decoded_content_length =
+            header_parse(X_AMZ_DECODED_CONTENT_LENGTH, map).map_err(|_| {
+                S3Error::invalid_request(
+                    "Invalid header: x-amz-decoded-content-length",
+                )
+            })?;
+
+        let trailers = header_string_opt(X_AMZ_TRAILER, map)
+            .map(|x| {
+                x.split(',')
+                    .map(|x| x.trim().to_ascii_lowercase())
+                    .filter(|x| !x.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let signer = ctx
+            .authentication()
+            .filter(|_| signed)
+            .map(ChunkSigner::new);
+
+        Ok(Some(Self {
+            signed,
+            signer,
+            trailers,
+            decoded_content_length,
+            expected_checksum: None,
+        }))
+    }
+
+    pub fn decoded_content_length(&self) -> Option<u64> {
+        self.decoded_content_length
+    }
+
+    /// The algorithm of the checksum announced as a trailer.
+    pub fn trailer_checksum(&self) -> Option<ChecksumAlgorithm> {
+        self.trailers
+            .iter()
+            .find_map(|x| ChecksumAlgorithm::from_header_name(x))
+    }
+
+    /// Set the checksum receiving the value of the trailer once the payload
+    /// is decoded.
+    pub fn set_expected_checksum(&mut self, expected: ExpectedChecksum) {
+        self.expected_checksum = Some(expected);
+    }
+
+    /// Strip the chunk framing, the body fails when the payload is malformed
+    /// or when a signature doesn't match.
+    pub fn decode(self, body: Body) -> Body {
+        Body::from_stream(self.decode_stream(body.into_data_stream()))
+    }
+
+    fn decode_stream(
+        mut self,
+        stream: BodyDataStream,
+    ) -> impl Stream<Item = Result<Bytes, axum::Error>> {
+        let mut reader = ChunkReader {
+            stream,
+            buffer: BytesMut::new(),
+        };
+
+        async_stream::try_stream! {
+            let mut decoded = 0u64;
+            loop {
+                let header = reader.line().await?;
+                let (size, signature) = parse_chunk_header(&header, self.signed)?;
+                let chunk = reader.bytes(size).await?;
+
+                if let (Some(signer), Some(signature)) = (&mut self.signer, signature) {
+                    if !signer.verify_chunk(&chunk, signature) {
+                        Err(invalid(BucketStorageError::ChunkSignatureMismatch))?;
+                    }
+                }
+
+                if size == 0 {
+                    break;
+                }
+
+                reader.crlf().await?;
+                decoded += size as u64;
+                yield chunk;
+            }
+
+            self.read_trailers(&mut reader).await?;
+
+            if let Some(expected) = self.decoded_content_length {
+                if expected != decoded {
+                    Err(invalid(BucketStorageError::IncompleteBody))?;
+                }
+            }
+        }
+    }
+
+    /// Read the trailing headers until the final empty line.
+    async fn read_trailers(
+        &mut self,
+        reader: &mut ChunkReader,
+    ) -> Result<(), axum::Error> {
+        let mut canonical = String::new();
+        let mut received = Vec::new();
+        let mut signature = None;
+        let mut checksum = None;
+
+        loop {
+            let line = reader.line().await?;
+            if line.is_empty() {
+                break;
+            }
+
+            let (name, value) = line
+                .split_once(':')
+                .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim()))
+                .ok_or_else(|| invalid(BucketStorageError::MalformedTrailer))?;
+
+            if name == TRAILER_SIGNATURE {
+                signature = Some(value.to_string());
+                continue;
+            }
+
+            if !self.trailers.contains(&name) {
+                return Err(invalid(BucketStorageError::MalformedTrailer));
+            }
+            if ChecksumAlgorithm::from_header_name(&name).is_some() {
+                checksum = Some(value.to_string());
+            }
+            canonical.push_str(&format!("{name}:{value}\n"));
+            received.push(name);
+        }
+
+        if self.trailers.iter().any(|x| !received.contains(x)) {
+            return Err(invalid(BucketStorageError::MalformedTrailer));
+        }
+
+        if let Some(signer) = &mut self.signer {
+            if self.trailers.is_empty() {
+                return Ok(());
+            }
+
+            let signature = signature
+                .ok_or_else(|| invalid(BucketStorageError::MalformedTrailer))?;
+            if !signer.verify_trailer(&canonical, &signature) {
+                return Err(invalid(
+                    BucketStorageError::ChunkSignatureMismatch,
+                ));
+            }
+        }
+
+        if let (Some(expected), Some(checksum)) =
+            (&self.expected_checksum, checksum)
+        {
+            expected.set(checksum);
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether `Content-Encoding` holds `aws-chunked`.
+fn is_aws_chunked(map: &HeaderMap) -> bool {
+    header_string_opt(CONTENT_ENCODING, map)
+        .is_some_and(|x| x.split(',').any(|x| x.trim() == AWS_CHUNKED))
+}
+
+/// The `Content-Encoding` of the decoded payload, without `aws-chunked`.
+pub fn content_encoding(map: &HeaderMap) -> Option<String> {
+    let encoding = header_string_opt(CONTENT_ENCODING, map)?
+        .split(',')
+        .map(str::trim)
+        .filter(|x| !x.is_empty() && *x != AWS_CHUNKED)
+        .collect::<Vec<_>>()
+        .join(",");
+
+    (!encoding.is_empty()).then_some(encoding)
+}
+
+/// Parse `<hex-size>[;chunk-signature=<signature>]`.
+fn parse_chunk_header(
+    header: &str,
+    signed: bool,
+) -> Result<(usize, Option<&str>), axum::Error> {
+    let (size, extension) = match header.split_once(';') {
+        Some((size, extension)) => (size, Some(extension)),
+        None => (header, None),
+    };
+
+    let size = usize::from_str_radix(size.trim(), 16)
+        .ok()
+        .filter(|x| *x <= MAX_CHUNK_SIZE)
+        .ok_or_else(|| invalid(BucketStorageError::InvalidChunk))?;
+
+    let signature = extension
+        .and_then(|x| x.trim().strip_prefix("chunk-signature="))
+        .map(str::trim);
+    if signed && signature.is_none() {
+        return Err(invalid(BucketStorageError::InvalidChunk));
+    }
+
+    Ok((size, signature))
+}
+
+fn invalid(err: BucketStorageError) -> axum::Error {
+    axum::Error::new(err)
+}
+
+struct ChunkReader {
+    stream: BodyDataStream,
+    buffer: BytesMut,
+}
+
+impl ChunkReader {
+    async fn fill(&mut self) -> Result<(), axum::Error> {
+        match self.stream.next().await {
+            Some(Ok(bytes)) => {
+                self.buffer.extend_from_slice(&bytes);
+                Ok(())
+            }
+            Some(Err(err)) => Err(err),
+            None => Err(invalid(BucketStorageError::IncompleteBody)),
+        }
+    }
+
+    /// Read a line ending with `\r\n`.
+    async fn line(&mut self) -> Result<String, axum::Error> {
+        loop {
+            if let Some(pos) = self.buffer.windows(2).position(|x| x == b"\r\n")
+            {
+                let line = self.buffer.split_to(pos);
+                self.buffer.advance(2);
+                return String::from_utf8(line.to_vec())
+                    .map_err(|_| invalid(BucketStorageError::InvalidChunk));
+            }
+
+            if self.buffer.len() > MAX_LINE_SIZE {
+                return Err(invalid(BucketStorageError::InvalidChunk));
+            }
+            self.fill().await?;
+        }
+    }
+
+    async fn bytes(&mut self, size: usize) -> Result<Bytes, axum::Error> {
+        while self.buffer.len() < size {
+            self.fill().await?;
+        }
+        Ok(self.buffer.split_to(size).freeze())
+    }
+
+    async fn crlf(&mut self) -> Result<(), axum::Error> {
+        if self.bytes(2).await?.as_ref() != b"\r\n" {
+            return Err(invalid(BucketStorageError::InvalidChunk));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), not(target_os = "wasi")))]
+mod tests {
+    use http_body_util::BodyExt;
+
+    use super::*;
+
+    fn decoder(signer: Option<ChunkSigner>, trailers: &[&str]) -> AwsChunked {
+        AwsChunked {
+            signed: signer.is_some(),
+            signer,
+            trailers: trailers.iter().map(|x| x.to_string()).collect(),
+            decoded_content_length: None,
+            expected_checksum: None,
+        }
+    }
+
+    /// Signer of the example of the AWS documentation.
+    fn signer() -> ChunkSigner {
+        ChunkSigner {
+            signing_key: sigv4::signing_key(
+                "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY",
+                "20130524",
+                "us-east-1",
+                "s3",
+            ),
+            amz_date: "20130524T000000Z".to_string(),
+            scope: "20130524/us-east-1/s3/aws4_request".to_string(),
+            previous:
+                "4f232c4386841ef735655705268965c44a0e4690baa4adea153f7db9fa80a0a9"
+                    .to_string(),
+        }
+    }
+
+    fn signed_body(last_signature: &str) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(b"10000;chunk-signature=ad80c730a21e5b8d04586a2213dd63b9a0e99e0e2307b0ade35a65485a288648\r\n");
+        body.extend_from_slice(&[b'a'; 65536]);
+        body.extend_from_slice(b"\r\n400;chunk-signature=0055627c9e194cb4542bae2aa5492e3c1575bbb81b612b7d234b86a503ef5497\r\n");
+        body.extend_from_slice(&[b'a'; 1024]);
+        body.extend_from_slice(
+            format!("\r\n0;chunk-signature={last_signature}\r\n\r\n")
+                .as_bytes(),
+        );
+        body
+    }
+
+    async fn collect(body: Body) -> Result<Vec<u8>, BucketStorageError> {
+        body.collect()
+            .await
+            .map(|x| x.to_bytes().to_vec())
+            .map_err(|err| {
+                let mut source: Option<&(dyn std::error::Error + 'static)> =
+                    Some(&err);
+                while let Some(err) = source {
+                    if let Some(err) = err.downcast_ref::<BucketStorageError>()
+                    {
+                        return err.clone();
+                    }
+                    source = err.source();
+                }
+                BucketStorageError::Unknown
+            })
+    }
+
+    #[tokio::test]
+    async fn decode_signed_chunks() {
+        let body = signed_body(
+            "b6c6ea8a5354eaf15b3cb7646744f4275b71ea724fed81ceb9323e279d449df9",
+        );
+
+        let decoded = collect(decoder(Some(signer()), &[]).decode(body.into()))
+            .await
+            .unwrap();
+        assert_eq!(decoded, vec![b'a'; 66560]);
+    }
+
+    #[tokio::test]
+    async fn reject_wrong_chunk_signature() {
+        let body = signed_body(
+            "0000000000000000000000000000000000000000000000000000000000000000",
+        );
+
+        let result =
+            collect(decoder(Some(signer()), &[]).decode(body.into())).await;
+        insta::assert_debug_snapshot!(result, @r###"
+        Err(
+            ChunkSignatureMismatch,
+        )
+        "###);
+    }
+
+    #[tokio::test]
+    async fn decode_unsigned_chunks_with_trailer() {
+        let body = "5\r\nhello\r\n6\r\n \
+                    world\r\n0\r\nx-amz-checksum-crc32:DUoRhQ==\r\n\r\n";
+
+        let mut decoder = decoder(None, &["x-amz-checksum-crc32"]);
+        assert_eq!(decoder.trailer_checksum(), Some(ChecksumAlgorithm::CRC32));
+        let expected = ExpectedChecksum::default();
+        decoder.set_expected_checksum(expected.clone());
+
+        let decoded = collect(decoder.decode(body.into())).await.unwrap();
+        assert_eq!(decoded, b"hello world");
+        assert_eq!(expected.get().as_deref(), Some("DUoRhQ=="));
+    }
+
+    #[tokio::test]
+    async fn reject_missing_trailer_and_truncated_body() {
+        let body = "5\r\nhello\r\n0\r\n\r\n";
+        let result = collect(
+            decoder(None, &["x-amz-checksum-crc32"]).decode(body.into()),
+        )
+        .await;
+        insta::assert_debug_snapshot!(result, @r###"
+        Err(
+            MalformedTrailer,
+        )
+        "###);
+
+        let body = "5\r\nhel";
+        let result = collect(decoder(None, &[]).decode(body.into())).await;
+        insta::assert_debug_snapshot!(result, @r###"
+        Err(
+            IncompleteBody,
+        )
+        "###);
+    }
+
+    #[test]
+    fn content_encoding_without_aws_chunked() {
+        let mut map = HeaderMap::new();
+        map.insert(CONTENT_ENCODING, "aws-chunked".parse().unwrap());
+        assert_eq!(content_encoding(&map), None);
+
+        map.insert(CONTENT_ENCODING, "aws-chunked, gzip".parse().unwrap());
+        assert_eq!(content_encoding(&map), Some("gzip".to_string()));
+    }
+}
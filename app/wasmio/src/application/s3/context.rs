@@ -1,11 +1,12 @@
 use axum::async_trait;
 use axum::body::Body;
 use axum::http::request::Parts;
-use axum::http::{Method, Request};
+use axum::http::{header, Method, Request};
 use axum::response::Response;
 use tracing::error;
 use ulid::Ulid;
 
+use super::auth::Authentication;
 use super::errors::{S3Error, S3HTTPError};
 use super::path::S3Path;
 use super::state::S3State;
@@ -20,21 +21,33 @@ pub struct Context {
     parts: Parts,
     pub body: Body,
     path: S3Path,
+    /// The bucket is in the host instead of the path.
+    virtual_hosted: bool,
+    /// Set when the request has been authenticated.
+    authentication: Option<Authentication>,
 }
 
 impl Context {
-    pub fn new(request: Request<Body>) -> Result<Self, S3HTTPError> {
+    /// `domain` is the base domain of the virtual-hosted-style requests.
+    pub fn new(
+        request: Request<Body>,
+        domain: Option<&str>,
+    ) -> Result<Self, S3HTTPError> {
         let request_id = Ulid::new();
 
         let (parts, body) = request.into_parts();
 
-        let path = S3Path::from_part(&request_id, &parts)?;
+        let path = S3Path::from_part(&request_id, &parts, domain)?;
+        let virtual_hosted =
+            S3Path::virtual_host_bucket(&parts, domain).is_some();
 
         Ok(Self {
             request_id,
             parts,
             body,
             path,
+            virtual_hosted,
+            authentication: None,
         })
     }
 
@@ -46,6 +59,10 @@ impl Context {
         &self.path
     }
 
+    pub fn is_virtual_hosted(&self) -> bool {
+        self.virtual_hosted
+    }
+
     pub fn expect_bucket(&self) -> Result<&String, S3Error> {
         match &self.path {
             S3Path::Bucket { bucket } => Ok(bucket),
@@ -85,6 +102,28 @@ impl Context {
     pub fn method(&self) -> &Method {
         &self.parts.method
     }
+
+    pub fn authentication(&self) -> Option<&Authentication> {
+        self.authentication.as_ref()
+    }
+
+    pub fn set_authentication(&mut self, authentication: Authentication) {
+        self.authentication = Some(authentication);
+    }
+
+    /// A `POST` of a form on a bucket, to upload an object from a browser.
+    pub fn is_browser_upload(&self) -> bool {
+        self.parts.method == Method::POST
+            && self.path.is_bucket()
+            && self
+                .parts
+                .headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|x| x.to_str().ok())
+                .is_some_and(|x| {
+                    x.to_ascii_lowercase().starts_with("multipart/form-data")
+                })
+    }
 }
 
 /// The routing of S3 is based on URI matching, it conflicts with the usual way
@@ -6,7 +6,11 @@ use axum::response::{IntoResponse, Response};
 use serde::{Deserialize, Serialize};
 use tracing::error;
 
+use super::auth::policy::PolicyError;
+use super::headers::X_AMZ_BUCKET_REGION;
+use crate::domain::select::SelectError;
 use crate::domain::storage::errors::BucketStorageError;
+use crate::infrastructure::storage::encryption::CustomerKeyError;
 
 /// S3 partiel error code enum
 ///
@@ -15,16 +19,69 @@ use crate::domain::storage::errors::BucketStorageError;
 #[allow(clippy::upper_case_acronyms)]
 #[non_exhaustive]
 pub enum S3ErrorCodeKind {
+    /// Access Denied
+    AccessDenied,
+    /// The authorization header that you provided is not valid.
+    AuthorizationHeaderMalformed,
+    /// The query parameters of the presigned URL are not valid.
+    AuthorizationQueryParametersError,
+    /// Attempt to convert from one data type to another using CAST failed
+    /// in the SQL expression.
+    CastFailed,
+    /// Encountered an error parsing the CSV file.
+    CSVParsingError,
     /// The requested bucket name is not available. The bucket namespace is
     /// shared by all users of the system. Please select a different name and
     /// try again.
     BucketAlreadyExists,
+    /// The Content-MD5 or checksum value that you specified did not match
+    /// what the server received.
+    BadDigest,
+    /// Your proposed upload exceeds the maximum allowed object size.
+    EntityTooLarge,
+    /// Your proposed upload is smaller than the minimum allowed object size.
+    EntityTooSmall,
+    /// The arguments of an operation in the SQL expression are not valid.
+    EvaluatorInvalidArguments,
+    /// Integer overflow or underflow in the SQL expression.
+    IntegerOverflow,
+    /// Invalid Argument.
+    InvalidArgument,
+    /// The file is not in a supported compression format.
+    InvalidCompressionFormat,
+    /// The ExpressionType value is not valid. Only SQL expressions are
+    /// supported.
+    InvalidExpressionType,
+    /// The requested part number is greater than the number of parts of the
+    /// object.
+    InvalidPartNumber,
+    /// The requested range can't be satisfied.
+    InvalidRange,
+    /// The FileHeaderInfo value is not valid. Only NONE, USE, and IGNORE are
+    /// supported.
+    InvalidFileHeaderInfo,
     /// The specified bucket is not valid.
     InvalidBucketName,
     /// An internal error occurred. Try again.
     InternalError,
+    /// The AWS access key ID that you provided does not exist in our records.
+    InvalidAccessKeyId,
+    /// You did not provide the number of bytes specified by the
+    /// Content-Length HTTP header.
+    IncompleteBody,
+    /// The content of the form does not meet the conditions specified in the
+    /// policy document.
+    InvalidPolicyDocument,
+    /// The location constraint doesn't match the region of the server.
+    IllegalLocationConstraintException,
+    /// The value of a parameter in SelectRequest element is not valid.
+    InvalidRequestParameter,
+    /// The target bucket for logging doesn't exist.
+    InvalidTargetBucketForLogging,
     /// Couldn't parse the specified URI.
     InvalidURI,
+    /// Encountered an error parsing the JSON file.
+    JSONParsingError,
     /// Your key is too long.
     KeyTooLongError,
     /// This error might occur for the following reasons:
@@ -62,51 +119,267 @@ pub enum S3ErrorCodeKind {
     /// is, \"The XML you provided was not well-formed or did not validate
     /// against our published schema.\"
     MalformedXML,
+    /// The body of your POST request is not well-formed
+    /// multipart/form-data.
+    MalformedPOSTRequest,
+    /// The request contained trailing data that was not well-formed or did
+    /// not conform to our published schema.
+    MalformedTrailerError,
+    /// The Content-MD5 or checksum value that you specified is not valid.
+    InvalidDigest,
     /// The specified bucket does not exist.
     NoSuchBucket,
     /// The specified key does not exist.
     NoSuchKey,
+    /// The specified bucket does not have a website configuration.
+    NoSuchWebsiteConfiguration,
+    /// InputSerialization or OutputSerialization doesn't specify exactly one
+    /// format.
+    ObjectSerializationConflict,
+    /// The SQL expression contains an unexpected token.
+    ParseUnexpectedToken,
+    /// The SQL expression contains unsupported syntax.
+    ParseUnsupportedSyntax,
+    /// The difference between the request time and the server's time is too
+    /// large.
+    RequestTimeTooSkewed,
+    /// The server side encryption configuration was not found.
+    ServerSideEncryptionConfigurationNotFoundError,
+    /// The request signature that the server calculated does not match the
+    /// signature that you provided. Check your AWS secret access key and
+    /// signing method.
+    SignatureDoesNotMatch,
+    /// Encountered an unsupported SQL function.
+    UnsupportedFunction,
+    /// Scan range queries are not supported on this type of object.
+    UnsupportedScanRangeInput,
+    /// The provided `x-amz-content-sha256` header does not match what was
+    /// computed.
+    XAmzContentSHA256Mismatch,
 }
 
 impl S3ErrorCodeKind {
-    const fn status_code(&self) -> StatusCode {
+    pub(crate) const fn status_code(&self) -> StatusCode {
         match self {
+            S3ErrorCodeKind::AccessDenied => StatusCode::FORBIDDEN,
+            S3ErrorCodeKind::AuthorizationHeaderMalformed => {
+                StatusCode::BAD_REQUEST
+            }
+            S3ErrorCodeKind::AuthorizationQueryParametersError => {
+                StatusCode::BAD_REQUEST
+            }
+            S3ErrorCodeKind::BadDigest => StatusCode::BAD_REQUEST,
+            S3ErrorCodeKind::CastFailed => StatusCode::BAD_REQUEST,
+            S3ErrorCodeKind::CSVParsingError => StatusCode::BAD_REQUEST,
             S3ErrorCodeKind::BucketAlreadyExists => StatusCode::CONFLICT,
+            S3ErrorCodeKind::EntityTooLarge => StatusCode::BAD_REQUEST,
+            S3ErrorCodeKind::EntityTooSmall => StatusCode::BAD_REQUEST,
+            S3ErrorCodeKind::EvaluatorInvalidArguments => {
+                StatusCode::BAD_REQUEST
+            }
+            S3ErrorCodeKind::IntegerOverflow => StatusCode::BAD_REQUEST,
+            S3ErrorCodeKind::InvalidArgument => StatusCode::BAD_REQUEST,
+            S3ErrorCodeKind::InvalidCompressionFormat => {
+                StatusCode::BAD_REQUEST
+            }
+            S3ErrorCodeKind::InvalidExpressionType => StatusCode::BAD_REQUEST,
+            S3ErrorCodeKind::InvalidPartNumber => {
+                StatusCode::RANGE_NOT_SATISFIABLE
+            }
+            S3ErrorCodeKind::InvalidRange => StatusCode::RANGE_NOT_SATISFIABLE,
+            S3ErrorCodeKind::InvalidFileHeaderInfo => StatusCode::BAD_REQUEST,
             S3ErrorCodeKind::InvalidBucketName => StatusCode::BAD_REQUEST,
             S3ErrorCodeKind::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+            S3ErrorCodeKind::InvalidAccessKeyId => StatusCode::FORBIDDEN,
             S3ErrorCodeKind::InvalidRequest => StatusCode::BAD_REQUEST,
+            S3ErrorCodeKind::IncompleteBody => StatusCode::BAD_REQUEST,
+            S3ErrorCodeKind::InvalidPolicyDocument => StatusCode::BAD_REQUEST,
+            S3ErrorCodeKind::IllegalLocationConstraintException => {
+                StatusCode::BAD_REQUEST
+            }
+            S3ErrorCodeKind::InvalidRequestParameter => StatusCode::BAD_REQUEST,
+            S3ErrorCodeKind::InvalidTargetBucketForLogging => {
+                StatusCode::BAD_REQUEST
+            }
             S3ErrorCodeKind::InvalidURI => StatusCode::BAD_REQUEST,
+            S3ErrorCodeKind::JSONParsingError => StatusCode::BAD_REQUEST,
             S3ErrorCodeKind::KeyTooLongError => StatusCode::BAD_REQUEST,
             S3ErrorCodeKind::MalformedXML => StatusCode::BAD_REQUEST,
+            S3ErrorCodeKind::MalformedPOSTRequest => StatusCode::BAD_REQUEST,
+            S3ErrorCodeKind::MalformedTrailerError => StatusCode::BAD_REQUEST,
+            S3ErrorCodeKind::InvalidDigest => StatusCode::BAD_REQUEST,
             S3ErrorCodeKind::NoSuchBucket => StatusCode::NOT_FOUND,
             S3ErrorCodeKind::NoSuchKey => StatusCode::NOT_FOUND,
+            S3ErrorCodeKind::NoSuchWebsiteConfiguration => {
+                StatusCode::NOT_FOUND
+            }
+            S3ErrorCodeKind::ObjectSerializationConflict => {
+                StatusCode::BAD_REQUEST
+            }
+            S3ErrorCodeKind::ParseUnexpectedToken => StatusCode::BAD_REQUEST,
+            S3ErrorCodeKind::ParseUnsupportedSyntax => StatusCode::BAD_REQUEST,
+            S3ErrorCodeKind::RequestTimeTooSkewed => StatusCode::FORBIDDEN,
+            S3ErrorCodeKind::ServerSideEncryptionConfigurationNotFoundError => {
+                StatusCode::NOT_FOUND
+            }
+            S3ErrorCodeKind::SignatureDoesNotMatch => StatusCode::FORBIDDEN,
+            S3ErrorCodeKind::UnsupportedFunction => StatusCode::BAD_REQUEST,
+            S3ErrorCodeKind::UnsupportedScanRangeInput => {
+                StatusCode::BAD_REQUEST
+            }
+            S3ErrorCodeKind::XAmzContentSHA256Mismatch => {
+                StatusCode::BAD_REQUEST
+            }
         }
     }
 
-    const fn message(&self) -> &'static str {
+    pub(crate) const fn message(&self) -> &'static str {
         match self {
+            S3ErrorCodeKind::AccessDenied => "Access Denied",
+            S3ErrorCodeKind::AuthorizationHeaderMalformed => {
+                "The authorization header that you provided is not valid."
+            }
+            S3ErrorCodeKind::AuthorizationQueryParametersError => {
+                "X-Amz-Algorithm, X-Amz-Credential, X-Amz-Date, X-Amz-Expires, \
+                 X-Amz-SignedHeaders and X-Amz-Signature must be valid query \
+                 parameters."
+            }
+            S3ErrorCodeKind::BadDigest => {
+                "The Content-MD5 or checksum value that you specified did not \
+                 match what the server received."
+            }
+            S3ErrorCodeKind::CastFailed => {
+                "Attempt to convert from one data type to another using CAST \
+                 failed in the SQL expression."
+            }
+            S3ErrorCodeKind::CSVParsingError => {
+                "Encountered an error parsing the CSV file."
+            }
             S3ErrorCodeKind::BucketAlreadyExists => {
                 "The requested bucket name is not available. The bucket \
                  namespace is shared by all users of the system. Please select \
                  a different name and try again."
             }
+            S3ErrorCodeKind::EntityTooLarge => {
+                "Your proposed upload exceeds the maximum allowed size."
+            }
+            S3ErrorCodeKind::EntityTooSmall => {
+                "Your proposed upload is smaller than the minimum allowed size."
+            }
+            S3ErrorCodeKind::EvaluatorInvalidArguments => {
+                "The arguments of an operation in the SQL expression are not \
+                 valid."
+            }
+            S3ErrorCodeKind::IntegerOverflow => {
+                "Integer overflow or underflow in the SQL expression."
+            }
+            S3ErrorCodeKind::InvalidArgument => "Invalid Argument",
+            S3ErrorCodeKind::InvalidCompressionFormat => {
+                "The file is not in a supported compression format."
+            }
+            S3ErrorCodeKind::InvalidPartNumber => {
+                "The requested partnumber is not satisfiable"
+            }
+            S3ErrorCodeKind::InvalidRange => {
+                "The requested range is not satisfiable"
+            }
+            S3ErrorCodeKind::InvalidExpressionType => {
+                "The ExpressionType value is not valid. Only SQL expressions \
+                 are supported."
+            }
+            S3ErrorCodeKind::InvalidFileHeaderInfo => {
+                "The FileHeaderInfo value is not valid. Only NONE, USE, and \
+                 IGNORE are supported."
+            }
             S3ErrorCodeKind::InvalidBucketName => {
                 "The specified bucket is not valid."
             }
             S3ErrorCodeKind::InternalError => {
                 "An internal error occurred. Try again."
             }
+            S3ErrorCodeKind::InvalidAccessKeyId => {
+                "The AWS access key ID that you provided does not exist in our \
+                 records."
+            }
             S3ErrorCodeKind::InvalidRequest => "Invalid Request",
+            S3ErrorCodeKind::IncompleteBody => {
+                "You did not provide the number of bytes specified by the \
+                 Content-Length HTTP header."
+            }
+            S3ErrorCodeKind::InvalidPolicyDocument => {
+                "The content of the form does not meet the conditions \
+                 specified in the policy document."
+            }
+            S3ErrorCodeKind::IllegalLocationConstraintException => {
+                "The location constraint is incompatible for the region \
+                 specific endpoint this request was sent to."
+            }
+            S3ErrorCodeKind::InvalidRequestParameter => {
+                "The value of a parameter in SelectRequest element is not \
+                 valid."
+            }
+            S3ErrorCodeKind::InvalidTargetBucketForLogging => {
+                "The target bucket for logging does not exist."
+            }
             S3ErrorCodeKind::InvalidURI => "Couldn't parse the specified URI.",
+            S3ErrorCodeKind::JSONParsingError => {
+                "Encountered an error parsing the JSON file."
+            }
             S3ErrorCodeKind::KeyTooLongError => "Your key is too long",
             S3ErrorCodeKind::MalformedXML => {
                 "The XML that you provided was not well formed or did not \
                  validate against our published schema."
             }
+            S3ErrorCodeKind::MalformedPOSTRequest => {
+                "The body of your POST request is not well-formed \
+                 multipart/form-data."
+            }
+            S3ErrorCodeKind::MalformedTrailerError => {
+                "The request contained trailing data that was not well-formed \
+                 or did not conform to our published schema."
+            }
+            S3ErrorCodeKind::InvalidDigest => {
+                "The Content-MD5 or checksum value that you specified is not \
+                 valid."
+            }
             S3ErrorCodeKind::NoSuchBucket => {
                 "The specified bucket does not exist."
             }
             S3ErrorCodeKind::NoSuchKey => "The specified key does not exist.",
+            S3ErrorCodeKind::NoSuchWebsiteConfiguration => {
+                "The specified bucket does not have a website configuration."
+            }
+            S3ErrorCodeKind::ObjectSerializationConflict => {
+                "InputSerialization and OutputSerialization can only specify \
+                 one format each."
+            }
+            S3ErrorCodeKind::ParseUnexpectedToken => {
+                "The SQL expression contains an unexpected token."
+            }
+            S3ErrorCodeKind::ParseUnsupportedSyntax => {
+                "The SQL expression contains unsupported syntax."
+            }
+            S3ErrorCodeKind::RequestTimeTooSkewed => {
+                "The difference between the request time and the server's time \
+                 is too large."
+            }
+            S3ErrorCodeKind::ServerSideEncryptionConfigurationNotFoundError => {
+                "The server side encryption configuration was not found."
+            }
+            S3ErrorCodeKind::SignatureDoesNotMatch => {
+                "The request signature we calculated does not match the \
+                 signature you provided. Check your key and signing method."
+            }
+            S3ErrorCodeKind::UnsupportedFunction => {
+                "Encountered an unsupported SQL function."
+            }
+            S3ErrorCodeKind::UnsupportedScanRangeInput => {
+                "Scan range queries are not supported on this type of object."
+            }
+            S3ErrorCodeKind::XAmzContentSHA256Mismatch => {
+                "The provided 'x-amz-content-sha256' header does not match \
+                 what was computed."
+            }
         }
     }
 }
@@ -115,6 +388,9 @@ impl S3ErrorCodeKind {
 pub struct S3Error {
     message: Option<String>,
     kind: S3ErrorCodeKind,
+    /// Region of the server, returned so the clients can retry in the right
+    /// region.
+    region: Option<String>,
 }
 
 impl Display for S3Error {
@@ -124,7 +400,7 @@ impl Display for S3Error {
 }
 
 impl S3Error {
-    fn message(&self) -> &str {
+    pub(crate) fn message(&self) -> &str {
         if let Some(msg) = &self.message {
             msg
         } else {
@@ -140,8 +416,48 @@ impl S3Error {
         Self {
             kind: S3ErrorCodeKind::InvalidRequest,
             message: Some(reason.to_string()),
+            region: None,
+        }
+    }
+
+    pub fn invalid_argument(reason: &'static str) -> Self {
+        Self {
+            kind: S3ErrorCodeKind::InvalidArgument,
+            message: Some(reason.to_string()),
+            region: None,
+        }
+    }
+
+    pub fn access_denied(reason: &'static str) -> Self {
+        Self {
+            kind: S3ErrorCodeKind::AccessDenied,
+            message: Some(reason.to_string()),
+            region: None,
+        }
+    }
+
+    /// The request was signed for another region than the one of the
+    /// server.
+    pub fn wrong_region(
+        kind: S3ErrorCodeKind,
+        signed: &str,
+        expected: &str,
+    ) -> Self {
+        Self {
+            kind,
+            message: Some(format!(
+                "The authorization header is malformed; the region '{signed}' \
+                 is wrong; expecting '{expected}'"
+            )),
+            region: Some(expected.to_string()),
         }
     }
+
+    /// Attach the region of the server to the error.
+    pub fn with_region(mut self, region: impl Into<String>) -> Self {
+        self.region = Some(region.into());
+        self
+    }
 }
 
 impl From<S3ErrorCodeKind> for S3Error {
@@ -149,6 +465,7 @@ impl From<S3ErrorCodeKind> for S3Error {
         Self {
             kind: value,
             message: None,
+            region: None,
         }
     }
 }
@@ -191,6 +508,8 @@ pub struct Error {
     pub message: String,
     pub resource: String,
     pub request_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
 }
 
 impl IntoResponse for S3HTTPError {
@@ -200,6 +519,7 @@ impl IntoResponse for S3HTTPError {
             message: self.kind.message().to_string(),
             resource: self.ressource,
             request_id: self.request_id,
+            region: self.kind.region.clone(),
         }) {
             Ok(elt) => elt,
             Err(err) => {
@@ -218,11 +538,13 @@ impl IntoResponse for S3HTTPError {
             err = err
         );
 
-        Response::builder()
+        let mut response = Response::builder()
             .status(self.kind.status_code())
-            .header(axum::http::header::CONTENT_TYPE, "application/xml")
-            .body(Body::new(body))
-            .unwrap()
+            .header(axum::http::header::CONTENT_TYPE, "application/xml");
+        if let Some(region) = &self.kind.region {
+            response = response.header(X_AMZ_BUCKET_REGION, region);
+        }
+        response.body(Body::new(body)).unwrap()
     }
 }
 
@@ -239,6 +561,188 @@ impl From<BucketStorageError> for S3Error {
                 S3ErrorCodeKind::NoSuchBucket.into()
             }
             BucketStorageError::NoKey => S3ErrorCodeKind::NoSuchKey.into(),
+            BucketStorageError::NoWebsiteConfiguration => {
+                S3ErrorCodeKind::NoSuchWebsiteConfiguration.into()
+            }
+            BucketStorageError::NoEncryptionConfiguration => {
+                S3ErrorCodeKind::ServerSideEncryptionConfigurationNotFoundError
+                    .into()
+            }
+            BucketStorageError::ContentSha256Mismatch => {
+                S3ErrorCodeKind::XAmzContentSHA256Mismatch.into()
+            }
+            BucketStorageError::ChunkSignatureMismatch => {
+                S3ErrorCodeKind::SignatureDoesNotMatch.into()
+            }
+            BucketStorageError::IncompleteBody => {
+                S3ErrorCodeKind::IncompleteBody.into()
+            }
+            BucketStorageError::InvalidChunk => S3Error::invalid_request(
+                "The aws-chunked payload is malformed.",
+            ),
+            BucketStorageError::MalformedTrailer => {
+                S3ErrorCodeKind::MalformedTrailerError.into()
+            }
+            BucketStorageError::BadDigest => S3ErrorCodeKind::BadDigest.into(),
+            BucketStorageError::InvalidDigest => {
+                S3ErrorCodeKind::InvalidDigest.into()
+            }
+            BucketStorageError::InvalidCustomerKey(err) => {
+                S3Error::invalid_argument(match err {
+                    CustomerKeyError::Algorithm => {
+                        "The encryption algorithm specified is not supported."
+                    }
+                    CustomerKeyError::Key => {
+                        "The secret key was invalid for the specified \
+                         algorithm."
+                    }
+                    CustomerKeyError::KeyMd5 => {
+                        "The calculated MD5 hash of the key did not match the \
+                         hash that was provided."
+                    }
+                })
+            }
+            BucketStorageError::MissingCustomerKey => S3Error::invalid_request(
+                "The object was stored using a form of Server Side \
+                 Encryption. The correct parameters must be provided to \
+                 retrieve the object.",
+            ),
+            BucketStorageError::CustomerKeyMismatch => S3Error::access_denied(
+                "The provided SSE-C key does not match the object.",
+            ),
+            BucketStorageError::NotCustomerEncrypted => {
+                S3Error::invalid_request(
+                    "The encryption parameters are not applicable to this \
+                     object.",
+                )
+            }
+            BucketStorageError::UnsupportedEncryption => {
+                S3Error::invalid_argument(
+                    "The encryption method specified is not supported",
+                )
+            }
+            BucketStorageError::ConflictingEncryption => {
+                S3Error::invalid_argument(
+                    "Server side encryption specified with both SSE-C and \
+                     SSE-S3 headers",
+                )
+            }
+            BucketStorageError::EntityTooLarge => {
+                S3ErrorCodeKind::EntityTooLarge.into()
+            }
+            BucketStorageError::EntityTooSmall => {
+                S3ErrorCodeKind::EntityTooSmall.into()
+            }
+            BucketStorageError::MalformedForm => {
+                S3ErrorCodeKind::MalformedPOSTRequest.into()
+            }
+            BucketStorageError::EncryptionNotConfigured => {
+                S3Error::invalid_request(
+                    "Server side encryption is not configured on this server.",
+                )
+            }
+            BucketStorageError::UnsupportedNotificationEvent => {
+                S3Error::invalid_argument(
+                    "The event is not supported for notifications",
+                )
+            }
+            BucketStorageError::InvalidFilterRule => S3Error::invalid_argument(
+                "A filter can only have one prefix rule and one suffix rule.",
+            ),
+            BucketStorageError::InvalidRange => {
+                S3ErrorCodeKind::InvalidRange.into()
+            }
+            BucketStorageError::InvalidPartNumber => {
+                S3ErrorCodeKind::InvalidPartNumber.into()
+            }
+            BucketStorageError::PartNumberOutOfRange => {
+                S3Error::invalid_argument(
+                    "Part number must be an integer between 1 and 10000, \
+                     inclusive",
+                )
+            }
+            BucketStorageError::RangeWithPartNumber => {
+                S3Error::invalid_request(
+                    "Cannot specify both Range header and partNumber query \
+                     parameter",
+                )
+            }
+            BucketStorageError::InvalidMaxKeys => S3Error::invalid_argument(
+                "Argument maxKeys must be an integer between 0 and 2147483647",
+            ),
+            BucketStorageError::InvalidContinuationToken => {
+                S3Error::invalid_argument(
+                    "The continuation token provided is incorrect",
+                )
+            }
+            BucketStorageError::InvalidTargetBucketForLogging => {
+                S3ErrorCodeKind::InvalidTargetBucketForLogging.into()
+            }
+            BucketStorageError::Select(err) => err.into(),
+        }
+    }
+}
+
+impl From<SelectError> for S3Error {
+    fn from(value: SelectError) -> Self {
+        let kind = match value {
+            SelectError::InvalidExpressionType => {
+                S3ErrorCodeKind::InvalidExpressionType
+            }
+            SelectError::InvalidCompressionFormat => {
+                S3ErrorCodeKind::InvalidCompressionFormat
+            }
+            SelectError::InvalidFileHeaderInfo => {
+                S3ErrorCodeKind::InvalidFileHeaderInfo
+            }
+            SelectError::InvalidRequestParameter(_) => {
+                S3ErrorCodeKind::InvalidRequestParameter
+            }
+            SelectError::ObjectSerializationConflict => {
+                S3ErrorCodeKind::ObjectSerializationConflict
+            }
+            SelectError::UnsupportedScanRange => {
+                S3ErrorCodeKind::UnsupportedScanRangeInput
+            }
+            SelectError::Parse(_) => S3ErrorCodeKind::ParseUnexpectedToken,
+            SelectError::UnsupportedSyntax(_) => {
+                S3ErrorCodeKind::ParseUnsupportedSyntax
+            }
+            SelectError::UnsupportedFunction(_) => {
+                S3ErrorCodeKind::UnsupportedFunction
+            }
+            SelectError::CsvParsing(_) => S3ErrorCodeKind::CSVParsingError,
+            SelectError::JsonParsing(_) => S3ErrorCodeKind::JSONParsingError,
+            SelectError::CastFailed(_) => S3ErrorCodeKind::CastFailed,
+            SelectError::InvalidArguments(_) => {
+                S3ErrorCodeKind::EvaluatorInvalidArguments
+            }
+            SelectError::IntegerOverflow => S3ErrorCodeKind::IntegerOverflow,
+            SelectError::Internal(_) => S3ErrorCodeKind::InternalError,
+        };
+        Self {
+            kind,
+            message: Some(value.to_string()),
+            region: None,
+        }
+    }
+}
+
+impl From<PolicyError> for S3Error {
+    fn from(value: PolicyError) -> Self {
+        match value {
+            PolicyError::Malformed => {
+                S3ErrorCodeKind::InvalidPolicyDocument.into()
+            }
+            PolicyError::Expired => S3Error::access_denied(
+                "Invalid according to Policy: Policy expired.",
+            ),
+            PolicyError::ConditionFailed(_) => S3Error::access_denied(
+                "Invalid according to Policy: Policy Condition failed.",
+            ),
+            PolicyError::ExtraField(_) => S3Error::access_denied(
+                "Invalid according to Policy: Extra input fields.",
+            ),
         }
     }
 }
@@ -248,7 +752,7 @@ mod tests {
     use axum::response::IntoResponse;
     use http_body_util::BodyExt;
 
-    use super::{S3ErrorCodeKind, S3HTTPError};
+    use super::{S3Error, S3ErrorCodeKind, S3HTTPError};
 
     #[tokio::test]
     async fn simple_response_error() {
@@ -276,4 +780,31 @@ mod tests {
         <Error><code>InternalError</code><message>An internal error occurred. Try again.</message><resource>test</resource><request_id>blbl</request_id></Error>
         "###);
     }
+
+    #[tokio::test]
+    async fn wrong_region_response_error() {
+        let response = S3HTTPError::custom(
+            "test",
+            "blbl",
+            S3Error::wrong_region(
+                S3ErrorCodeKind::AuthorizationHeaderMalformed,
+                "us-east-1",
+                "eu-west-1",
+            ),
+        )
+        .into_response();
+
+        assert_eq!(response.status(), 400);
+        assert_eq!(
+            response.headers().get("x-amz-bucket-region").unwrap(),
+            "eu-west-1"
+        );
+        let body = response.into_body().collect().await.unwrap();
+
+        let result = String::from_utf8(body.to_bytes().to_vec()).unwrap();
+        insta::assert_display_snapshot!(result, @r###"
+        <?xml version="1.0" encoding="UTF-8"?>
+        <Error><code>AuthorizationHeaderMalformed</code><message>The authorization header is malformed; the region &apos;us-east-1&apos; is wrong; expecting &apos;eu-west-1&apos;</message><resource>test</resource><request_id>blbl</request_id><region>eu-west-1</region></Error>
+        "###);
+    }
 }
@@ -0,0 +1,130 @@
+//! Encoding of the `application/vnd.amazon.eventstream` messages used by the
+//! responses of `SelectObjectContent`.
+//!
+//! Each message is framed as:
+//!
+//! ```text
+//! [total length: u32][headers length: u32][prelude crc: u32]
+//! [headers][payload][message crc: u32]
+//! ```
+//!
+//! See [`Event stream encoding`](https://docs.aws.amazon.com/AmazonS3/latest/API/RESTSelectObjectAppendix.html)
+
+use bytes::{BufMut, Bytes, BytesMut};
+use crc::{Crc, CRC_32_ISO_HDLC};
+use tracing::error;
+use wasmio_aws_types::types::Stats;
+
+use super::errors::S3Error;
+use crate::domain::select::{SelectError, SelectEvent};
+
+static CRC32: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+
+/// Type of the header values, only strings are used.
+const HEADER_TYPE_STRING: u8 = 7;
+
+/// Length of the prelude: the total length, the headers length and their
+/// checksum.
+const PRELUDE_LEN: usize = 12;
+
+/// Encode a message with string headers.
+fn message(headers: &[(&str, &str)], payload: &[u8]) -> Bytes {
+    let mut encoded_headers = BytesMut::new();
+    for (name, value) in headers {
+        encoded_headers.put_u8(name.len() as u8);
+        encoded_headers.put_slice(name.as_bytes());
+        encoded_headers.put_u8(HEADER_TYPE_STRING);
+        encoded_headers.put_u16(value.len() as u16);
+        encoded_headers.put_slice(value.as_bytes());
+    }
+
+    let total_len = PRELUDE_LEN + encoded_headers.len() + payload.len() + 4;
+    let mut message = BytesMut::with_capacity(total_len);
+    message.put_u32(total_len as u32);
+    message.put_u32(encoded_headers.len() as u32);
+    message.put_u32(CRC32.checksum(&message));
+    message.put_slice(&encoded_headers);
+    message.put_slice(payload);
+    message.put_u32(CRC32.checksum(&message));
+    message.freeze()
+}
+
+fn stats_payload(root: &str, stats: &Stats) -> Vec<u8> {
+    let stats = match quick_xml::se::to_string_with_root(root, stats) {
+        Ok(elt) => elt,
+        Err(err) => {
+            error!("{err:?}");
+            String::new()
+        }
+    };
+    format!(
+        r###"<?xml version="1.0" encoding="UTF-8"?>
+{stats}
+"###
+    )
+    .into_bytes()
+}
+
+/// Encode an event of the query, an error is sent as an error message.
+pub fn select_event(event: Result<SelectEvent, SelectError>) -> Bytes {
+    match event {
+        Ok(SelectEvent::Records(records)) => message(
+            &[
+                (":event-type", "Records"),
+                (":content-type", "application/octet-stream"),
+                (":message-type", "event"),
+            ],
+            &records,
+        ),
+        Ok(SelectEvent::Progress(stats)) => message(
+            &[
+                (":event-type", "Progress"),
+                (":content-type", "text/xml"),
+                (":message-type", "event"),
+            ],
+            &stats_payload("Progress", &stats),
+        ),
+        Ok(SelectEvent::Stats(stats)) => message(
+            &[
+                (":event-type", "Stats"),
+                (":content-type", "text/xml"),
+                (":message-type", "event"),
+            ],
+            &stats_payload("Stats", &stats),
+        ),
+        Ok(SelectEvent::End) => {
+            message(&[(":event-type", "End"), (":message-type", "event")], &[])
+        }
+        Err(err) => {
+            let err = S3Error::from(err);
+            message(
+                &[
+                    (":error-code", &err.to_string()),
+                    (":error-message", err.message()),
+                    (":message-type", "error"),
+                ],
+                &[],
+            )
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), not(target_os = "wasi")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn end_message() {
+        let end = select_event(Ok(SelectEvent::End));
+        insta::assert_debug_snapshot!(end);
+
+        // The lengths and the checksums of the frame.
+        let total_len = u32::from_be_bytes(end[0..4].try_into().unwrap());
+        assert_eq!(total_len as usize, end.len());
+        let prelude_crc = u32::from_be_bytes(end[8..12].try_into().unwrap());
+        assert_eq!(prelude_crc, CRC32.checksum(&end[..8]));
+        let message_crc =
+            u32::from_be_bytes(end[end.len() - 4..].try_into().unwrap());
+        assert_eq!(message_crc, CRC32.checksum(&end[..end.len() - 4]));
+    }
+}
@@ -1,9 +1,7 @@
 use axum::async_trait;
-use axum::body::Body;
-use axum::extract::FromRequest;
-use axum::http::{header, Method, Request, StatusCode};
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Method, StatusCode};
 use axum::response::Response;
-use axum_serde::xml::Xml;
 use if_chain::if_chain;
 use tracing::{error, info};
 use wasmio_aws_types::types::{
@@ -16,8 +14,12 @@ use crate::application::s3::errors::{S3Error, S3ErrorCodeKind};
 use crate::application::s3::state::S3State;
 use crate::domain::storage::errors::BucketStorageError;
 use crate::domain::storage::BackendDriver;
+use crate::infrastructure::config::DEFAULT_REGION;
 use crate::infrastructure::storage::BackendStorage;
 
+/// The configuration only has the location constraint.
+const MAX_CONFIGURATION_SIZE: usize = 64 * 1024;
+
 #[derive(Clone, Copy)]
 pub struct BucketCreateHandler;
 
@@ -47,17 +49,34 @@ impl S3Handler for BucketCreateHandler {
         let body = ctx.body();
         let bucket_name = ctx.expect_bucket()?;
 
-        // Useless clone, but it'll do for now;
-        let parts = ctx.parts().clone();
-        let request = Request::from_parts(parts, body);
+        // The configuration is optional, the body is empty without it.
+        let body = to_bytes(body, MAX_CONFIGURATION_SIZE)
+            .await
+            .map_err(|_| S3ErrorCodeKind::MalformedXML)?;
+        let input = match body.is_empty() {
+            true => CreateBucketConfiguration::default(),
+            false => quick_xml::de::from_reader(body.as_ref())
+                .map_err(|_| S3ErrorCodeKind::MalformedXML)?,
+        };
 
-        let _input =
-            Xml::<CreateBucketConfiguration>::from_request(request, &())
-                .await
-                .map_err(|_| S3ErrorCodeKind::MalformedXML)?;
+        // Buckets are created in the region of the server.
+        let region = &state.region;
+        let location_constraint =
+            input.location_constraint.filter(|x| !x.is_empty());
+        if location_constraint.is_some_and(|x| &x != region) {
+            return Err(S3Error::from(
+                S3ErrorCodeKind::IllegalLocationConstraintException,
+            )
+            .with_region(region));
+        }
 
         let request = CreateBucketRequestBuilder::default()
             .bucket(bucket_name)
+            .create_bucket_configuration(CreateBucketConfiguration {
+                // Like S3, the default region is not a location constraint.
+                location_constraint: Some(region.clone())
+                    .filter(|x| x != DEFAULT_REGION),
+            })
             .build();
 
         if let Err(err) = request {
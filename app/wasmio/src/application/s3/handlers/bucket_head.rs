@@ -0,0 +1,91 @@
+use axum::async_trait;
+use axum::body::Body;
+use axum::http::{Method, StatusCode};
+use axum::response::Response;
+use if_chain::if_chain;
+use tracing::{error, info};
+use wasmio_aws_types::types::HeadBucketRequestBuilder;
+
+use crate::application::s3::axum::header_string_opt;
+use crate::application::s3::context::{Context, S3Handler};
+use crate::application::s3::errors::S3Error;
+use crate::application::s3::headers;
+use crate::application::s3::state::S3State;
+use crate::domain::storage::errors::BucketStorageError;
+use crate::domain::storage::BackendDriver;
+use crate::infrastructure::config::DEFAULT_REGION;
+use crate::infrastructure::storage::BackendStorage;
+
+/// Check that a bucket exists, the SDKs also use it to find the region of a
+/// bucket.
+#[derive(Clone, Copy)]
+pub struct BucketHeadHandler;
+
+#[async_trait]
+impl S3Handler for BucketHeadHandler {
+    #[inline]
+    fn is_match(&self, ctx: &Context) -> bool {
+        if_chain! {
+            if ctx.method() == Method::HEAD;
+            if ctx.path().is_bucket();
+            then {
+                true
+            } else {
+                false
+            }
+        }
+    }
+
+    async fn handle<T: BackendDriver>(
+        &self,
+        ctx: Context,
+        state: S3State<T>,
+    ) -> Result<Response, S3Error>
+    where
+        BucketStorageError: From<<T as BackendStorage>::Error>,
+    {
+        let bucket_name = ctx.expect_bucket()?;
+        let map = &ctx.parts().headers;
+
+        info!(
+            message = "Checking a bucket",
+            bucket = %bucket_name,
+        );
+
+        let request = HeadBucketRequestBuilder::default()
+            .bucket(bucket_name)
+            .expected_bucket_owner(header_string_opt(
+                headers::X_AMZ_EXPECTED_BUCKET_OWNER,
+                map,
+            ))
+            .build();
+
+        if let Err(err) = request {
+            error!("{err:?}");
+            return Err(S3Error::invalid_request(
+                "Server error, please check repo or contact admin.",
+            ));
+        }
+
+        let region = state
+            .bucket_loader
+            .head_bucket(request.expect("can't fail"))
+            .await
+            .map_err(|err| S3Error::from(err).with_region(&state.region))?
+            .bucket_region
+            .unwrap_or(DEFAULT_REGION.to_string());
+        let stats = state
+            .bucket_loader
+            .bucket_stats(bucket_name)
+            .await
+            .map_err(|err| S3Error::from(err).with_region(&state.region))?;
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(headers::X_AMZ_BUCKET_REGION, region)
+            .header(headers::X_AMZ_BUCKET_OBJECT_COUNT, stats.objects)
+            .header(headers::X_AMZ_BUCKET_BYTES_USED, stats.bytes)
+            .body(Body::empty())
+            .unwrap())
+    }
+}
@@ -0,0 +1,99 @@
+use axum::async_trait;
+use axum::body::Body;
+use axum::extract::Query;
+use axum::http::{header, Method, StatusCode};
+use axum::response::Response;
+use if_chain::if_chain;
+use tracing::{error, info, warn};
+use wasmio_aws_types::types::GetBucketLocationRequestBuilder;
+
+use crate::application::s3::axum::header_string_opt;
+use crate::application::s3::context::{Context, S3Handler};
+use crate::application::s3::errors::{S3Error, S3ErrorCodeKind};
+use crate::application::s3::headers;
+use crate::application::s3::state::S3State;
+use crate::domain::storage::errors::BucketStorageError;
+use crate::domain::storage::BackendDriver;
+use crate::infrastructure::storage::BackendStorage;
+
+#[derive(serde::Deserialize)]
+pub struct BucketLocationQS {
+    #[allow(dead_code)]
+    location: String,
+}
+
+#[derive(Clone, Copy)]
+pub struct BucketLocationHandler;
+
+#[async_trait]
+impl S3Handler for BucketLocationHandler {
+    #[inline]
+    fn is_match(&self, ctx: &Context) -> bool {
+        if_chain! {
+            if ctx.method() == Method::GET;
+            if ctx.path().is_bucket();
+            if Query::<BucketLocationQS>::try_from_uri(&ctx.parts().uri).is_ok();
+            then {
+                true
+            } else {
+                false
+            }
+        }
+    }
+
+    async fn handle<T: BackendDriver>(
+        &self,
+        ctx: Context,
+        state: S3State<T>,
+    ) -> Result<Response, S3Error>
+    where
+        BucketStorageError: From<<T as BackendStorage>::Error>,
+    {
+        let bucket_name = ctx.expect_bucket()?;
+        let map = &ctx.parts().headers;
+
+        info!(
+            message = "Getting the location of a bucket",
+            bucket = %bucket_name,
+        );
+
+        let request = GetBucketLocationRequestBuilder::default()
+            .bucket(bucket_name)
+            .expected_bucket_owner(header_string_opt(
+                headers::X_AMZ_EXPECTED_BUCKET_OWNER,
+                map,
+            ))
+            .build();
+
+        if let Err(err) = request {
+            error!("{err:?}");
+            return Err(S3Error::invalid_request(
+                "Server error, please check repo or contact admin.",
+            ));
+        }
+
+        // The location constraint of the buckets in `us-east-1` is empty.
+        let result = state
+            .bucket_loader
+            .get_bucket_location(request.expect("can't fail"))
+            .await?;
+
+        let xml = quick_xml::se::to_string(&result).map_err(|err| {
+            warn!("{err}");
+            S3Error::from(S3ErrorCodeKind::InternalError)
+        })?;
+
+        let body = format!(
+            r###"<?xml version="1.0" encoding="UTF-8"?>
+{xml}
+"###,
+            xml = xml
+        );
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(Body::new(body))
+            .unwrap())
+    }
+}
@@ -0,0 +1,162 @@
+use axum::async_trait;
+use axum::body::{to_bytes, Body};
+use axum::extract::Query;
+use axum::http::{header, Method, StatusCode};
+use axum::response::Response;
+use if_chain::if_chain;
+use tracing::{error, info, warn};
+use wasmio_aws_types::types::{
+    BucketLoggingStatus, GetBucketLoggingRequestBuilder,
+    PutBucketLoggingRequestBuilder,
+};
+
+use crate::application::s3::axum::header_string_opt;
+use crate::application::s3::context::{Context, S3Handler};
+use crate::application::s3::errors::{S3Error, S3ErrorCodeKind};
+use crate::application::s3::headers::{self};
+use crate::application::s3::state::S3State;
+use crate::domain::storage::errors::BucketStorageError;
+use crate::domain::storage::BackendDriver;
+use crate::infrastructure::storage::BackendStorage;
+
+/// The status only has the target of the logs.
+const MAX_CONFIGURATION_SIZE: usize = 64 * 1024;
+
+#[derive(serde::Deserialize)]
+pub struct BucketLoggingQS {
+    #[allow(dead_code)]
+    logging: String,
+}
+
+fn is_logging_match(ctx: &Context, method: Method) -> bool {
+    if_chain! {
+        if ctx.method() == method;
+        if ctx.path().is_bucket();
+        if Query::<BucketLoggingQS>::try_from_uri(&ctx.parts().uri).is_ok();
+        then {
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct BucketLoggingPutHandler;
+
+#[async_trait]
+impl S3Handler for BucketLoggingPutHandler {
+    #[inline]
+    fn is_match(&self, ctx: &Context) -> bool {
+        is_logging_match(ctx, Method::PUT)
+    }
+
+    async fn handle<T: BackendDriver>(
+        &self,
+        mut ctx: Context,
+        state: S3State<T>,
+    ) -> Result<Response, S3Error>
+    where
+        BucketStorageError: From<<T as BackendStorage>::Error>,
+    {
+        let body = ctx.body();
+        let bucket_name = ctx.expect_bucket()?;
+        let map = &ctx.parts().headers;
+
+        let body = to_bytes(body, MAX_CONFIGURATION_SIZE)
+            .await
+            .map_err(|_| S3ErrorCodeKind::MalformedXML)?;
+        let bucket_logging_status: BucketLoggingStatus =
+            quick_xml::de::from_reader(body.as_ref())
+                .map_err(|_| S3ErrorCodeKind::MalformedXML)?;
+
+        let request = PutBucketLoggingRequestBuilder::default()
+            .bucket(bucket_name)
+            .bucket_logging_status(bucket_logging_status)
+            .content_md5(header_string_opt(headers::CONTENT_MD5, map))
+            .expected_bucket_owner(header_string_opt(
+                headers::X_AMZ_EXPECTED_BUCKET_OWNER,
+                map,
+            ))
+            .build();
+
+        if let Err(err) = request {
+            error!("{err:?}");
+            return Err(S3Error::invalid_request(
+                "Server error, please check repo or contact admin.",
+            ));
+        }
+
+        info!(message = "Updating logging configuration", bucket = %bucket_name);
+        state
+            .bucket_loader
+            .put_bucket_logging(request.expect("can't fail"))
+            .await?;
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(Body::empty())
+            .unwrap())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct BucketLoggingGetHandler;
+
+#[async_trait]
+impl S3Handler for BucketLoggingGetHandler {
+    #[inline]
+    fn is_match(&self, ctx: &Context) -> bool {
+        is_logging_match(ctx, Method::GET)
+    }
+
+    async fn handle<T: BackendDriver>(
+        &self,
+        ctx: Context,
+        state: S3State<T>,
+    ) -> Result<Response, S3Error>
+    where
+        BucketStorageError: From<<T as BackendStorage>::Error>,
+    {
+        let bucket_name = ctx.expect_bucket()?;
+        let map = &ctx.parts().headers;
+
+        let request = GetBucketLoggingRequestBuilder::default()
+            .bucket(bucket_name)
+            .expected_bucket_owner(header_string_opt(
+                headers::X_AMZ_EXPECTED_BUCKET_OWNER,
+                map,
+            ))
+            .build();
+
+        if let Err(err) = request {
+            error!("{err:?}");
+            return Err(S3Error::invalid_request(
+                "Server error, please check repo or contact admin.",
+            ));
+        }
+
+        let result = state
+            .bucket_loader
+            .get_bucket_logging(request.expect("can't fail"))
+            .await?;
+
+        let xml = quick_xml::se::to_string(&result).map_err(|err| {
+            warn!("{err}");
+            S3Error::from(S3ErrorCodeKind::MalformedXML)
+        })?;
+
+        let body = format!(
+            r###"<?xml version="1.0" encoding="UTF-8"?>
+{xml}
+"###,
+            xml = xml
+        );
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(Body::new(body))
+            .unwrap())
+    }
+}
@@ -0,0 +1,184 @@
+use axum::async_trait;
+use axum::body::Body;
+use axum::extract::{FromRequest, Query};
+use axum::http::{header, Method, Request, StatusCode};
+use axum::response::Response;
+use axum_serde::xml::Xml;
+use if_chain::if_chain;
+use tracing::{error, info, warn};
+use wasmio_aws_types::types::{
+    GetBucketNotificationConfigurationRequestBuilder,
+    NotificationConfiguration,
+    PutBucketNotificationConfigurationRequestBuilder,
+};
+
+use crate::application::s3::axum::{header_parse_bool, header_string_opt};
+use crate::application::s3::context::{Context, S3Handler};
+use crate::application::s3::errors::{S3Error, S3ErrorCodeKind};
+use crate::application::s3::headers::{self};
+use crate::application::s3::state::S3State;
+use crate::domain::storage::errors::BucketStorageError;
+use crate::domain::storage::BackendDriver;
+use crate::infrastructure::storage::BackendStorage;
+
+#[derive(serde::Deserialize)]
+pub struct BucketNotificationQS {
+    #[allow(dead_code)]
+    notification: String,
+}
+
+fn is_notification_match(ctx: &Context, method: Method) -> bool {
+    if_chain! {
+        if ctx.method() == method;
+        if ctx.path().is_bucket();
+        if Query::<BucketNotificationQS>::try_from_uri(&ctx.parts().uri).is_ok();
+        then {
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct BucketNotificationPutHandler;
+
+#[async_trait]
+impl S3Handler for BucketNotificationPutHandler {
+    #[inline]
+    fn is_match(&self, ctx: &Context) -> bool {
+        is_notification_match(ctx, Method::PUT)
+    }
+
+    async fn handle<T: BackendDriver>(
+        &self,
+        mut ctx: Context,
+        state: S3State<T>,
+    ) -> Result<Response, S3Error>
+    where
+        BucketStorageError: From<<T as BackendStorage>::Error>,
+    {
+        let body = ctx.body();
+        let bucket_name = ctx.expect_bucket()?;
+
+        // Useless clone, but it'll do for now;
+        let parts = ctx.parts().clone();
+        let map = &parts.headers;
+        let request = Request::from_parts(parts.clone(), body);
+
+        let Xml(notification_configuration) =
+            Xml::<NotificationConfiguration>::from_request(request, &())
+                .await
+                .map_err(|_| S3ErrorCodeKind::MalformedXML)?;
+
+        // The destinations are the webhooks configured on the server.
+        let skip_destination_validation =
+            header_parse_bool(headers::X_AMZ_SKIP_DESTINATION_VALIDATION, map);
+        let unknown_destination = notification_configuration
+            .queue_configurations
+            .iter()
+            .any(|x| {
+                state
+                    .notifier
+                    .as_ref()
+                    .and_then(|notifier| notifier.target_of(&x.queue_arn))
+                    .is_none()
+            });
+        if unknown_destination && skip_destination_validation != Some(true) {
+            return Err(S3Error::invalid_argument(
+                "Unable to validate the following destination configurations",
+            ));
+        }
+
+        let request =
+            PutBucketNotificationConfigurationRequestBuilder::default()
+                .bucket(bucket_name)
+                .expected_bucket_owner(header_string_opt(
+                    headers::X_AMZ_EXPECTED_BUCKET_OWNER,
+                    map,
+                ))
+                .notification_configuration(notification_configuration)
+                .skip_destination_validation(skip_destination_validation)
+                .build();
+
+        if let Err(err) = request {
+            error!("{err:?}");
+            return Err(S3Error::invalid_request(
+                "Server error, please check repo or contact admin.",
+            ));
+        }
+
+        info!(message = "Updating notification configuration", bucket = %bucket_name);
+        state
+            .bucket_loader
+            .put_bucket_notification_configuration(request.expect("can't fail"))
+            .await?;
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(Body::empty())
+            .unwrap())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct BucketNotificationGetHandler;
+
+#[async_trait]
+impl S3Handler for BucketNotificationGetHandler {
+    #[inline]
+    fn is_match(&self, ctx: &Context) -> bool {
+        is_notification_match(ctx, Method::GET)
+    }
+
+    async fn handle<T: BackendDriver>(
+        &self,
+        ctx: Context,
+        state: S3State<T>,
+    ) -> Result<Response, S3Error>
+    where
+        BucketStorageError: From<<T as BackendStorage>::Error>,
+    {
+        let bucket_name = ctx.expect_bucket()?;
+        let map = &ctx.parts().headers;
+
+        let request =
+            GetBucketNotificationConfigurationRequestBuilder::default()
+                .bucket(bucket_name)
+                .expected_bucket_owner(header_string_opt(
+                    headers::X_AMZ_EXPECTED_BUCKET_OWNER,
+                    map,
+                ))
+                .build();
+
+        if let Err(err) = request {
+            error!("{err:?}");
+            return Err(S3Error::invalid_request(
+                "Server error, please check repo or contact admin.",
+            ));
+        }
+
+        let result = state
+            .bucket_loader
+            .get_bucket_notification_configuration(request.expect("can't fail"))
+            .await?;
+
+        let xml = quick_xml::se::to_string(&result).map_err(|err| {
+            warn!("{err}");
+            S3Error::from(S3ErrorCodeKind::MalformedXML)
+        })?;
+
+        let body = format!(
+            r###"<?xml version="1.0" encoding="UTF-8"?>
+{xml}
+"###,
+            xml = xml
+        );
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/xml")
+            .body(Body::new(body))
+            .unwrap())
+    }
+}
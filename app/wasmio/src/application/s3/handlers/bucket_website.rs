@@ -0,0 +1,210 @@
+use axum::async_trait;
+use axum::body::Body;
+use axum::extract::{FromRequest, Query};
+use axum::http::{Method, Request, StatusCode};
+use axum::response::Response;
+use axum_serde::xml::Xml;
+use if_chain::if_chain;
+use tracing::{error, info, warn};
+use wasmio_aws_types::types::{
+    DeleteBucketWebsiteRequestBuilder, GetBucketWebsiteRequestBuilder,
+    PutBucketWebsiteRequestBuilder, WebsiteConfiguration,
+};
+
+use crate::application::s3::axum::header_string_opt;
+use crate::application::s3::context::{Context, S3Handler};
+use crate::application::s3::errors::{S3Error, S3ErrorCodeKind};
+use crate::application::s3::headers::{self};
+use crate::application::s3::state::S3State;
+use crate::domain::storage::errors::BucketStorageError;
+use crate::domain::storage::BackendDriver;
+use crate::infrastructure::storage::BackendStorage;
+
+#[derive(serde::Deserialize)]
+pub struct BucketWebsiteQS {
+    #[allow(dead_code)]
+    website: String,
+}
+
+fn is_website_match(ctx: &Context, method: Method) -> bool {
+    if_chain! {
+        if ctx.method() == method;
+        if ctx.path().is_bucket();
+        if Query::<BucketWebsiteQS>::try_from_uri(&ctx.parts().uri).is_ok();
+        then {
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct BucketWebsitePutHandler;
+
+#[async_trait]
+impl S3Handler for BucketWebsitePutHandler {
+    #[inline]
+    fn is_match(&self, ctx: &Context) -> bool {
+        is_website_match(ctx, Method::PUT)
+    }
+
+    async fn handle<T: BackendDriver>(
+        &self,
+        mut ctx: Context,
+        state: S3State<T>,
+    ) -> Result<Response, S3Error>
+    where
+        BucketStorageError: From<<T as BackendStorage>::Error>,
+    {
+        let body = ctx.body();
+        let bucket_name = ctx.expect_bucket()?;
+
+        // Useless clone, but it'll do for now;
+        let parts = ctx.parts().clone();
+        let map = &parts.headers;
+        let request = Request::from_parts(parts.clone(), body);
+
+        let Xml(website_configuration) =
+            Xml::<WebsiteConfiguration>::from_request(request, &())
+                .await
+                .map_err(|_| S3ErrorCodeKind::MalformedXML)?;
+
+        let request = PutBucketWebsiteRequestBuilder::default()
+            .bucket(bucket_name)
+            .content_md5(header_string_opt(headers::CONTENT_MD5, map))
+            .expected_bucket_owner(header_string_opt(
+                headers::X_AMZ_EXPECTED_BUCKET_OWNER,
+                map,
+            ))
+            .website_configuration(website_configuration)
+            .build();
+
+        if let Err(err) = request {
+            error!("{err:?}");
+            return Err(S3Error::invalid_request(
+                "Server error, please check repo or contact admin.",
+            ));
+        }
+
+        info!(message = "Updating website configuration", bucket = %bucket_name);
+        state
+            .bucket_loader
+            .put_bucket_website(request.expect("can't fail"))
+            .await?;
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(Body::empty())
+            .unwrap())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct BucketWebsiteGetHandler;
+
+#[async_trait]
+impl S3Handler for BucketWebsiteGetHandler {
+    #[inline]
+    fn is_match(&self, ctx: &Context) -> bool {
+        is_website_match(ctx, Method::GET)
+    }
+
+    async fn handle<T: BackendDriver>(
+        &self,
+        ctx: Context,
+        state: S3State<T>,
+    ) -> Result<Response, S3Error>
+    where
+        BucketStorageError: From<<T as BackendStorage>::Error>,
+    {
+        let bucket_name = ctx.expect_bucket()?;
+        let map = &ctx.parts().headers;
+
+        let request = GetBucketWebsiteRequestBuilder::default()
+            .bucket(bucket_name)
+            .expected_bucket_owner(header_string_opt(
+                headers::X_AMZ_EXPECTED_BUCKET_OWNER,
+                map,
+            ))
+            .build();
+
+        if let Err(err) = request {
+            error!("{err:?}");
+            return Err(S3Error::invalid_request(
+                "Server error, please check repo or contact admin.",
+            ));
+        }
+
+        let result = state
+            .bucket_loader
+            .get_bucket_website(request.expect("can't fail"))
+            .await?;
+
+        let xml = quick_xml::se::to_string(&result).map_err(|err| {
+            warn!("{err}");
+            S3Error::from(S3ErrorCodeKind::MalformedXML)
+        })?;
+
+        let body = format!(
+            r###"<?xml version="1.0" encoding="UTF-8"?>
+{xml}
+"###,
+            xml = xml
+        );
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(Body::new(body))
+            .unwrap())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct BucketWebsiteDeleteHandler;
+
+#[async_trait]
+impl S3Handler for BucketWebsiteDeleteHandler {
+    #[inline]
+    fn is_match(&self, ctx: &Context) -> bool {
+        is_website_match(ctx, Method::DELETE)
+    }
+
+    async fn handle<T: BackendDriver>(
+        &self,
+        ctx: Context,
+        state: S3State<T>,
+    ) -> Result<Response, S3Error>
+    where
+        BucketStorageError: From<<T as BackendStorage>::Error>,
+    {
+        let bucket_name = ctx.expect_bucket()?;
+        let map = &ctx.parts().headers;
+
+        let request = DeleteBucketWebsiteRequestBuilder::default()
+            .bucket(bucket_name)
+            .expected_bucket_owner(header_string_opt(
+                headers::X_AMZ_EXPECTED_BUCKET_OWNER,
+                map,
+            ))
+            .build();
+
+        if let Err(err) = request {
+            error!("{err:?}");
+            return Err(S3Error::invalid_request(
+                "Server error, please check repo or contact admin.",
+            ));
+        }
+
+        info!(message = "Removing website configuration", bucket = %bucket_name);
+        state
+            .bucket_loader
+            .delete_bucket_website(request.expect("can't fail"))
+            .await?;
+
+        Ok(Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty())
+            .unwrap())
+    }
+}
@@ -1,6 +1,14 @@
 pub mod bucket_create;
-// pub mod object_attributes;
+pub mod bucket_encryption;
+pub mod bucket_head;
+pub mod bucket_location;
+pub mod bucket_logging;
+pub mod bucket_notification;
+pub mod bucket_website;
+pub mod object_attributes;
 pub mod object_delete;
 pub mod object_get;
 pub mod object_list_v2;
+pub mod object_post;
 pub mod object_put;
+pub mod object_select;
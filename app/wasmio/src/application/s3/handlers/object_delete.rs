@@ -12,8 +12,10 @@ use crate::application::s3::axum::{
 use crate::application::s3::context::{Context, S3Handler};
 use crate::application::s3::errors::S3Error;
 use crate::application::s3::headers::{self};
+use crate::application::s3::notification::{notify, EventObject, EventRequest};
 use crate::application::s3::state::S3State;
 use crate::domain::storage::errors::BucketStorageError;
+use crate::domain::storage::notification::EventName;
 use crate::domain::storage::BackendDriver;
 use crate::infrastructure::storage::BackendStorage;
 
@@ -80,6 +82,19 @@ impl S3Handler for ObjectDeleteHandler {
             .delete_object(request.expect("can't fail"));
         let output = insert_task.await?;
 
+        notify(
+            &state,
+            EventRequest::new(&ctx),
+            EventName::ObjectRemovedDelete,
+            EventObject {
+                bucket: bucket_name,
+                key,
+                size: None,
+                e_tag: None,
+            },
+        )
+        .await;
+
         Ok(Response::builder()
             .status(StatusCode::OK)
             .header_opt(headers::X_AMZ_VERSION_ID, output.version_id)
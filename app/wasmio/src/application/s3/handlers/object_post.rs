@@ -0,0 +1,321 @@
+//! Browser-based uploads, an object is uploaded with a HTML form.
+//!
+//! See [`PostObject`](https://docs.aws.amazon.com/AmazonS3/latest/API/RESTObjectPOST.html)
+use std::collections::HashMap;
+
+use axum::async_trait;
+use axum::body::Body;
+use axum::http::header::{CONTENT_TYPE, ETAG, HOST, LOCATION};
+use axum::http::{StatusCode, Uri};
+use axum::response::Response;
+use bytes::Bytes;
+use chrono::Utc;
+use futures::{Stream, StreamExt};
+use multer::{Field, Multipart};
+use tracing::{error, info, warn};
+use wasmio_aws_types::types::{PostResponseBuilder, PutObjectRequestBuilder};
+
+use crate::application::s3::auth::policy::PostPolicy;
+use crate::application::s3::axum::RequestExt;
+use crate::application::s3::context::{Context, S3Handler};
+use crate::application::s3::errors::{S3Error, S3ErrorCodeKind};
+use crate::application::s3::headers;
+use crate::application::s3::notification::{notify, EventObject, EventRequest};
+use crate::application::s3::state::S3State;
+use crate::domain::storage::errors::BucketStorageError;
+use crate::domain::storage::notification::EventName;
+use crate::domain::storage::BackendDriver;
+use crate::infrastructure::storage::BackendStorage;
+
+/// The fields before the file can't be larger than 20KB.
+const MAX_FIELDS_SIZE: usize = 20 * 1024;
+
+const FILENAME_VARIABLE: &str = "${filename}";
+const X_AMZ_META: &str = "x-amz-meta-";
+
+#[derive(Clone, Copy)]
+pub struct ObjectPostHandler;
+
+#[async_trait]
+impl S3Handler for ObjectPostHandler {
+    #[inline]
+    fn is_match(&self, ctx: &Context) -> bool {
+        ctx.is_browser_upload()
+    }
+
+    async fn handle<T: BackendDriver>(
+        &self,
+        mut ctx: Context,
+        state: S3State<T>,
+    ) -> Result<Response, S3Error>
+    where
+        BucketStorageError: From<<T as BackendStorage>::Error>,
+    {
+        let body = ctx.body();
+        let bucket_name = ctx.expect_bucket()?.clone();
+        let bucket_name = &bucket_name;
+
+        let boundary = ctx
+            .parts()
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|x| x.to_str().ok())
+            .and_then(|x| multer::parse_boundary(x).ok())
+            .ok_or(S3ErrorCodeKind::MalformedPOSTRequest)?;
+        let mut multipart = Multipart::new(body.into_data_stream(), boundary);
+
+        // The fields after the file are ignored.
+        let mut fields = HashMap::new();
+        let mut remaining = MAX_FIELDS_SIZE;
+        let file = loop {
+            let mut field = multipart
+                .next_field()
+                .await
+                .map_err(|_| S3ErrorCodeKind::MalformedPOSTRequest)?
+                .ok_or_else(|| {
+                    S3Error::invalid_argument(
+                        "POST requires exactly one file upload per request.",
+                    )
+                })?;
+            let name = field.name().unwrap_or_default().to_ascii_lowercase();
+            if name == "file" {
+                break field;
+            }
+            let value = read_field(&mut field, &mut remaining).await?;
+            fields.insert(name, value);
+        };
+
+        let key = fields.get("key").ok_or_else(|| {
+            S3Error::invalid_argument(
+                "Bucket POST must contain a field named 'key'.",
+            )
+        })?;
+        let key = key
+            .replace(FILENAME_VARIABLE, file.file_name().unwrap_or_default());
+        if key.is_empty() {
+            return Err(S3Error::invalid_argument(
+                "User key must have a length greater than 0.",
+            ));
+        }
+        fields.insert("key".to_string(), key.clone());
+        fields.insert("bucket".to_string(), bucket_name.clone());
+
+        // The signature is checked here as the authentication of the request
+        // is skipped for browser uploads.
+        let authentication = state
+            .auth
+            .as_ref()
+            .map(|auth| auth.authenticate_post(&fields))
+            .transpose()?;
+        if let Some(authentication) = authentication {
+            ctx.set_authentication(authentication);
+        }
+        let policy = fields
+            .get("policy")
+            .map(|x| PostPolicy::parse(x))
+            .transpose()?;
+        if let Some(policy) = &policy {
+            policy.check(&fields, Utc::now())?;
+        }
+
+        info!(
+            message = "Trying to insert a new element from a form",
+            bucket = %bucket_name,
+            key = %key,
+        );
+
+        let content_type = fields
+            .get("content-type")
+            .cloned()
+            .or_else(|| file.content_type().map(|x| x.to_string()));
+        let metadata: HashMap<String, String> = fields
+            .iter()
+            .filter_map(|(k, v)| {
+                k.strip_prefix(X_AMZ_META)
+                    .map(|k| (k.to_string(), v.clone()))
+            })
+            .collect();
+        let range = policy.as_ref().and_then(|x| x.content_length_range());
+        let body = Body::from_stream(file_stream(file, range));
+
+        let request = PutObjectRequestBuilder::default()
+            .bucket(bucket_name)
+            .key(key.clone())
+            .body(Some(body.into_data_stream()))
+            .acl(fields.get("acl").cloned())
+            .cache_control(fields.get("cache-control").cloned())
+            .content_type(content_type)
+            .content_disposition(fields.get("content-disposition").cloned())
+            .content_encoding(fields.get("content-encoding").cloned())
+            .expires(fields.get("expires").cloned())
+            .metadata(Some(metadata))
+            .storage_class(fields.get("x-amz-storage-class").cloned())
+            .server_side_encryption(
+                fields.get("x-amz-server-side-encryption").cloned(),
+            )
+            .website_redirect_location(
+                fields.get("x-amz-website-redirect-location").cloned(),
+            )
+            .build();
+
+        if let Err(err) = request {
+            error!("{err:?}");
+            return Err(S3Error::invalid_request(
+                "Server error, please check repo or contact admin.",
+            ));
+        }
+
+        let output = state
+            .bucket_loader
+            .put_object(request.expect("can't fail"), None)
+            .await?;
+        let e_tag = output.e_tag.unwrap_or_default();
+
+        notify(
+            &state,
+            EventRequest::new(&ctx),
+            EventName::ObjectCreatedPost,
+            EventObject {
+                bucket: bucket_name,
+                key: &key,
+                size: output.size.map(|x| x as u64),
+                e_tag: Some(e_tag.clone()),
+            },
+        )
+        .await;
+        let location = object_location(&ctx, bucket_name, &key);
+
+        let redirect = fields
+            .get("success_action_redirect")
+            .or_else(|| fields.get("redirect"))
+            .filter(|x| x.parse::<Uri>().is_ok_and(|x| x.scheme().is_some()));
+        if let Some(redirect) = redirect {
+            let query = [
+                ("bucket", bucket_name.as_str()),
+                ("key", key.as_str()),
+                ("etag", e_tag.as_str()),
+            ]
+            .map(|(k, v)| [k, &urlencoding::encode(v)].join("="))
+            .join("&");
+            let separator = if redirect.contains('?') { "&" } else { "?" };
+            return Ok(Response::builder()
+                .status(StatusCode::SEE_OTHER)
+                .header(LOCATION, [redirect, separator, &query].concat())
+                .header(ETAG, e_tag)
+                .body(Body::empty())
+                .unwrap());
+        }
+
+        let status =
+            match fields.get("success_action_status").map(|x| x.as_str()) {
+                Some("200") => StatusCode::OK,
+                Some("201") => StatusCode::CREATED,
+                _ => StatusCode::NO_CONTENT,
+            };
+        let response = Response::builder()
+            .status(status)
+            .header(LOCATION, &location)
+            .header(ETAG, &e_tag)
+            .header_opt(
+                headers::X_AMZ_SERVER_SIDE_ENCRYPTION,
+                output.server_side_encryption,
+            );
+        if status != StatusCode::CREATED {
+            return Ok(response.body(Body::empty()).unwrap());
+        }
+
+        let post_response = PostResponseBuilder::default()
+            .location(location)
+            .bucket(bucket_name)
+            .key(key)
+            .e_tag(e_tag)
+            .build()
+            .map_err(|_| S3ErrorCodeKind::InternalError)?;
+        let xml = quick_xml::se::to_string(&post_response).map_err(|err| {
+            warn!("{err}");
+            S3Error::from(S3ErrorCodeKind::InternalError)
+        })?;
+
+        let body = format!(
+            r###"<?xml version="1.0" encoding="UTF-8"?>
+{xml}
+"###,
+            xml = xml
+        );
+
+        Ok(response
+            .header(CONTENT_TYPE, "application/xml")
+            .body(Body::new(body))
+            .unwrap())
+    }
+}
+
+/// Read a field of the form as text, `remaining` is the size left for the
+/// fields.
+async fn read_field(
+    field: &mut Field<'static>,
+    remaining: &mut usize,
+) -> Result<String, S3Error> {
+    let mut value = Vec::new();
+    while let Some(chunk) = field
+        .chunk()
+        .await
+        .map_err(|_| S3ErrorCodeKind::MalformedPOSTRequest)?
+    {
+        *remaining = remaining.checked_sub(chunk.len()).ok_or_else(|| {
+            S3Error::invalid_argument(
+                "Your POST request fields preceding the upload file were too \
+                 large.",
+            )
+        })?;
+        value.extend_from_slice(&chunk);
+    }
+    String::from_utf8(value)
+        .map_err(|_| S3ErrorCodeKind::MalformedPOSTRequest.into())
+}
+
+/// The content of the file, failing when its size is out of the
+/// `content-length-range` of the policy.
+fn file_stream(
+    mut file: Field<'static>,
+    range: Option<(u64, u64)>,
+) -> impl Stream<Item = Result<Bytes, axum::Error>> {
+    let (min, max) = range.unwrap_or((0, u64::MAX));
+
+    async_stream::stream! {
+        let mut size = 0u64;
+        while let Some(chunk) = file.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(err) => {
+                    warn!("{err:?}");
+                    yield Err(axum::Error::new(BucketStorageError::MalformedForm));
+                    return;
+                }
+            };
+            size += chunk.len() as u64;
+            if size > max {
+                yield Err(axum::Error::new(BucketStorageError::EntityTooLarge));
+                return;
+            }
+            yield Ok(chunk);
+        }
+
+        if size < min {
+            yield Err(axum::Error::new(BucketStorageError::EntityTooSmall));
+        }
+    }
+}
+
+/// The URL of the uploaded object, based on the host of the request.
+fn object_location(ctx: &Context, bucket: &str, key: &str) -> String {
+    let key = urlencoding::encode(key);
+    let path = match ctx.is_virtual_hosted() {
+        true => ["/", &key].concat(),
+        false => ["/", bucket, "/", &key].concat(),
+    };
+    match ctx.parts().headers.get(HOST).and_then(|x| x.to_str().ok()) {
+        Some(host) => ["http://", host, &path].concat(),
+        None => path,
+    }
+}
@@ -1,8 +1,8 @@
 use axum::async_trait;
 use axum::body::{Body, BodyDataStream};
 use axum::http::header::{
-    CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_ENCODING, CONTENT_LANGUAGE,
-    CONTENT_LENGTH, CONTENT_TYPE, ETAG,
+    CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_LANGUAGE, CONTENT_LENGTH,
+    CONTENT_TYPE, ETAG,
 };
 use axum::http::{Method, StatusCode};
 use axum::response::Response;
@@ -13,11 +13,15 @@ use wasmio_aws_types::types::PutObjectRequestBuilder;
 use crate::application::s3::axum::{
     header_parse, header_string_opt, RequestExt,
 };
+use crate::application::s3::checksum::checksum_request;
+use crate::application::s3::chunked::{self, AwsChunked};
 use crate::application::s3::context::{Context, S3Handler};
 use crate::application::s3::errors::S3Error;
 use crate::application::s3::headers::{self, X_AMZ_STORAGE_CLASS};
+use crate::application::s3::notification::{notify, EventObject, EventRequest};
 use crate::application::s3::state::S3State;
 use crate::domain::storage::errors::BucketStorageError;
+use crate::domain::storage::notification::EventName;
 use crate::domain::storage::BackendDriver;
 use crate::infrastructure::storage::BackendStorage;
 
@@ -48,8 +52,22 @@ impl S3Handler for ObjectPutHandler {
     where
         BucketStorageError: From<<T as BackendStorage>::Error>,
     {
+        let mut chunked = AwsChunked::from_context(&ctx)?;
         let body = ctx.body();
         let (bucket_name, key) = ctx.expect_object()?;
+        let map = &ctx.parts().headers;
+        let checksum = checksum_request(map, chunked.as_mut())?;
+
+        let content_length = match &chunked {
+            Some(chunked) => chunked.decoded_content_length().map(|x| x as i64),
+            None => header_parse(CONTENT_LENGTH, map).map_err(|_err| {
+                S3Error::invalid_request("Invalid header: content-length")
+            })?,
+        };
+        let body = match chunked {
+            Some(chunked) => chunked.decode(body),
+            None => body,
+        };
 
         info!(
             message = "Trying to insert a new element",
@@ -60,23 +78,42 @@ impl S3Handler for ObjectPutHandler {
         // switch to https://docs.rs/http-body-util/latest/http_body_util/struct.BodyStream.html
         // to have trailers which will be needed for the full implementation
         let stream: BodyDataStream = body.into_data_stream();
-        let map = &ctx.parts().headers;
 
         let request = PutObjectRequestBuilder::default()
             .bucket(bucket_name)
             .body(Some(stream))
-            .content_length(header_parse(CONTENT_LENGTH, map).map_err(
-                |_err| {
-                    S3Error::invalid_request("Invalid header: content-length")
-                },
-            )?)
+            .content_length(content_length)
             .acl(header_string_opt(headers::X_AMZ_ACL, map))
             .cache_control(header_string_opt(CACHE_CONTROL, map))
             .content_type(header_string_opt(CONTENT_TYPE, map))
             .content_language(header_string_opt(CONTENT_LANGUAGE, map))
-            .content_encoding(header_string_opt(CONTENT_ENCODING, map))
+            .content_encoding(chunked::content_encoding(map))
             .content_disposition(header_string_opt(CONTENT_DISPOSITION, map))
+            .content_md5(header_string_opt(headers::CONTENT_MD5, map))
+            .checksum_algorithm(
+                checksum.as_ref().map(|x| x.algorithm.to_string()),
+            )
             .storage_class(header_string_opt(X_AMZ_STORAGE_CLASS, map))
+            .server_side_encryption(header_string_opt(
+                headers::X_AMZ_SERVER_SIDE_ENCRYPTION,
+                map,
+            ))
+            .sse_customer_algorithm(header_string_opt(
+                headers::X_AMZ_SERVER_SIDE_ENCRYPTION_CUSTOMER_ALGORITHM,
+                map,
+            ))
+            .sse_customer_key(header_string_opt(
+                headers::X_AMZ_SERVER_SIDE_ENCRYPTION_CUSTOMER_KEY,
+                map,
+            ))
+            .sse_customer_key_md5(header_string_opt(
+                headers::X_AMZ_SERVER_SIDE_ENCRYPTION_CUSTOMER_KEY_MD5,
+                map,
+            ))
+            .website_redirect_location(header_string_opt(
+                headers::X_AMZ_WEBSITE_REDIRECT_LOCATION,
+                map,
+            ))
             .key(key)
             .build();
 
@@ -87,15 +124,32 @@ impl S3Handler for ObjectPutHandler {
             ));
         }
 
-        let insert_task =
-            state.bucket_loader.put_object(request.expect("can't fail"));
+        let insert_task = state
+            .bucket_loader
+            .put_object(request.expect("can't fail"), checksum);
         let output = insert_task.await?;
 
+        notify(
+            &state,
+            EventRequest::new(&ctx),
+            EventName::ObjectCreatedPut,
+            EventObject {
+                bucket: bucket_name,
+                key,
+                size: output.size.map(|x| x as u64),
+                e_tag: output.e_tag.clone(),
+            },
+        )
+        .await;
+
         Ok(Response::builder()
             .status(StatusCode::OK)
             .header_opt(ETAG, output.e_tag)
             .header_opt(headers::X_AMZ_EXPIRATION, output.expiration)
-            .header_opt(headers::X_AMZ_CONTENT_SHA_256, output.checksum)
+            .header_opt(headers::X_AMZ_CHECKSUM_CRC32, output.checksum_crc32)
+            .header_opt(headers::X_AMZ_CHECKSUM_CRC32C, output.checksum_crc32c)
+            .header_opt(headers::X_AMZ_CHECKSUM_SHA1, output.checksum_sha1)
+            .header_opt(headers::X_AMZ_CHECKSUM_SHA256, output.checksum_sha256)
             .header_opt(
                 headers::X_AMZ_SERVER_SIDE_ENCRYPTION,
                 output.server_side_encryption,
@@ -0,0 +1,119 @@
+use std::convert::Infallible;
+
+use axum::async_trait;
+use axum::body::{to_bytes, Body};
+use axum::extract::Query;
+use axum::http::{Method, StatusCode};
+use axum::response::Response;
+use futures::StreamExt;
+use if_chain::if_chain;
+use tracing::info;
+use wasmio_aws_types::types::SelectObjectContentRequest;
+
+use crate::application::s3::axum::header_string_opt;
+use crate::application::s3::context::{Context, S3Handler};
+use crate::application::s3::errors::{S3Error, S3ErrorCodeKind};
+use crate::application::s3::event_stream::select_event;
+use crate::application::s3::headers;
+use crate::application::s3::state::S3State;
+use crate::domain::storage::errors::BucketStorageError;
+use crate::domain::storage::BackendDriver;
+use crate::infrastructure::storage::BackendStorage;
+
+/// Maximum size of the request, the expression is limited to 256 KB.
+const MAX_REQUEST_SIZE: usize = 512 * 1024;
+
+/// The only version of the select API.
+const SELECT_TYPE: &str = "2";
+
+#[derive(Clone, Copy)]
+pub struct ObjectSelectHandler;
+
+#[derive(serde::Deserialize)]
+pub struct ObjectSelectQS {
+    #[allow(dead_code)]
+    select: String,
+    #[serde(rename = "select-type")]
+    select_type: String,
+}
+
+#[async_trait]
+impl S3Handler for ObjectSelectHandler {
+    #[inline]
+    fn is_match(&self, ctx: &Context) -> bool {
+        if_chain! {
+            if ctx.method() == Method::POST;
+            if ctx.path().is_object();
+            if Query::<ObjectSelectQS>::try_from_uri(&ctx.parts().uri).is_ok();
+            then {
+                true
+            } else {
+                false
+            }
+        }
+    }
+
+    async fn handle<T: BackendDriver>(
+        &self,
+        mut ctx: Context,
+        state: S3State<T>,
+    ) -> Result<Response, S3Error>
+    where
+        BucketStorageError: From<<T as BackendStorage>::Error>,
+    {
+        let body = ctx.body();
+        let (bucket_name, key) = ctx.expect_object()?;
+        let Query(qs) = Query::<ObjectSelectQS>::try_from_uri(&ctx.parts().uri)
+            .expect("Can't fail as we already checked.");
+        if qs.select_type != SELECT_TYPE {
+            return Err(S3Error::invalid_argument(
+                "The select-type must be 2.",
+            ));
+        }
+
+        let body = to_bytes(body, MAX_REQUEST_SIZE)
+            .await
+            .map_err(|_| S3ErrorCodeKind::MalformedXML)?;
+        let input: SelectObjectContentRequest =
+            quick_xml::de::from_reader(body.as_ref())
+                .map_err(|_| S3ErrorCodeKind::MalformedXML)?;
+
+        let map = &ctx.parts().headers;
+        let request = SelectObjectContentRequest {
+            bucket: bucket_name.to_string(),
+            key: key.to_string(),
+            sse_customer_algorithm: header_string_opt(
+                headers::X_AMZ_SERVER_SIDE_ENCRYPTION_CUSTOMER_ALGORITHM,
+                map,
+            ),
+            sse_customer_key: header_string_opt(
+                headers::X_AMZ_SERVER_SIDE_ENCRYPTION_CUSTOMER_KEY,
+                map,
+            ),
+            sse_customer_key_md5: header_string_opt(
+                headers::X_AMZ_SERVER_SIDE_ENCRYPTION_CUSTOMER_KEY_MD5,
+                map,
+            ),
+            expected_bucket_owner: header_string_opt(
+                headers::X_AMZ_EXPECTED_BUCKET_OWNER,
+                map,
+            ),
+            ..input
+        };
+
+        info!(
+            message = "Selecting the content of an element",
+            bucket = %bucket_name,
+        );
+        let events = state.bucket_loader.select_object_content(request).await?;
+
+        // The errors happening while the query runs are sent as events.
+        let body = Body::from_stream(
+            events.map(|event| Ok::<_, Infallible>(select_event(event))),
+        );
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(body)
+            .unwrap())
+    }
+}
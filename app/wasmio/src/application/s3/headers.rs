@@ -149,6 +149,20 @@ declare_header_name! {
     /// x-amz-bucket-object-lock-enabled
     X_AMZ_BUCKET_OBJECT_LOCK_ENABLED: "x-amz-bucket-object-lock-enabled";
 
+    /// x-amz-bucket-region
+    X_AMZ_BUCKET_REGION: "x-amz-bucket-region";
+
+    /// x-amz-bucket-object-count, not an AWS header: the number of objects
+    /// of a bucket.
+    X_AMZ_BUCKET_OBJECT_COUNT: "x-amz-bucket-object-count";
+
+    /// x-amz-bucket-bytes-used, not an AWS header: the bytes of the objects
+    /// of a bucket.
+    X_AMZ_BUCKET_BYTES_USED: "x-amz-bucket-bytes-used";
+
+    /// x-amz-skip-destination-validation
+    X_AMZ_SKIP_DESTINATION_VALIDATION: "x-amz-skip-destination-validation";
+
     /// x-amz-server-side-encryption-bucket-key-enabled
     X_AMZ_BUCKET_SERVER_SIDE_ENCRYPTION_BUCKET_KEY_ENABLED: "x-amz-server-side-encryption-bucket-key-enabled";
 
@@ -169,4 +183,34 @@ declare_header_name! {
 
     /// x-amz-expected-bucket-owner
     X_AMZ_EXPECTED_BUCKET_OWNER: "x-amz-expected-bucket-owner";
+
+    /// x-amz-decoded-content-length
+    X_AMZ_DECODED_CONTENT_LENGTH: "x-amz-decoded-content-length";
+
+    /// x-amz-trailer
+    X_AMZ_TRAILER: "x-amz-trailer";
+
+    /// x-amz-sdk-checksum-algorithm
+    X_AMZ_SDK_CHECKSUM_ALGORITHM: "x-amz-sdk-checksum-algorithm";
+
+    /// x-amz-checksum-crc32
+    X_AMZ_CHECKSUM_CRC32: "x-amz-checksum-crc32";
+
+    /// x-amz-checksum-crc32c
+    X_AMZ_CHECKSUM_CRC32C: "x-amz-checksum-crc32c";
+
+    /// x-amz-checksum-sha1
+    X_AMZ_CHECKSUM_SHA1: "x-amz-checksum-sha1";
+
+    /// x-amz-checksum-sha256
+    X_AMZ_CHECKSUM_SHA256: "x-amz-checksum-sha256";
+
+    /// x-amz-checksum-mode
+    X_AMZ_CHECKSUM_MODE: "x-amz-checksum-mode";
+
+    /// x-amz-object-attributes
+    X_AMZ_OBJECT_ATTRIBUTES: "x-amz-object-attributes";
+
+    /// x-amz-max-parts
+    X_AMZ_MAX_PARTS: "x-amz-max-parts";
 }
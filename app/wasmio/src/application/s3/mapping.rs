@@ -4,14 +4,35 @@ use axum::http::Request;
 use axum::response::{IntoResponse, Response};
 use axum::Router;
 use tower::ServiceBuilder;
+use tracing::debug;
 
+use super::access_log::AccessLogEntry;
 use super::context::{Context, S3Handler, VisitorNil};
 use super::errors::S3HTTPError;
 use super::handlers::bucket_create::BucketCreateHandler;
+use super::handlers::bucket_encryption::{
+    BucketEncryptionDeleteHandler, BucketEncryptionGetHandler,
+    BucketEncryptionPutHandler,
+};
+use super::handlers::bucket_head::BucketHeadHandler;
+use super::handlers::bucket_location::BucketLocationHandler;
+use super::handlers::bucket_logging::{
+    BucketLoggingGetHandler, BucketLoggingPutHandler,
+};
+use super::handlers::bucket_notification::{
+    BucketNotificationGetHandler, BucketNotificationPutHandler,
+};
+use super::handlers::bucket_website::{
+    BucketWebsiteDeleteHandler, BucketWebsiteGetHandler,
+    BucketWebsitePutHandler,
+};
+use super::handlers::object_attributes::ObjectAttributesHandler;
 use super::handlers::object_delete::ObjectDeleteHandler;
 use super::handlers::object_get::ObjectGetHandler;
 use super::handlers::object_list_v2::ObjectListHandlerV2;
+use super::handlers::object_post::ObjectPostHandler;
 use super::handlers::object_put::ObjectPutHandler;
+use super::handlers::object_select::ObjectSelectHandler;
 use super::state::S3State;
 use crate::domain::storage::errors::BucketStorageError;
 use crate::domain::storage::BackendDriver;
@@ -37,22 +58,79 @@ where
         let handlers = VisitorNil
             .with(BucketCreateHandler)
             .with(ObjectPutHandler)
+            .with(ObjectPostHandler)
             .with(ObjectDeleteHandler)
             .with(ObjectListHandlerV2)
-            .with(ObjectGetHandler);
+            .with(ObjectGetHandler)
+            .with(ObjectAttributesHandler)
+            .with(BucketWebsitePutHandler)
+            .with(BucketWebsiteGetHandler)
+            .with(BucketWebsiteDeleteHandler)
+            .with(BucketEncryptionPutHandler)
+            .with(BucketEncryptionGetHandler)
+            .with(BucketEncryptionDeleteHandler)
+            .with(BucketHeadHandler)
+            .with(BucketLocationHandler)
+            .with(BucketNotificationPutHandler)
+            .with(BucketNotificationGetHandler)
+            .with(ObjectSelectHandler)
+            .with(BucketLoggingPutHandler)
+            .with(BucketLoggingGetHandler);
 
         let service =
             ServiceBuilder::new().service_fn(move |req: Request<Body>| {
                 let state = self.state.clone();
                 // Create a request context and route it based on this.
                 async move {
-                    let context = Context::new(req)?;
+                    let mut context =
+                        Context::new(req, state.domain.as_deref())?;
                     let r_id = context.request_id();
                     let resource = context.resource();
+                    let access_log = state.access_log.clone();
+                    let mut entry = AccessLogEntry::new(&context);
 
-                    handlers.handle(context, state).await.map_err(|err| {
-                        S3HTTPError::custom(resource, r_id.to_string(), err)
-                    })
+                    let result = async {
+                        if let Some(auth) = &state.auth {
+                            auth.authenticate(&mut context).map_err(|err| {
+                                S3HTTPError::custom(
+                                    &resource,
+                                    r_id.to_string(),
+                                    err,
+                                )
+                            })?;
+                        }
+
+                        if let Some(auth) = context.authentication() {
+                            debug!(
+                                message = "Authenticated request",
+                                request_id = %r_id,
+                                access_key = %auth.access_key,
+                            );
+                        }
+                        entry.authenticated(&context);
+
+                        handlers.handle(context, state).await.map_err(|err| {
+                            S3HTTPError::custom(
+                                &resource,
+                                r_id.to_string(),
+                                err,
+                            )
+                        })
+                    }
+                    .await;
+
+                    // Every request on a bucket is logged, even the failed
+                    // ones.
+                    let (response, error_code) = match result {
+                        Ok(response) => (response, None),
+                        Err(err) => {
+                            let code = err.to_string();
+                            (err.into_response(), Some(code))
+                        }
+                    };
+                    access_log.record(entry, &response, error_code);
+
+                    Ok::<_, S3HTTPError>(response)
                 }
             });
 
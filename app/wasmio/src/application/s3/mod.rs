@@ -1,9 +1,16 @@
+pub mod access_log;
+pub mod auth;
 mod axum;
+mod checksum;
+mod chunked;
 pub mod mapping;
 pub mod state;
+pub mod website;
 
 mod context;
-mod errors;
+pub mod errors;
+mod event_stream;
 mod handlers;
 mod headers;
+mod notification;
 mod path;
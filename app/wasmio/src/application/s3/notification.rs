@@ -0,0 +1,154 @@
+//! Events of the elements, sent to the destinations configured on their
+//! bucket.
+use std::net::SocketAddr;
+
+use axum::extract::ConnectInfo;
+use chrono::{SecondsFormat, Utc};
+use tracing::warn;
+
+use super::context::Context;
+use super::state::S3State;
+use crate::domain::storage::errors::BucketStorageError;
+use crate::domain::storage::notification::EventName;
+use crate::domain::storage::BackendDriver;
+use crate::infrastructure::notification::event::{
+    BucketEntity, Event, EventRecord, Identity, ObjectEntity,
+    RequestParameters, ResponseElements, S3Entity, EVENT_SOURCE, EVENT_VERSION,
+    S3_SCHEMA_VERSION,
+};
+use crate::infrastructure::storage::BackendStorage;
+
+const ANONYMOUS: &str = "anonymous";
+
+/// The request which emitted the event, taken from the [Context] as it
+/// can't be kept while the event is queued.
+pub struct EventRequest {
+    principal_id: String,
+    source_ip_address: String,
+    request_id: String,
+}
+
+impl EventRequest {
+    pub fn new(ctx: &Context) -> Self {
+        Self {
+            principal_id: ctx
+                .authentication()
+                .map(|x| x.access_key.clone())
+                .unwrap_or_else(|| ANONYMOUS.to_string()),
+            source_ip_address: ctx
+                .parts()
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|x| x.0.ip().to_string())
+                .unwrap_or_default(),
+            request_id: ctx.request_id().to_string(),
+        }
+    }
+}
+
+/// The element changed by a request.
+pub struct EventObject<'a> {
+    pub bucket: &'a str,
+    pub key: &'a str,
+    pub size: Option<u64>,
+    pub e_tag: Option<String>,
+}
+
+/// Queue the event for every matching destination of the bucket. The request
+/// already succeeded, so a failure is only logged.
+pub async fn notify<T: BackendDriver>(
+    state: &S3State<T>,
+    request: EventRequest,
+    event_name: EventName,
+    object: EventObject<'_>,
+) where
+    BucketStorageError: From<<T as BackendStorage>::Error>,
+{
+    let Some(notifier) = &state.notifier else {
+        return;
+    };
+
+    let destinations = match state
+        .bucket_loader
+        .notification_destinations(object.bucket, event_name, object.key)
+        .await
+    {
+        Ok(destinations) => destinations,
+        Err(err) => {
+            warn!("Can't get the notification destinations: {err}");
+            return;
+        }
+    };
+
+    for destination in destinations {
+        let Some(target) = notifier.target_of(&destination.queue_arn) else {
+            warn!(
+                message = "The destination of the event is not configured",
+                arn = %destination.queue_arn,
+            );
+            continue;
+        };
+
+        let event = Event {
+            records: vec![record(
+                &request,
+                state,
+                event_name,
+                destination.id.unwrap_or_default(),
+                &object,
+            )],
+        };
+        if let Err(err) = notifier.send(target, event).await {
+            warn!("Can't queue the event: {err}");
+        }
+    }
+}
+
+fn record<T: BackendDriver>(
+    request: &EventRequest,
+    state: &S3State<T>,
+    event_name: EventName,
+    configuration_id: String,
+    object: &EventObject<'_>,
+) -> EventRecord {
+    let now = Utc::now();
+
+    EventRecord {
+        event_version: EVENT_VERSION.to_string(),
+        event_source: EVENT_SOURCE.to_string(),
+        aws_region: state.region.clone(),
+        event_time: now.to_rfc3339_opts(SecondsFormat::Millis, true),
+        event_name: event_name.to_string(),
+        user_identity: Identity {
+            principal_id: request.principal_id.clone(),
+        },
+        request_parameters: RequestParameters {
+            source_ip_address: request.source_ip_address.clone(),
+        },
+        response_elements: ResponseElements {
+            request_id: request.request_id.clone(),
+            host_id: request.request_id.clone(),
+        },
+        s3: S3Entity {
+            s3_schema_version: S3_SCHEMA_VERSION.to_string(),
+            configuration_id,
+            bucket: BucketEntity {
+                name: object.bucket.to_string(),
+                owner_identity: Identity {
+                    principal_id: request.principal_id.clone(),
+                },
+                arn: format!("arn:aws:s3:::{}", object.bucket),
+            },
+            object: ObjectEntity {
+                key: urlencoding::encode(object.key).into_owned(),
+                size: object.size,
+                e_tag: object.e_tag.clone(),
+                version_id: None,
+                sequencer: format!(
+                    "{:016X}",
+                    now.timestamp_nanos_opt().unwrap_or_default()
+                ),
+            },
+        },
+    }
+}
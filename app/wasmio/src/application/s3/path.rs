@@ -1,6 +1,7 @@
 //! Part of it was taken from `s3-server` crate.
 use std::net::IpAddr;
 
+use axum::http::header;
 use axum::http::request::Parts;
 use ulid::Ulid;
 
@@ -14,9 +15,13 @@ pub enum S3Path {
 }
 
 impl S3Path {
+    /// Resolve the path of a request, the bucket is taken from the host when
+    /// it's a subdomain of `domain` (virtual-hosted-style), otherwise from
+    /// the path (path-style).
     pub fn from_part(
         request_id: &Ulid,
         parts: &Parts,
+        domain: Option<&str>,
     ) -> Result<Self, S3HTTPError> {
         let path = parts.uri.path();
         let path = urlencoding::decode(path).map_err(|_e| {
@@ -26,13 +31,32 @@ impl S3Path {
                 S3ErrorCodeKind::InvalidURI,
             )
         })?;
-        let path = Self::try_from_path(&path).map_err(|e| {
-            S3HTTPError::custom(&path, request_id.to_string(), e)
-        })?;
+        let path = match Self::virtual_host_bucket(parts, domain) {
+            Some(bucket) => Self::try_from_virtual_host(bucket, &path),
+            None => Self::try_from_path(&path),
+        }
+        .map_err(|e| S3HTTPError::custom(&path, request_id.to_string(), e))?;
 
         Ok(path)
     }
 
+    /// The bucket of a virtual-hosted-style request, `bucket.{domain}`.
+    pub fn virtual_host_bucket<'a>(
+        parts: &'a Parts,
+        domain: Option<&str>,
+    ) -> Option<&'a str> {
+        let host = parts.uri.host().or_else(|| {
+            parts
+                .headers
+                .get(header::HOST)
+                .and_then(|x| x.to_str().ok())
+        })?;
+        let host = host.split(':').next().unwrap_or(host);
+
+        let bucket = host.strip_suffix(domain?)?.strip_suffix('.')?;
+        (!bucket.is_empty()).then_some(bucket)
+    }
+
     /// See [bucket nameing rules](https://docs.aws.amazon.com/AmazonS3/latest/dev/BucketRestrictions.html#bucketnamingrules)
     #[must_use]
     pub fn check_bucket_name(name: &str) -> bool {
@@ -128,6 +152,39 @@ impl S3Path {
         })
     }
 
+    /// Parse a virtual-hosted-style request, the path is the key.
+    /// # Errors
+    /// Returns an `Err` if the bucket or the key is invalid
+    pub fn try_from_virtual_host(
+        bucket: &str,
+        path: &str,
+    ) -> Result<Self, S3Error> {
+        let key = if let Some(("", x)) = path.split_once('/') {
+            x
+        } else {
+            return Err(S3ErrorCodeKind::InvalidURI.into());
+        };
+
+        if !Self::check_bucket_name(bucket) {
+            return Err(S3ErrorCodeKind::InvalidBucketName.into());
+        }
+
+        if key.is_empty() {
+            return Ok(S3Path::Bucket {
+                bucket: bucket.to_string(),
+            });
+        }
+
+        if !Self::check_key(key) {
+            return Err(S3ErrorCodeKind::KeyTooLongError.into());
+        }
+
+        Ok(Self::Object {
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+        })
+    }
+
     #[must_use]
     pub const fn is_bucket(&self) -> bool {
         matches!(*self, Self::Bucket { .. })
@@ -195,6 +252,7 @@ mod tests {
             S3Error {
                 message: None,
                 kind: InvalidURI,
+                region: None,
             },
         )
         "###);
@@ -207,6 +265,7 @@ mod tests {
             S3Error {
                 message: None,
                 kind: InvalidURI,
+                region: None,
             },
         )
         "###);
@@ -219,6 +278,68 @@ mod tests {
             S3Error {
                 message: None,
                 kind: InvalidBucketName,
+                region: None,
+            },
+        )
+        "###);
+    }
+
+    fn request_parts(uri: &str, host: &str) -> Parts {
+        axum::http::Request::builder()
+            .uri(uri)
+            .header(header::HOST, host)
+            .body(())
+            .unwrap()
+            .into_parts()
+            .0
+    }
+
+    #[test]
+    fn parse_s3_path_virtual_host() {
+        let request_id = Ulid::new();
+        let domain = Some("s3.localhost");
+
+        let parts = request_parts("/dir/object", "bucket.s3.localhost:8000");
+        insta::assert_debug_snapshot!(S3Path::from_part(&request_id, &parts, domain).unwrap(), @r###"
+        Object {
+            bucket: "bucket",
+            key: "dir/object",
+        }
+        "###);
+
+        let parts = request_parts("/", "my.bucket.s3.localhost");
+        insta::assert_debug_snapshot!(S3Path::from_part(&request_id, &parts, domain).unwrap(), @r###"
+        Bucket {
+            bucket: "my.bucket",
+        }
+        "###);
+
+        // Path-style requests are still served on the domain itself.
+        let parts = request_parts("/bucket/object", "s3.localhost:8000");
+        insta::assert_debug_snapshot!(S3Path::from_part(&request_id, &parts, domain).unwrap(), @r###"
+        Object {
+            bucket: "bucket",
+            key: "object",
+        }
+        "###);
+
+        let parts = request_parts("/bucket/object", "bucket.s3.localhost");
+        insta::assert_debug_snapshot!(S3Path::from_part(&request_id, &parts, None).unwrap(), @r###"
+        Object {
+            bucket: "bucket",
+            key: "object",
+        }
+        "###);
+    }
+
+    #[test]
+    fn parse_s3_path_virtual_host_fail() {
+        insta::assert_debug_snapshot!(S3Path::try_from_virtual_host("B*", "/"), @r###"
+        Err(
+            S3Error {
+                message: None,
+                kind: InvalidBucketName,
+                region: None,
             },
         )
         "###);
@@ -232,6 +353,7 @@ mod tests {
             S3Error {
                 message: None,
                 kind: KeyTooLongError,
+                region: None,
             },
         )
         "###);
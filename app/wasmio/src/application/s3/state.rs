@@ -1,16 +1,43 @@
+use std::sync::Arc;
+
+use super::access_log::AccessLog;
+use super::auth::Authenticator;
 use crate::application::state::AppState;
+use crate::domain::storage::errors::BucketStorageError;
 use crate::domain::storage::{BackendDriver, BucketStorage};
-use crate::infrastructure::storage::FSStorage;
+use crate::infrastructure::notification::Notifier;
+use crate::infrastructure::storage::BackendStorage;
 
 #[derive(Debug, Clone)]
 pub struct S3State<T: BackendDriver> {
     pub bucket_loader: BucketStorage<T>,
+    /// Requests are not authenticated when it's not set.
+    pub auth: Option<Arc<Authenticator>>,
+    /// Base domain of the virtual-hosted-style requests.
+    pub domain: Option<String>,
+    /// Region of the server.
+    pub region: String,
+    /// Delivery of the bucket events, disabled when it's not set.
+    pub notifier: Option<Notifier>,
+    /// Server access logs of the buckets.
+    pub access_log: AccessLog,
 }
 
-impl S3State<FSStorage> {
-    pub fn from_state(app: &AppState) -> Self {
+impl<T> S3State<T>
+where
+    T: BackendDriver,
+    BucketStorageError: From<<T as BackendStorage>::Error>,
+{
+    pub fn from_state(app: &AppState<T>) -> Self {
         Self {
             bucket_loader: BucketStorage::new(app.storage.clone()),
+            auth: app.cfg.auth.as_ref().map(|auth| {
+                Arc::new(Authenticator::new(auth, &app.cfg.region))
+            }),
+            domain: app.cfg.domain.clone(),
+            region: app.cfg.region.clone(),
+            notifier: app.notifier.clone(),
+            access_log: app.access_log.clone(),
         }
     }
 }
@@ -0,0 +1,363 @@
+use std::convert::Infallible;
+
+use axum::body::Body;
+use axum::http::{header, HeaderMap, Method, Request, StatusCode, Uri};
+use axum::response::Response;
+use axum::Router;
+use tower::ServiceBuilder;
+use tracing::{info, warn};
+use ulid::Ulid;
+use wasmio_aws_types::types::{
+    GetBucketWebsiteRequest, GetObjectOutput, GetObjectRequest,
+    WebsiteConfiguration,
+};
+
+use super::routing::{matching_rule, redirect_for};
+use crate::application::s3::axum::RequestExt;
+use crate::application::s3::errors::S3ErrorCodeKind;
+use crate::application::s3::path::S3Path;
+use crate::application::s3::state::S3State;
+use crate::domain::storage::errors::BucketStorageError;
+use crate::domain::storage::BackendDriver;
+use crate::infrastructure::storage::BackendStorage;
+
+/// The website endpoint serves the content of a bucket as a static website,
+/// the bucket is resolved from the `Host` of the request.
+///
+/// See [`Website endpoints`](https://docs.aws.amazon.com/AmazonS3/latest/userguide/WebsiteEndpoints.html)
+pub struct WebsiteMapping<T: BackendDriver> {
+    state: S3State<T>,
+    /// When set, a request on `bucket.{domain}` is served from `bucket`,
+    /// otherwise the whole host is used as the bucket name.
+    domain: Option<String>,
+}
+
+impl<T: BackendDriver> WebsiteMapping<T>
+where
+    BucketStorageError: From<<T as BackendStorage>::Error>,
+{
+    pub fn new(state: S3State<T>, domain: Option<String>) -> Self {
+        Self { state, domain }
+    }
+
+    pub fn into_router(self) -> Router {
+        let service =
+            ServiceBuilder::new().service_fn(move |req: Request<Body>| {
+                let state = self.state.clone();
+                let domain = self.domain.clone();
+                async move {
+                    Ok::<_, Infallible>(
+                        WebsiteRequest::new(req, domain.as_deref())
+                            .respond(state)
+                            .await,
+                    )
+                }
+            });
+
+        Router::new().fallback_service(service)
+    }
+}
+
+/// Resolve the bucket served for this host.
+fn bucket_from_host(host: &str, domain: Option<&str>) -> Option<String> {
+    let host = host.split(':').next().unwrap_or(host);
+    let bucket = match domain {
+        Some(domain) => host.strip_suffix(domain)?.strip_suffix('.')?,
+        None => host,
+    };
+
+    S3Path::check_bucket_name(bucket).then(|| bucket.to_string())
+}
+
+fn request_host(uri: &Uri, headers: &HeaderMap) -> Option<String> {
+    uri.host().map(ToString::to_string).or_else(|| {
+        headers
+            .get(header::HOST)
+            .and_then(|x| x.to_str().ok())
+            .map(ToString::to_string)
+    })
+}
+
+struct WebsiteRequest {
+    request_id: Ulid,
+    method: Method,
+    host: Option<String>,
+    bucket: Option<String>,
+    /// The requested key, without the leading `/`.
+    key: Option<String>,
+    path: String,
+}
+
+impl WebsiteRequest {
+    fn new(req: Request<Body>, domain: Option<&str>) -> Self {
+        let host = request_host(req.uri(), req.headers());
+        let bucket = host
+            .as_deref()
+            .and_then(|host| bucket_from_host(host, domain));
+        let path = req.uri().path().to_string();
+        let key = urlencoding::decode(&path)
+            .ok()
+            .map(|x| x.trim_start_matches('/').to_string());
+
+        Self {
+            request_id: Ulid::new(),
+            method: req.method().clone(),
+            host,
+            bucket,
+            key,
+            path,
+        }
+    }
+
+    async fn respond<T: BackendDriver>(self, state: S3State<T>) -> Response
+    where
+        BucketStorageError: From<<T as BackendStorage>::Error>,
+    {
+        if self.method != Method::GET && self.method != Method::HEAD {
+            return self.error_page(
+                StatusCode::METHOD_NOT_ALLOWED,
+                "MethodNotAllowed",
+                "The specified method is not allowed against this resource.",
+            );
+        }
+
+        let (Some(bucket), Some(key), Some(host)) =
+            (&self.bucket, &self.key, &self.host)
+        else {
+            return self.error_kind(S3ErrorCodeKind::InvalidURI);
+        };
+
+        let website = match state
+            .bucket_loader
+            .get_bucket_website(GetBucketWebsiteRequest {
+                bucket: bucket.clone(),
+                ..Default::default()
+            })
+            .await
+        {
+            Ok(website) => website,
+            Err(BucketStorageError::NoBucket) => {
+                return self.error_kind(S3ErrorCodeKind::NoSuchBucket);
+            }
+            Err(BucketStorageError::NoWebsiteConfiguration) => {
+                return self
+                    .error_kind(S3ErrorCodeKind::NoSuchWebsiteConfiguration);
+            }
+            Err(err) => {
+                warn!("{err:?}");
+                return self.error_kind(S3ErrorCodeKind::InternalError);
+            }
+        };
+
+        let WebsiteConfiguration {
+            error_document,
+            index_document,
+            redirect_all_requests_to,
+            routing_rules,
+        } = website;
+
+        if let Some(redirect) = redirect_all_requests_to {
+            let location = format!(
+                "{protocol}://{host}{path}",
+                protocol = redirect.protocol.as_deref().unwrap_or("http"),
+                host = redirect.host_name,
+                path = self.path,
+            );
+            return redirect_response(StatusCode::MOVED_PERMANENTLY, location);
+        }
+
+        if let Some(rule) = routing_rules
+            .as_ref()
+            .and_then(|rules| matching_rule(rules, key, None))
+        {
+            let (status, location) = redirect_for(rule, key, host, "http");
+            return redirect_response(status, location);
+        }
+
+        info!(
+            message = "Serving a website element",
+            bucket = %bucket,
+            key = %key,
+        );
+
+        let index = index_document.map(|x| x.suffix);
+        let object_key = match &index {
+            Some(suffix) if key.is_empty() || key.ends_with('/') => {
+                format!("{key}{suffix}")
+            }
+            _ => key.clone(),
+        };
+
+        let error = match get(&state, bucket, &object_key).await {
+            Ok(output) => return self.object_response(StatusCode::OK, output),
+            Err(BucketStorageError::NoKey) => S3ErrorCodeKind::NoSuchKey,
+            Err(err) => {
+                warn!("{err:?}");
+                S3ErrorCodeKind::InternalError
+            }
+        };
+
+        // A request on `/dir` is redirected to `/dir/` when it holds an index
+        // document.
+        if let Some(suffix) = &index {
+            if !key.is_empty()
+                && !key.ends_with('/')
+                && matches!(
+                    state
+                        .bucket_loader
+                        .object_exists(bucket, &format!("{key}/{suffix}"))
+                        .await,
+                    Ok(true)
+                )
+            {
+                return redirect_response(
+                    StatusCode::FOUND,
+                    format!("/{key}/"),
+                );
+            }
+        }
+
+        let status = error.status_code();
+        if let Some(rule) = routing_rules
+            .as_ref()
+            .and_then(|rules| matching_rule(rules, key, Some(status)))
+        {
+            let (status, location) = redirect_for(rule, key, host, "http");
+            return redirect_response(status, location);
+        }
+
+        if let Some(error_document) = error_document {
+            match get(&state, bucket, &error_document.key).await {
+                Ok(output) => return self.object_response(status, output),
+                Err(err) => {
+                    warn!(message = "Can't load the error document", err = ?err);
+                }
+            }
+        }
+
+        self.error_kind(error)
+    }
+
+    fn object_response(
+        &self,
+        status: StatusCode,
+        GetObjectOutput {
+            body,
+            content_length,
+            content_type,
+            e_tag,
+            last_modified,
+            website_redirect_location,
+            ..
+        }: GetObjectOutput,
+    ) -> Response {
+        if let Some(location) = website_redirect_location {
+            return redirect_response(StatusCode::MOVED_PERMANENTLY, location);
+        }
+
+        let body = if self.method == Method::HEAD {
+            Body::empty()
+        } else {
+            body.unwrap_or_default()
+        };
+
+        Response::builder()
+            .status(status)
+            .header_opt(header::CONTENT_LENGTH, content_length)
+            .header_opt(header::CONTENT_TYPE, content_type)
+            .header_opt(header::ETAG, e_tag)
+            .header_opt(header::LAST_MODIFIED, last_modified)
+            .body(body)
+            .unwrap()
+    }
+
+    fn error_kind(&self, kind: S3ErrorCodeKind) -> Response {
+        self.error_page(kind.status_code(), &kind.to_string(), kind.message())
+    }
+
+    fn error_page(
+        &self,
+        status: StatusCode,
+        code: &str,
+        message: &str,
+    ) -> Response {
+        let body = format!(
+            r###"<html>
+<head><title>{status}</title></head>
+<body>
+<h1>{status}</h1>
+<ul>
+<li>Code: {code}</li>
+<li>Message: {message}</li>
+<li>RequestId: {request_id}</li>
+</ul>
+</body>
+</html>
+"###,
+            request_id = self.request_id,
+        );
+
+        let body = if self.method == Method::HEAD {
+            Body::empty()
+        } else {
+            Body::new(body)
+        };
+
+        Response::builder()
+            .status(status)
+            .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+            .body(body)
+            .unwrap()
+    }
+}
+
+async fn get<T: BackendDriver>(
+    state: &S3State<T>,
+    bucket: &str,
+    key: &str,
+) -> Result<GetObjectOutput, BucketStorageError>
+where
+    BucketStorageError: From<<T as BackendStorage>::Error>,
+{
+    state
+        .bucket_loader
+        .get_object(GetObjectRequest {
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+            ..Default::default()
+        })
+        .await
+}
+
+fn redirect_response(status: StatusCode, location: String) -> Response {
+    Response::builder()
+        .status(status)
+        .header(header::LOCATION, location)
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), not(target_os = "wasi")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_from_host_with_domain() {
+        assert_eq!(
+            bucket_from_host("docs.website.local:8080", Some("website.local")),
+            Some("docs".to_string())
+        );
+        assert_eq!(
+            bucket_from_host("website.local", Some("website.local")),
+            None
+        );
+    }
+
+    #[test]
+    fn bucket_from_host_without_domain() {
+        assert_eq!(
+            bucket_from_host("docs.example.com", None),
+            Some("docs.example.com".to_string())
+        );
+    }
+}
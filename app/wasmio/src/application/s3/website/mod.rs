@@ -0,0 +1,2 @@
+pub mod mapping;
+mod routing;
@@ -0,0 +1,147 @@
+//! Evaluation of the website `RoutingRules`.
+//!
+//! See [`Configuring redirects`](https://docs.aws.amazon.com/AmazonS3/latest/userguide/how-to-page-redirect.html)
+use axum::http::StatusCode;
+use wasmio_aws_types::types::{Redirect, RoutingRule, RoutingRules};
+
+/// Find the first rule matching the requested key.
+///
+/// When `error` is `None`, only rules without an error condition are
+/// evaluated, they are applied before the lookup of the object. When the
+/// lookup failed, only the rules matching the returned error code are
+/// evaluated.
+pub fn matching_rule<'a>(
+    rules: &'a RoutingRules,
+    key: &str,
+    error: Option<StatusCode>,
+) -> Option<&'a RoutingRule> {
+    rules.routing_rule.iter().find(|rule| {
+        let Some(condition) = &rule.condition else {
+            return error.is_none();
+        };
+
+        let prefix_match = match condition.key_prefix_equals.as_deref() {
+            Some(prefix) => key.starts_with(prefix),
+            None => true,
+        };
+
+        let error_match =
+            match (&condition.http_error_code_returned_equals, error) {
+                (None, None) => true,
+                (Some(code), Some(error)) => code == error.as_str(),
+                _ => false,
+            };
+
+        prefix_match && error_match
+    })
+}
+
+/// Compute the `Location` and the status code of the redirection described by
+/// the rule.
+pub fn redirect_for(
+    rule: &RoutingRule,
+    key: &str,
+    host: &str,
+    protocol: &str,
+) -> (StatusCode, String) {
+    let Redirect {
+        host_name,
+        http_redirect_code,
+        protocol: redirect_protocol,
+        replace_key_prefix_with,
+        replace_key_with,
+    } = &rule.redirect;
+
+    let status = http_redirect_code
+        .as_deref()
+        .and_then(|code| code.parse::<u16>().ok())
+        .and_then(|code| StatusCode::from_u16(code).ok())
+        .unwrap_or(StatusCode::MOVED_PERMANENTLY);
+
+    let key = match (replace_key_with, replace_key_prefix_with) {
+        (Some(replace), _) => replace.clone(),
+        (None, Some(replace)) => {
+            let prefix = rule
+                .condition
+                .as_ref()
+                .and_then(|x| x.key_prefix_equals.as_deref())
+                .unwrap_or_default();
+            let rest = key.strip_prefix(prefix).unwrap_or(key);
+            format!("{replace}{rest}")
+        }
+        (None, None) => key.to_string(),
+    };
+
+    let location = format!(
+        "{protocol}://{host}/{key}",
+        protocol = redirect_protocol.as_deref().unwrap_or(protocol),
+        host = host_name.as_deref().unwrap_or(host),
+    );
+
+    (status, location)
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), not(target_os = "wasi")))]
+mod tests {
+    use wasmio_aws_types::types::{Condition, Redirect, RoutingRule};
+
+    use super::*;
+
+    fn rules() -> RoutingRules {
+        RoutingRules {
+            routing_rule: vec![
+                RoutingRule {
+                    condition: Some(Condition {
+                        key_prefix_equals: Some("docs/".to_string()),
+                        http_error_code_returned_equals: None,
+                    }),
+                    redirect: Redirect {
+                        replace_key_prefix_with: Some("documents/".to_string()),
+                        ..Default::default()
+                    },
+                },
+                RoutingRule {
+                    condition: Some(Condition {
+                        key_prefix_equals: None,
+                        http_error_code_returned_equals: Some(
+                            "404".to_string(),
+                        ),
+                    }),
+                    redirect: Redirect {
+                        host_name: Some("example.com".to_string()),
+                        http_redirect_code: Some("302".to_string()),
+                        replace_key_with: Some("not-found.html".to_string()),
+                        ..Default::default()
+                    },
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn prefix_rule_applies_before_lookup() {
+        let rules = rules();
+        let rule = matching_rule(&rules, "docs/a.html", None).unwrap();
+        insta::assert_debug_snapshot!(redirect_for(rule, "docs/a.html", "bucket.local", "http"), @r###"
+        (
+            301,
+            "http://bucket.local/documents/a.html",
+        )
+        "###);
+    }
+
+    #[test]
+    fn error_rule_only_applies_on_error() {
+        let rules = rules();
+        assert!(matching_rule(&rules, "a.html", None).is_none());
+
+        let rule = matching_rule(&rules, "a.html", Some(StatusCode::NOT_FOUND))
+            .unwrap();
+        insta::assert_debug_snapshot!(redirect_for(rule, "a.html", "bucket.local", "http"), @r###"
+        (
+            302,
+            "http://example.com/not-found.html",
+        )
+        "###);
+    }
+}
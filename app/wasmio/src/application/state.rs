@@ -1,12 +1,38 @@
-use crate::infrastructure::storage::FSStorage;
+use std::sync::Arc;
+
+use super::s3::access_log::AccessLog;
+use crate::domain::storage::errors::BucketStorageError;
+use crate::domain::storage::{BackendDriver, BucketStorage};
+use crate::infrastructure::config::Cfg;
+use crate::infrastructure::notification::Notifier;
+use crate::infrastructure::storage::BackendStorage;
 
 #[derive(Debug, Clone)]
-pub struct AppState {
-    pub storage: FSStorage,
+pub struct AppState<T: BackendDriver> {
+    pub storage: T,
+    /// Delivery of the bucket events, disabled when it's not set.
+    pub notifier: Option<Notifier>,
+    /// Server access logs of the buckets.
+    pub access_log: AccessLog,
+    pub cfg: Arc<Cfg>,
 }
 
-impl AppState {
-    pub fn new(storage: FSStorage) -> Self {
-        Self { storage }
+impl<T> AppState<T>
+where
+    T: BackendDriver,
+    BucketStorageError: From<<T as BackendStorage>::Error>,
+{
+    pub fn new(storage: T, notifier: Option<Notifier>, cfg: Arc<Cfg>) -> Self {
+        let access_log = AccessLog::start(
+            BucketStorage::new(storage.clone()),
+            &cfg.access_log,
+        );
+
+        Self {
+            storage,
+            notifier,
+            access_log,
+            cfg,
+        }
     }
 }
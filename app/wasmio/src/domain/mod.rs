@@ -1 +1,2 @@
+pub mod select;
 pub mod storage;
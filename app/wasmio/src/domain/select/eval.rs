@@ -0,0 +1,632 @@
+//! Evaluation of the expressions over the records of the object.
+
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use super::sql::{
+    Aggregate, BinaryOperator, DataType, Expr, Function, Identifier,
+    UnaryOperator, TABLE_NAME,
+};
+use super::SelectError;
+
+/// A value manipulated by the expressions.
+///
+/// The fields of a CSV record are strings, they are compared and computed as
+/// numbers when they can be parsed as such.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    /// An object or an array of a JSON record.
+    Json(serde_json::Value),
+}
+
+impl Value {
+    pub fn from_json(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(x) => Value::Bool(x),
+            serde_json::Value::Number(x) => match x.as_i64() {
+                Some(x) => Value::Int(x),
+                None => Value::Float(x.as_f64().unwrap_or(f64::NAN)),
+            },
+            serde_json::Value::String(x) => Value::String(x),
+            value => Value::Json(value),
+        }
+    }
+
+    pub fn into_json(self) -> serde_json::Value {
+        match self {
+            Value::Null => serde_json::Value::Null,
+            Value::Bool(x) => serde_json::Value::Bool(x),
+            Value::Int(x) => serde_json::Value::from(x),
+            Value::Float(x) => serde_json::Value::from(x),
+            Value::String(x) => serde_json::Value::String(x),
+            Value::Json(x) => x,
+        }
+    }
+
+    /// The textual representation of the value, `None` for `NULL`.
+    pub fn into_text(self) -> Option<String> {
+        match self {
+            Value::Null => None,
+            Value::Bool(x) => Some(x.to_string()),
+            Value::Int(x) => Some(x.to_string()),
+            Value::Float(x) => Some(x.to_string()),
+            Value::String(x) => Some(x),
+            Value::Json(x) => Some(x.to_string()),
+        }
+    }
+
+    /// The value as a number, strings are parsed.
+    fn to_number(&self) -> Result<Option<Value>, SelectError> {
+        match self {
+            Value::Null => Ok(None),
+            Value::Int(_) | Value::Float(_) => Ok(Some(self.clone())),
+            Value::String(x) => parse_number(x).map(Some).ok_or_else(|| {
+                SelectError::InvalidArguments(format!(
+                    "The value '{x}' is not a number"
+                ))
+            }),
+            _ => Err(SelectError::InvalidArguments(
+                "The value is not a number".to_string(),
+            )),
+        }
+    }
+
+    fn to_bool(&self) -> Result<Option<bool>, SelectError> {
+        match self {
+            Value::Null => Ok(None),
+            Value::Bool(x) => Ok(Some(*x)),
+            Value::String(x) if x.eq_ignore_ascii_case("true") => {
+                Ok(Some(true))
+            }
+            Value::String(x) if x.eq_ignore_ascii_case("false") => {
+                Ok(Some(false))
+            }
+            _ => Err(SelectError::InvalidArguments(
+                "The value is not a boolean".to_string(),
+            )),
+        }
+    }
+}
+
+fn parse_number(value: &str) -> Option<Value> {
+    let value = value.trim();
+    match value.parse::<i64>() {
+        Ok(x) => Some(Value::Int(x)),
+        Err(_) => value.parse::<f64>().ok().map(Value::Float),
+    }
+}
+
+fn as_f64(value: &Value) -> f64 {
+    match value {
+        Value::Int(x) => *x as f64,
+        Value::Float(x) => *x,
+        _ => f64::NAN,
+    }
+}
+
+/// Compare two values, `None` when they can't be compared.
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Null, _) | (_, Value::Null) => None,
+        (Value::Int(x), Value::Int(y)) => Some(x.cmp(y)),
+        (Value::Int(_) | Value::Float(_), Value::Int(_) | Value::Float(_)) => {
+            as_f64(left).partial_cmp(&as_f64(right))
+        }
+        (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+        (Value::String(x), Value::Int(_) | Value::Float(_)) => {
+            compare(&parse_number(x)?, right)
+        }
+        (Value::Int(_) | Value::Float(_), Value::String(y)) => {
+            compare(left, &parse_number(y)?)
+        }
+        (Value::Bool(x), Value::Bool(y)) => Some(x.cmp(y)),
+        (Value::Json(x), Value::Json(y)) if x == y => Some(Ordering::Equal),
+        _ => None,
+    }
+}
+
+/// A record of the object.
+#[derive(Debug, Clone)]
+pub enum Record {
+    Csv {
+        /// The names of the columns when the header is used.
+        header: Option<Arc<Vec<String>>>,
+        fields: Vec<String>,
+    },
+    Json(serde_json::Value),
+}
+
+impl Record {
+    /// The columns of the record, as returned by `SELECT *`.
+    pub fn columns(self) -> Vec<(String, Value)> {
+        match self {
+            Record::Csv { header, fields } => fields
+                .into_iter()
+                .enumerate()
+                .map(|(i, field)| {
+                    let name = header
+                        .as_ref()
+                        .and_then(|x| x.get(i).cloned())
+                        .unwrap_or_else(|| format!("_{}", i + 1));
+                    (name, Value::String(field))
+                })
+                .collect(),
+            Record::Json(serde_json::Value::Object(map)) => map
+                .into_iter()
+                .map(|(name, value)| (name, Value::from_json(value)))
+                .collect(),
+            Record::Json(value) => {
+                vec![("_1".to_string(), Value::from_json(value))]
+            }
+        }
+    }
+
+    fn column(&self, path: &[Identifier], alias: Option<&str>) -> Value {
+        // The column can be prefixed by the table or its alias.
+        let path = match path {
+            [table, rest @ ..]
+                if !rest.is_empty()
+                    && (table.matches(TABLE_NAME)
+                        || alias.is_some_and(|x| table.matches(x))) =>
+            {
+                rest
+            }
+            path => path,
+        };
+
+        match self {
+            Record::Csv { header, fields } => {
+                let [column] = path else {
+                    return Value::Null;
+                };
+                let index = match column
+                    .name
+                    .strip_prefix('_')
+                    .and_then(|x| x.parse::<usize>().ok())
+                {
+                    Some(position) => position.checked_sub(1),
+                    None => header
+                        .as_ref()
+                        .and_then(|x| x.iter().position(|x| column.matches(x))),
+                };
+                index
+                    .and_then(|x| fields.get(x))
+                    .map(|x| Value::String(x.clone()))
+                    .unwrap_or(Value::Null)
+            }
+            Record::Json(value) => {
+                let mut current = value;
+                for identifier in path {
+                    let serde_json::Value::Object(map) = current else {
+                        return Value::Null;
+                    };
+                    let next = map.get(&identifier.name).or_else(|| {
+                        map.iter()
+                            .find(|(name, _)| identifier.matches(name))
+                            .map(|(_, value)| value)
+                    });
+                    match next {
+                        Some(next) => current = next,
+                        None => return Value::Null,
+                    }
+                }
+                Value::from_json(current.clone())
+            }
+        }
+    }
+}
+
+/// Evaluates the expressions for a record, or for the result of the
+/// aggregates.
+pub struct Scope<'a> {
+    pub record: Option<&'a Record>,
+    pub alias: Option<&'a str>,
+    /// The results of the aggregates, by expression.
+    pub aggregates: &'a [(&'a Expr, Value)],
+}
+
+impl Scope<'_> {
+    pub fn eval(&self, expr: &Expr) -> Result<Value, SelectError> {
+        match expr {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Column(path) => Ok(self
+                .record
+                .map(|x| x.column(path, self.alias))
+                .unwrap_or(Value::Null)),
+            Expr::Aggregate { .. } => Ok(self
+                .aggregates
+                .iter()
+                .find(|(x, _)| std::ptr::eq(*x, expr))
+                .map(|(_, value)| value.clone())
+                .unwrap_or(Value::Null)),
+            Expr::Unary { op, expr } => {
+                let value = self.eval(expr)?;
+                match op {
+                    UnaryOperator::Not => {
+                        Ok(bool_or_null(value.to_bool()?.map(|x| !x)))
+                    }
+                    UnaryOperator::Minus => {
+                        arithmetic(BinaryOperator::Minus, Value::Int(0), value)
+                    }
+                }
+            }
+            Expr::Binary { op, left, right } => match op {
+                BinaryOperator::And => {
+                    let left = self.eval(left)?.to_bool()?;
+                    if left == Some(false) {
+                        return Ok(Value::Bool(false));
+                    }
+                    let right = self.eval(right)?.to_bool()?;
+                    Ok(match (left, right) {
+                        (_, Some(false)) => Value::Bool(false),
+                        (Some(true), Some(true)) => Value::Bool(true),
+                        _ => Value::Null,
+                    })
+                }
+                BinaryOperator::Or => {
+                    let left = self.eval(left)?.to_bool()?;
+                    if left == Some(true) {
+                        return Ok(Value::Bool(true));
+                    }
+                    let right = self.eval(right)?.to_bool()?;
+                    Ok(match (left, right) {
+                        (_, Some(true)) => Value::Bool(true),
+                        (Some(false), Some(false)) => Value::Bool(false),
+                        _ => Value::Null,
+                    })
+                }
+                BinaryOperator::Eq
+                | BinaryOperator::NotEq
+                | BinaryOperator::Lt
+                | BinaryOperator::LtEq
+                | BinaryOperator::Gt
+                | BinaryOperator::GtEq => {
+                    let ordering =
+                        compare(&self.eval(left)?, &self.eval(right)?);
+                    Ok(bool_or_null(ordering.map(|x| match op {
+                        BinaryOperator::Eq => x.is_eq(),
+                        BinaryOperator::NotEq => x.is_ne(),
+                        BinaryOperator::Lt => x.is_lt(),
+                        BinaryOperator::LtEq => x.is_le(),
+                        BinaryOperator::Gt => x.is_gt(),
+                        _ => x.is_ge(),
+                    })))
+                }
+                BinaryOperator::Concat => {
+                    let left = self.eval(left)?.into_text();
+                    let right = self.eval(right)?.into_text();
+                    Ok(match (left, right) {
+                        (Some(left), Some(right)) => {
+                            Value::String(left + &right)
+                        }
+                        _ => Value::Null,
+                    })
+                }
+                op => arithmetic(*op, self.eval(left)?, self.eval(right)?),
+            },
+            Expr::IsNull { expr, negated } => {
+                let is_null = self.eval(expr)? == Value::Null;
+                Ok(Value::Bool(is_null != *negated))
+            }
+            Expr::Like {
+                expr,
+                pattern,
+                escape,
+                negated,
+            } => {
+                let value = self.eval(expr)?.into_text();
+                let pattern = self.eval(pattern)?.into_text();
+                let escape = match escape {
+                    Some(escape) => {
+                        let escape =
+                            self.eval(escape)?.into_text().unwrap_or_default();
+                        let mut chars = escape.chars();
+                        match (chars.next(), chars.next()) {
+                            (Some(x), None) => Some(x),
+                            _ => {
+                                return Err(SelectError::InvalidArguments(
+                                    "The escape of LIKE must be a single \
+                                     character"
+                                        .to_string(),
+                                ))
+                            }
+                        }
+                    }
+                    None => None,
+                };
+                Ok(bool_or_null(value.zip(pattern).map(|(value, pattern)| {
+                    like(&value, &pattern, escape) != *negated
+                })))
+            }
+            Expr::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => {
+                let value = self.eval(expr)?;
+                let low = compare(&value, &self.eval(low)?);
+                let high = compare(&value, &self.eval(high)?);
+                Ok(bool_or_null(low.zip(high).map(|(low, high)| {
+                    (low.is_ge() && high.is_le()) != *negated
+                })))
+            }
+            Expr::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                let value = self.eval(expr)?;
+                let mut result = Some(false);
+                for elt in list {
+                    match compare(&value, &self.eval(elt)?) {
+                        Some(Ordering::Equal) => {
+                            result = Some(true);
+                            break;
+                        }
+                        Some(_) => {}
+                        None => result = None,
+                    }
+                }
+                Ok(bool_or_null(result.map(|x| x != *negated)))
+            }
+            Expr::Cast { expr, data_type } => {
+                cast(self.eval(expr)?, *data_type)
+            }
+            Expr::Function { function, args } => {
+                if *function == Function::Coalesce {
+                    for arg in args {
+                        let value = self.eval(arg)?;
+                        if value != Value::Null {
+                            return Ok(value);
+                        }
+                    }
+                    return Ok(Value::Null);
+                }
+
+                let Some(value) = self.eval(&args[0])?.into_text() else {
+                    return Ok(Value::Null);
+                };
+                Ok(match function {
+                    Function::Lower => Value::String(value.to_lowercase()),
+                    Function::Upper => Value::String(value.to_uppercase()),
+                    Function::Trim => Value::String(value.trim().to_string()),
+                    _ => Value::Int(value.chars().count() as i64),
+                })
+            }
+        }
+    }
+
+    /// Whether the record matches the condition, a `NULL` doesn't match.
+    pub fn matches(&self, condition: &Expr) -> Result<bool, SelectError> {
+        Ok(self.eval(condition)?.to_bool()? == Some(true))
+    }
+}
+
+fn bool_or_null(value: Option<bool>) -> Value {
+    value.map(Value::Bool).unwrap_or(Value::Null)
+}
+
+fn arithmetic(
+    op: BinaryOperator,
+    left: Value,
+    right: Value,
+) -> Result<Value, SelectError> {
+    let (Some(left), Some(right)) = (left.to_number()?, right.to_number()?)
+    else {
+        return Ok(Value::Null);
+    };
+
+    if let (Value::Int(x), Value::Int(y)) = (&left, &right) {
+        let result = match op {
+            BinaryOperator::Plus => x.checked_add(*y),
+            BinaryOperator::Minus => x.checked_sub(*y),
+            BinaryOperator::Multiply => x.checked_mul(*y),
+            BinaryOperator::Divide | BinaryOperator::Modulo if *y == 0 => {
+                return Err(SelectError::InvalidArguments(
+                    "Division by zero".to_string(),
+                ))
+            }
+            BinaryOperator::Divide => x.checked_div(*y),
+            _ => x.checked_rem(*y),
+        };
+        return result.map(Value::Int).ok_or(SelectError::IntegerOverflow);
+    }
+
+    let (x, y) = (as_f64(&left), as_f64(&right));
+    Ok(Value::Float(match op {
+        BinaryOperator::Plus => x + y,
+        BinaryOperator::Minus => x - y,
+        BinaryOperator::Multiply => x * y,
+        BinaryOperator::Divide => x / y,
+        _ => x % y,
+    }))
+}
+
+fn cast(value: Value, data_type: DataType) -> Result<Value, SelectError> {
+    let failed = || {
+        SelectError::CastFailed(format!(
+            "Attempt to convert from one data type to another using CAST \
+             failed in the SQL expression: {data_type:?}"
+        ))
+    };
+
+    Ok(match (value, data_type) {
+        (Value::Null, _) => Value::Null,
+        (value, DataType::String) => {
+            Value::String(value.into_text().unwrap_or_default())
+        }
+        (Value::Int(x), DataType::Int) => Value::Int(x),
+        (Value::Float(x), DataType::Int) => Value::Int(x.trunc() as i64),
+        (Value::Bool(x), DataType::Int) => Value::Int(x as i64),
+        (Value::String(x), DataType::Int) => match parse_number(&x) {
+            Some(Value::Int(x)) => Value::Int(x),
+            Some(Value::Float(x)) => Value::Int(x.trunc() as i64),
+            _ => return Err(failed()),
+        },
+        (Value::Int(x), DataType::Float) => Value::Float(x as f64),
+        (Value::Float(x), DataType::Float) => Value::Float(x),
+        (Value::String(x), DataType::Float) => match parse_number(&x) {
+            Some(value) => Value::Float(as_f64(&value)),
+            None => return Err(failed()),
+        },
+        (Value::Bool(x), DataType::Bool) => Value::Bool(x),
+        (Value::Int(x), DataType::Bool) => Value::Bool(x != 0),
+        (value @ Value::String(_), DataType::Bool) => {
+            Value::Bool(value.to_bool().map_err(|_| failed())?.unwrap_or(false))
+        }
+        _ => return Err(failed()),
+    })
+}
+
+/// Match a `LIKE` pattern, `%` matches any sequence and `_` any character.
+fn like(value: &str, pattern: &str, escape: Option<char>) -> bool {
+    enum Part {
+        Any,
+        One,
+        Char(char),
+    }
+
+    let mut parts = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        parts.push(match c {
+            c if Some(c) == escape => match chars.next() {
+                Some(x) => Part::Char(x),
+                None => Part::Char(c),
+            },
+            '%' => Part::Any,
+            '_' => Part::One,
+            c => Part::Char(c),
+        });
+    }
+
+    // Greedy matching which backtracks to the last `%`.
+    let value: Vec<char> = value.chars().collect();
+    let (mut v, mut p) = (0, 0);
+    let mut backtrack = None;
+    while v < value.len() {
+        match parts.get(p) {
+            Some(Part::Any) => {
+                backtrack = Some((p, v));
+                p += 1;
+            }
+            Some(Part::One) => {
+                v += 1;
+                p += 1;
+            }
+            Some(Part::Char(c)) if *c == value[v] => {
+                v += 1;
+                p += 1;
+            }
+            _ => match backtrack {
+                Some((any, start)) => {
+                    backtrack = Some((any, start + 1));
+                    p = any + 1;
+                    v = start + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    parts[p..].iter().all(|x| matches!(x, Part::Any))
+}
+
+/// Accumulates the values of an aggregate over the records.
+pub struct Accumulator {
+    function: Aggregate,
+    count: i64,
+    value: Option<Value>,
+}
+
+impl Accumulator {
+    pub fn new(function: Aggregate) -> Self {
+        Self {
+            function,
+            count: 0,
+            value: None,
+        }
+    }
+
+    /// Add the value of a record, `None` for `COUNT(*)`.
+    pub fn update(&mut self, value: Option<Value>) -> Result<(), SelectError> {
+        let value = match value {
+            None => {
+                self.count += 1;
+                return Ok(());
+            }
+            Some(Value::Null) => return Ok(()),
+            Some(value) => value,
+        };
+        self.count += 1;
+
+        self.value = Some(match (self.function, self.value.take()) {
+            (Aggregate::Count, _) => return Ok(()),
+            (Aggregate::Sum | Aggregate::Avg, None) => {
+                value.to_number()?.expect("a null value is already skipped")
+            }
+            (Aggregate::Sum | Aggregate::Avg, Some(sum)) => {
+                arithmetic(BinaryOperator::Plus, sum, value)?
+            }
+            (Aggregate::Min | Aggregate::Max, None) => value,
+            (Aggregate::Min | Aggregate::Max, Some(current)) => {
+                let ordering = compare(&value, &current);
+                let replace = match self.function {
+                    Aggregate::Min => ordering.is_some_and(Ordering::is_lt),
+                    _ => ordering.is_some_and(Ordering::is_gt),
+                };
+                match replace {
+                    true => value,
+                    false => current,
+                }
+            }
+        });
+        Ok(())
+    }
+
+    pub fn finish(&self) -> Value {
+        match self.function {
+            Aggregate::Count => Value::Int(self.count),
+            Aggregate::Avg => match &self.value {
+                Some(sum) => Value::Float(as_f64(sum) / self.count as f64),
+                None => Value::Null,
+            },
+            _ => self.value.clone().unwrap_or(Value::Null),
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), not(target_os = "wasi")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn like_patterns() {
+        assert!(like("hello", "h%o", None));
+        assert!(like("hello", "_ello", None));
+        assert!(like("hello", "%l%l%", None));
+        assert!(!like("hello", "h%x", None));
+        assert!(like("50%", "50!%", Some('!')));
+        assert!(!like("500", "50!%", Some('!')));
+    }
+
+    #[test]
+    fn compare_csv_fields_as_numbers() {
+        let ten = Value::String("10".to_string());
+        assert_eq!(compare(&ten, &Value::Int(9)), Some(Ordering::Greater));
+        assert_eq!(
+            compare(&ten, &Value::String("9".to_string())),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            compare(&Value::String("a".to_string()), &Value::Int(9)),
+            None
+        );
+    }
+}
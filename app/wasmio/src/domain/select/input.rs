@@ -0,0 +1,387 @@
+//! Readers which split the object into records.
+
+use std::sync::Arc;
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+use wasmio_aws_types::types::InputSerialization;
+
+use super::eval::Record;
+use super::SelectError;
+
+/// Size of the chunks read from the object.
+const READ_SIZE: usize = 64 * 1024;
+
+/// How the records of the object are serialized.
+#[derive(Debug, Clone)]
+pub enum InputFormat {
+    Csv(CsvInputFormat),
+    /// JSON values separated by whitespaces, it covers both the `DOCUMENT`
+    /// and the `LINES` types.
+    Json,
+}
+
+#[derive(Debug, Clone)]
+pub struct CsvInputFormat {
+    header: FileHeaderInfo,
+    comments: Option<Vec<u8>>,
+    quote_escape: Vec<u8>,
+    record_delimiter: Vec<u8>,
+    field_delimiter: Vec<u8>,
+    quote: Vec<u8>,
+    allow_quoted_record_delimiter: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileHeaderInfo {
+    None,
+    Ignore,
+    Use,
+}
+
+/// The parameter if it's set, the default value otherwise.
+fn delimiter(value: &Option<String>, default: &str) -> Vec<u8> {
+    value
+        .as_deref()
+        .filter(|x| !x.is_empty())
+        .unwrap_or(default)
+        .as_bytes()
+        .to_vec()
+}
+
+impl InputFormat {
+    pub fn new(input: &InputSerialization) -> Result<Self, SelectError> {
+        if input
+            .compression_type
+            .as_deref()
+            .is_some_and(|x| !x.eq_ignore_ascii_case("NONE"))
+        {
+            return Err(SelectError::InvalidCompressionFormat);
+        }
+
+        match (&input.csv, &input.json, &input.parquet) {
+            (Some(csv), None, None) => {
+                let header = match csv.file_header_info.as_deref() {
+                    None => FileHeaderInfo::None,
+                    Some(x) if x.eq_ignore_ascii_case("NONE") => {
+                        FileHeaderInfo::None
+                    }
+                    Some(x) if x.eq_ignore_ascii_case("IGNORE") => {
+                        FileHeaderInfo::Ignore
+                    }
+                    Some(x) if x.eq_ignore_ascii_case("USE") => {
+                        FileHeaderInfo::Use
+                    }
+                    Some(_) => return Err(SelectError::InvalidFileHeaderInfo),
+                };
+                let quote = delimiter(&csv.quote_character, "\"");
+                Ok(InputFormat::Csv(CsvInputFormat {
+                    header,
+                    comments: csv
+                        .comments
+                        .as_deref()
+                        .filter(|x| !x.is_empty())
+                        .map(|x| x.as_bytes().to_vec()),
+                    quote_escape: delimiter(
+                        &csv.quote_escape_character,
+                        std::str::from_utf8(&quote).unwrap_or("\""),
+                    ),
+                    record_delimiter: delimiter(&csv.record_delimiter, "\n"),
+                    field_delimiter: delimiter(&csv.field_delimiter, ","),
+                    quote,
+                    allow_quoted_record_delimiter: csv
+                        .allow_quoted_record_delimiter
+                        .unwrap_or(false),
+                }))
+            }
+            (None, Some(json), None) => match json.type_.as_deref() {
+                Some(x)
+                    if x.eq_ignore_ascii_case("DOCUMENT")
+                        || x.eq_ignore_ascii_case("LINES") =>
+                {
+                    Ok(InputFormat::Json)
+                }
+                _ => Err(SelectError::InvalidRequestParameter(
+                    "The JSON type must be DOCUMENT or LINES".to_string(),
+                )),
+            },
+            (None, None, Some(_)) => Err(SelectError::InvalidRequestParameter(
+                "Parquet objects are not supported".to_string(),
+            )),
+            _ => Err(SelectError::ObjectSerializationConflict),
+        }
+    }
+
+    pub fn reader<R: AsyncRead + Unpin>(&self, reader: R) -> RecordReader<R> {
+        let bytes = ByteReader {
+            inner: reader,
+            buffer: Vec::new(),
+            pos: 0,
+            eof: false,
+            scanned: 0,
+        };
+        match self {
+            InputFormat::Csv(format) => RecordReader::Csv {
+                format: format.clone(),
+                header: None,
+                started: false,
+                bytes,
+            },
+            InputFormat::Json => RecordReader::Json { bytes },
+        }
+    }
+}
+
+/// A buffered reader with a lookahead over the bytes of the object.
+pub struct ByteReader<R> {
+    inner: R,
+    buffer: Vec<u8>,
+    pos: usize,
+    eof: bool,
+    scanned: u64,
+}
+
+impl<R: AsyncRead + Unpin> ByteReader<R> {
+    /// The byte at `offset` from the current position.
+    async fn peek(&mut self, offset: usize) -> Result<Option<u8>, SelectError> {
+        while self.pos + offset >= self.buffer.len() && !self.eof {
+            self.buffer.drain(..self.pos);
+            self.pos = 0;
+
+            let len = self.buffer.len();
+            self.buffer.resize(len + READ_SIZE, 0);
+            let read = self.inner.read(&mut self.buffer[len..]).await;
+            let read = read.map_err(|_| {
+                SelectError::Internal("The object can't be read".to_string())
+            })?;
+            self.buffer.truncate(len + read);
+            self.scanned += read as u64;
+            self.eof = read == 0;
+        }
+        Ok(self.buffer.get(self.pos + offset).copied())
+    }
+
+    async fn starts_with(
+        &mut self,
+        offset: usize,
+        pattern: &[u8],
+    ) -> Result<bool, SelectError> {
+        for (i, x) in pattern.iter().enumerate() {
+            if self.peek(offset + i).await? != Some(*x) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn advance(&mut self, len: usize) {
+        self.pos += len;
+    }
+}
+
+pub enum RecordReader<R> {
+    Csv {
+        format: CsvInputFormat,
+        header: Option<Arc<Vec<String>>>,
+        /// Whether the header line was read.
+        started: bool,
+        bytes: ByteReader<R>,
+    },
+    Json {
+        bytes: ByteReader<R>,
+    },
+}
+
+impl<R: AsyncRead + Unpin> RecordReader<R> {
+    /// Number of bytes read from the object.
+    pub fn scanned(&self) -> u64 {
+        match self {
+            RecordReader::Csv { bytes, .. } | RecordReader::Json { bytes } => {
+                bytes.scanned
+            }
+        }
+    }
+
+    pub async fn next(&mut self) -> Result<Option<Record>, SelectError> {
+        match self {
+            RecordReader::Csv {
+                format,
+                header,
+                started,
+                bytes,
+            } => {
+                if !*started {
+                    *started = true;
+                    let first = next_csv_fields(format, bytes).await?;
+                    match (format.header, first) {
+                        (FileHeaderInfo::Use, Some(fields)) => {
+                            *header = Some(Arc::new(fields));
+                        }
+                        (FileHeaderInfo::Ignore, _) | (_, None) => {}
+                        (FileHeaderInfo::None, Some(fields)) => {
+                            return Ok(Some(Record::Csv {
+                                header: None,
+                                fields,
+                            }));
+                        }
+                    }
+                }
+                Ok(next_csv_fields(format, bytes).await?.map(|fields| {
+                    Record::Csv {
+                        header: header.clone(),
+                        fields,
+                    }
+                }))
+            }
+            RecordReader::Json { bytes } => next_json_value(bytes).await,
+        }
+    }
+}
+
+async fn next_csv_fields<R: AsyncRead + Unpin>(
+    format: &CsvInputFormat,
+    bytes: &mut ByteReader<R>,
+) -> Result<Option<Vec<String>>, SelectError> {
+    loop {
+        if bytes.peek(0).await?.is_none() {
+            return Ok(None);
+        }
+
+        // Empty lines and comments are skipped.
+        let skipped = match &format.comments {
+            Some(comments) => bytes.starts_with(0, comments).await?,
+            None => false,
+        };
+        if skipped || bytes.starts_with(0, &format.record_delimiter).await? {
+            while bytes.peek(0).await?.is_some()
+                && !bytes.starts_with(0, &format.record_delimiter).await?
+            {
+                bytes.advance(1);
+            }
+            bytes.advance(format.record_delimiter.len());
+            continue;
+        }
+
+        let mut fields = Vec::new();
+        let mut field = Vec::new();
+        let mut quoted = false;
+        let mut field_start = true;
+        loop {
+            let Some(byte) = bytes.peek(0).await? else {
+                break;
+            };
+
+            if quoted {
+                let quote = &format.quote;
+                let escape = &format.quote_escape;
+                if bytes.starts_with(0, escape).await?
+                    && bytes.starts_with(escape.len(), quote).await?
+                {
+                    field.extend_from_slice(quote);
+                    bytes.advance(escape.len() + quote.len());
+                } else if bytes.starts_with(0, quote).await? {
+                    quoted = false;
+                    bytes.advance(quote.len());
+                } else if !format.allow_quoted_record_delimiter
+                    && bytes.starts_with(0, &format.record_delimiter).await?
+                {
+                    return Err(SelectError::CsvParsing(
+                        "A quoted field contains a record delimiter, set \
+                         AllowQuotedRecordDelimiter to allow it"
+                            .to_string(),
+                    ));
+                } else {
+                    field.push(byte);
+                    bytes.advance(1);
+                }
+            } else if field_start && bytes.starts_with(0, &format.quote).await?
+            {
+                quoted = true;
+                field_start = false;
+                bytes.advance(format.quote.len());
+            } else if bytes.starts_with(0, &format.field_delimiter).await? {
+                fields.push(std::mem::take(&mut field));
+                field_start = true;
+                bytes.advance(format.field_delimiter.len());
+            } else if bytes.starts_with(0, &format.record_delimiter).await? {
+                bytes.advance(format.record_delimiter.len());
+                break;
+            } else {
+                field.push(byte);
+                field_start = false;
+                bytes.advance(1);
+            }
+        }
+        if quoted {
+            return Err(SelectError::CsvParsing(
+                "A quoted field is not terminated".to_string(),
+            ));
+        }
+        // Windows line endings with the default record delimiter.
+        if format.record_delimiter == b"\n" && field.last() == Some(&b'\r') {
+            field.pop();
+        }
+        fields.push(field);
+
+        let fields = fields
+            .into_iter()
+            .map(String::from_utf8)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| {
+                SelectError::CsvParsing(
+                    "The object is not encoded in UTF-8".to_string(),
+                )
+            })?;
+        return Ok(Some(fields));
+    }
+}
+
+/// Read the next JSON value, the values are delimited by tracking the
+/// nesting of objects, arrays and strings.
+async fn next_json_value<R: AsyncRead + Unpin>(
+    bytes: &mut ByteReader<R>,
+) -> Result<Option<Record>, SelectError> {
+    while bytes
+        .peek(0)
+        .await?
+        .is_some_and(|x| x.is_ascii_whitespace())
+    {
+        bytes.advance(1);
+    }
+
+    let mut value = Vec::new();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    while let Some(byte) = bytes.peek(0).await? {
+        if in_string {
+            match (escaped, byte) {
+                (true, _) => escaped = false,
+                (false, b'\\') => escaped = true,
+                (false, b'"') => in_string = false,
+                _ => {}
+            }
+        } else {
+            match byte {
+                b'"' => in_string = true,
+                b'{' | b'[' => depth += 1,
+                b'}' | b']' => depth = depth.saturating_sub(1),
+                x if x.is_ascii_whitespace() && depth == 0 => break,
+                _ => {}
+            }
+        }
+        value.push(byte);
+        bytes.advance(1);
+
+        if depth == 0 && !in_string && matches!(byte, b'}' | b']') {
+            break;
+        }
+    }
+
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let value = serde_json::from_slice(&value).map_err(|err| {
+        SelectError::JsonParsing(format!("The object is not valid JSON: {err}"))
+    })?;
+    Ok(Some(Record::Json(value)))
+}
@@ -0,0 +1,439 @@
+//! S3 Select: SQL queries over the CSV and JSON objects.
+//!
+//! The object is streamed, split into records which are filtered and
+//! projected by the query, and the rows are serialized in chunks sent as
+//! events.
+
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use tokio::io::AsyncRead;
+use wasmio_aws_types::types::{SelectObjectContentRequest, Stats};
+
+use self::eval::{Accumulator, Scope, Value};
+use self::input::InputFormat;
+use self::output::OutputFormat;
+use self::sql::{Expr, Projection, Query};
+
+pub mod eval;
+mod input;
+mod output;
+pub mod sql;
+
+/// Rows are buffered until this size before being sent in a `Records` event.
+const RECORDS_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The only supported type of expression.
+const EXPRESSION_TYPE_SQL: &str = "SQL";
+
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum SelectError {
+    #[error("The expression type is not supported, only SQL is")]
+    InvalidExpressionType,
+    #[error("The compression format is not supported")]
+    InvalidCompressionFormat,
+    #[error("The FileHeaderInfo is not valid")]
+    InvalidFileHeaderInfo,
+    #[error("A parameter of the request is not valid: {0}")]
+    InvalidRequestParameter(String),
+    #[error("The serialization must specify exactly one format")]
+    ObjectSerializationConflict,
+    #[error("The scan ranges are not supported")]
+    UnsupportedScanRange,
+    #[error("The expression can't be parsed: {0}")]
+    Parse(String),
+    #[error("The expression is not supported: {0}")]
+    UnsupportedSyntax(String),
+    #[error("The function {0} is not supported")]
+    UnsupportedFunction(String),
+    #[error("The CSV object can't be parsed: {0}")]
+    CsvParsing(String),
+    #[error("The JSON object can't be parsed: {0}")]
+    JsonParsing(String),
+    #[error("{0}")]
+    CastFailed(String),
+    #[error("The arguments are not valid: {0}")]
+    InvalidArguments(String),
+    #[error("An integer overflowed")]
+    IntegerOverflow,
+    #[error("An issue happened: {0}")]
+    Internal(String),
+}
+
+/// The events sent while the query runs.
+#[derive(Debug, Clone)]
+pub enum SelectEvent {
+    /// A chunk of serialized rows.
+    Records(Bytes),
+    /// Sent periodically when the progress is requested.
+    Progress(Stats),
+    /// Sent once all the records are processed.
+    Stats(Stats),
+    /// The last event of the query.
+    End,
+}
+
+/// A query ready to run over an object.
+#[derive(Debug)]
+pub struct Select {
+    query: Query,
+    input: InputFormat,
+    output: OutputFormat,
+    progress: bool,
+}
+
+impl Select {
+    pub fn new(
+        request: &SelectObjectContentRequest,
+    ) -> Result<Self, SelectError> {
+        if !request
+            .expression_type
+            .eq_ignore_ascii_case(EXPRESSION_TYPE_SQL)
+        {
+            return Err(SelectError::InvalidExpressionType);
+        }
+        if request.scan_range.is_some() {
+            return Err(SelectError::UnsupportedScanRange);
+        }
+
+        Ok(Self {
+            query: sql::parse(&request.expression)?,
+            input: InputFormat::new(&request.input_serialization)?,
+            output: OutputFormat::new(&request.output_serialization)?,
+            progress: request
+                .request_progress
+                .as_ref()
+                .and_then(|x| x.enabled)
+                .unwrap_or(false),
+        })
+    }
+
+    /// Run the query over the content of the object, the stream ends after
+    /// the first error.
+    pub fn run<R>(
+        self,
+        reader: R,
+    ) -> BoxStream<'static, Result<SelectEvent, SelectError>>
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        async_stream::try_stream! {
+            let Select { query, input, output, progress } = self;
+            let alias = query.alias.as_deref();
+            let mut records = input.reader(reader);
+
+            let mut aggregates = Vec::new();
+            if let Projection::Items(items) = &query.projection {
+                for item in items {
+                    item.expr.aggregates(&mut aggregates);
+                }
+            }
+            let mut accumulators: Vec<Accumulator> = aggregates
+                .iter()
+                .map(|x| match x {
+                    Expr::Aggregate { function, .. } => {
+                        Accumulator::new(*function)
+                    }
+                    _ => unreachable!("only aggregates are collected"),
+                })
+                .collect();
+            let is_aggregate = !aggregates.is_empty();
+
+            let mut buffer = Vec::new();
+            let mut returned = 0;
+            let mut matched = 0;
+            loop {
+                if !is_aggregate && query.limit.is_some_and(|x| matched >= x) {
+                    break;
+                }
+                let record = match records.next().await? {
+                    Some(record) => record,
+                    None => break,
+                };
+
+                let scope = Scope {
+                    record: Some(&record),
+                    alias,
+                    aggregates: &[],
+                };
+                if let Some(selection) = &query.selection {
+                    if !scope.matches(selection)? {
+                        continue;
+                    }
+                }
+                matched += 1;
+
+                if is_aggregate {
+                    for (expr, accumulator) in
+                        aggregates.iter().zip(accumulators.iter_mut())
+                    {
+                        let Expr::Aggregate { arg, .. } = expr else {
+                            unreachable!("only aggregates are collected");
+                        };
+                        let value = match arg {
+                            Some(arg) => Some(scope.eval(arg)?),
+                            None => None,
+                        };
+                        accumulator.update(value)?;
+                    }
+                    continue;
+                }
+
+                let row = match project(&query, &scope)? {
+                    Some(row) => row,
+                    None => record.columns(),
+                };
+                output.write(row, &mut buffer);
+                if buffer.len() >= RECORDS_CHUNK_SIZE {
+                    returned += buffer.len() as i64;
+                    yield SelectEvent::Records(std::mem::take(&mut buffer).into());
+                    if progress {
+                        yield SelectEvent::Progress(stats(records.scanned(), returned));
+                    }
+                }
+            }
+
+            if is_aggregate && query.limit != Some(0) {
+                let results: Vec<(&Expr, Value)> = aggregates
+                    .iter()
+                    .zip(accumulators.iter())
+                    .map(|(expr, accumulator)| (*expr, accumulator.finish()))
+                    .collect();
+                let scope = Scope {
+                    record: None,
+                    alias,
+                    aggregates: &results,
+                };
+                if let Some(row) = project(&query, &scope)? {
+                    output.write(row, &mut buffer);
+                }
+            }
+
+            if !buffer.is_empty() {
+                returned += buffer.len() as i64;
+                yield SelectEvent::Records(buffer.into());
+            }
+            yield SelectEvent::Stats(stats(records.scanned(), returned));
+            yield SelectEvent::End;
+        }
+        .boxed()
+    }
+}
+
+/// The row of the projection, `None` for `SELECT *`.
+fn project(
+    query: &Query,
+    scope: &Scope<'_>,
+) -> Result<Option<Vec<(String, Value)>>, SelectError> {
+    let Projection::Items(items) = &query.projection else {
+        return Ok(None);
+    };
+
+    let mut row = Vec::with_capacity(items.len());
+    for (i, item) in items.iter().enumerate() {
+        // Unnamed expressions are named by their position.
+        let name = match (&item.alias, &item.expr) {
+            (Some(alias), _) => alias.clone(),
+            (None, Expr::Column(path)) => {
+                path.last().map(|x| x.name.clone()).unwrap_or_default()
+            }
+            _ => format!("_{}", i + 1),
+        };
+        row.push((name, scope.eval(&item.expr)?));
+    }
+    Ok(Some(row))
+}
+
+fn stats(scanned: u64, returned: i64) -> Stats {
+    Stats {
+        bytes_scanned: scanned as i64,
+        bytes_processed: scanned as i64,
+        bytes_returned: returned,
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), not(target_os = "wasi")))]
+mod tests {
+    use std::io::Cursor;
+
+    use futures::TryStreamExt;
+    use wasmio_aws_types::types::{
+        CSVInput, CSVOutput, InputSerialization, JSONInput, JSONOutput,
+        OutputSerialization,
+    };
+
+    use super::*;
+
+    const CSV: &str = "name,city,age\nAlice,Paris,31\nBob,\"New York, \
+                       NY\",25\nCarol,Paris,45\n";
+
+    fn request(
+        expression: &str,
+        input: InputSerialization,
+        output: OutputSerialization,
+    ) -> SelectObjectContentRequest {
+        SelectObjectContentRequest {
+            expression: expression.to_string(),
+            expression_type: "SQL".to_string(),
+            input_serialization: input,
+            output_serialization: output,
+            ..Default::default()
+        }
+    }
+
+    fn csv_input() -> InputSerialization {
+        InputSerialization {
+            csv: Some(CSVInput {
+                file_header_info: Some("USE".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn csv_output() -> OutputSerialization {
+        OutputSerialization {
+            csv: Some(CSVOutput::default()),
+            ..Default::default()
+        }
+    }
+
+    fn json_output() -> OutputSerialization {
+        OutputSerialization {
+            json: Some(JSONOutput::default()),
+            ..Default::default()
+        }
+    }
+
+    /// Run the query and concatenate the records.
+    async fn run(
+        request: SelectObjectContentRequest,
+        content: &str,
+    ) -> Result<String, SelectError> {
+        let events: Vec<SelectEvent> = Select::new(&request)?
+            .run(Cursor::new(content.as_bytes().to_vec()))
+            .try_collect()
+            .await?;
+        assert!(matches!(events.last(), Some(SelectEvent::End)));
+
+        let mut records = String::new();
+        for event in events {
+            if let SelectEvent::Records(bytes) = event {
+                records.push_str(std::str::from_utf8(&bytes).unwrap());
+            }
+        }
+        Ok(records)
+    }
+
+    #[tokio::test]
+    async fn select_csv() {
+        let records = run(
+            request(
+                "SELECT s.name, s.city FROM S3Object s WHERE s.age > 30",
+                csv_input(),
+                csv_output(),
+            ),
+            CSV,
+        )
+        .await
+        .unwrap();
+        assert_eq!(records, "Alice,Paris\nCarol,Paris\n");
+
+        let records = run(
+            request(
+                "SELECT * FROM S3Object WHERE city LIKE 'New%' LIMIT 1",
+                csv_input(),
+                json_output(),
+            ),
+            CSV,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            records,
+            "{\"name\":\"Bob\",\"city\":\"New York, NY\",\"age\":\"25\"}\n"
+        );
+
+        let records = run(
+            request("SELECT _1, _2 FROM S3Object", csv_input(), csv_output()),
+            CSV,
+        )
+        .await
+        .unwrap();
+        assert_eq!(records, "Alice,Paris\nBob,\"New York, NY\"\nCarol,Paris\n");
+    }
+
+    #[tokio::test]
+    async fn select_aggregates() {
+        let records = run(
+            request(
+                "SELECT COUNT(*), SUM(age), AVG(age), MAX(name) FROM S3Object \
+                 WHERE city = 'Paris'",
+                csv_input(),
+                csv_output(),
+            ),
+            CSV,
+        )
+        .await
+        .unwrap();
+        assert_eq!(records, "2,76,38,Carol\n");
+    }
+
+    #[tokio::test]
+    async fn select_json_lines() {
+        let input = InputSerialization {
+            json: Some(JSONInput {
+                type_: Some("LINES".to_string()),
+            }),
+            ..Default::default()
+        };
+        let content = concat!(
+            r#"{"user":{"name":"Alice"},"score":1.5}"#,
+            "\n",
+            r#"{"user":{"name":"Bob"},"score":3}"#,
+            "\n",
+        );
+
+        let records = run(
+            request(
+                "SELECT s.user.name AS who, s.score * 2 FROM S3Object s WHERE \
+                 s.score > 2",
+                input.clone(),
+                json_output(),
+            ),
+            content,
+        )
+        .await
+        .unwrap();
+        assert_eq!(records, "{\"who\":\"Bob\",\"_2\":6}\n");
+
+        let err = run(
+            request("SELECT * FROM S3Object", input, csv_output()),
+            "{\"broken\": }",
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, SelectError::JsonParsing(_)));
+    }
+
+    #[test]
+    fn invalid_requests() {
+        let mut invalid =
+            request("SELECT * FROM S3Object", csv_input(), csv_output());
+        invalid.expression_type = "XPATH".to_string();
+        assert!(matches!(
+            Select::new(&invalid),
+            Err(SelectError::InvalidExpressionType)
+        ));
+
+        let invalid = request(
+            "SELECT * FROM S3Object",
+            InputSerialization::default(),
+            csv_output(),
+        );
+        assert!(matches!(
+            Select::new(&invalid),
+            Err(SelectError::ObjectSerializationConflict)
+        ));
+    }
+}
@@ -0,0 +1,128 @@
+//! Serialization of the rows returned by the query.
+
+use wasmio_aws_types::types::OutputSerialization;
+
+use super::eval::Value;
+use super::SelectError;
+
+/// How the rows are serialized in the `Records` events.
+#[derive(Debug, Clone)]
+pub enum OutputFormat {
+    Csv {
+        quote_fields: QuoteFields,
+        quote_escape: String,
+        record_delimiter: String,
+        field_delimiter: String,
+        quote: String,
+    },
+    Json {
+        record_delimiter: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteFields {
+    Always,
+    AsNeeded,
+}
+
+/// The parameter if it's set, the default value otherwise.
+fn delimiter(value: &Option<String>, default: &str) -> String {
+    value
+        .as_deref()
+        .filter(|x| !x.is_empty())
+        .unwrap_or(default)
+        .to_string()
+}
+
+impl OutputFormat {
+    pub fn new(output: &OutputSerialization) -> Result<Self, SelectError> {
+        match (&output.csv, &output.json) {
+            (Some(csv), None) => {
+                let quote_fields = match csv.quote_fields.as_deref() {
+                    None => QuoteFields::AsNeeded,
+                    Some(x) if x.eq_ignore_ascii_case("ASNEEDED") => {
+                        QuoteFields::AsNeeded
+                    }
+                    Some(x) if x.eq_ignore_ascii_case("ALWAYS") => {
+                        QuoteFields::Always
+                    }
+                    Some(_) => {
+                        return Err(SelectError::InvalidRequestParameter(
+                            "QuoteFields must be ALWAYS or ASNEEDED"
+                                .to_string(),
+                        ))
+                    }
+                };
+                let quote = delimiter(&csv.quote_character, "\"");
+                Ok(OutputFormat::Csv {
+                    quote_fields,
+                    quote_escape: delimiter(
+                        &csv.quote_escape_character,
+                        &quote,
+                    ),
+                    record_delimiter: delimiter(&csv.record_delimiter, "\n"),
+                    field_delimiter: delimiter(&csv.field_delimiter, ","),
+                    quote,
+                })
+            }
+            (None, Some(json)) => Ok(OutputFormat::Json {
+                record_delimiter: delimiter(&json.record_delimiter, "\n"),
+            }),
+            _ => Err(SelectError::ObjectSerializationConflict),
+        }
+    }
+
+    /// Serialize a row in the buffer.
+    pub fn write(&self, row: Vec<(String, Value)>, buffer: &mut Vec<u8>) {
+        match self {
+            OutputFormat::Csv {
+                quote_fields,
+                quote_escape,
+                record_delimiter,
+                field_delimiter,
+                quote,
+            } => {
+                for (i, (_, value)) in row.into_iter().enumerate() {
+                    if i > 0 {
+                        buffer.extend_from_slice(field_delimiter.as_bytes());
+                    }
+                    let value = value.into_text().unwrap_or_default();
+                    let quoted = *quote_fields == QuoteFields::Always
+                        || value.contains(field_delimiter.as_str())
+                        || value.contains(record_delimiter.as_str())
+                        || value.contains(quote.as_str())
+                        || value.contains(['\n', '\r']);
+                    if quoted {
+                        let escaped = format!("{quote_escape}{quote}");
+                        buffer.extend_from_slice(quote.as_bytes());
+                        buffer.extend_from_slice(
+                            value.replace(quote.as_str(), &escaped).as_bytes(),
+                        );
+                        buffer.extend_from_slice(quote.as_bytes());
+                    } else {
+                        buffer.extend_from_slice(value.as_bytes());
+                    }
+                }
+                buffer.extend_from_slice(record_delimiter.as_bytes());
+            }
+            OutputFormat::Json { record_delimiter } => {
+                // Written by hand to keep the order of the columns.
+                buffer.push(b'{');
+                for (i, (name, value)) in row.into_iter().enumerate() {
+                    if i > 0 {
+                        buffer.push(b',');
+                    }
+                    let name = serde_json::Value::String(name);
+                    buffer.extend_from_slice(name.to_string().as_bytes());
+                    buffer.push(b':');
+                    buffer.extend_from_slice(
+                        value.into_json().to_string().as_bytes(),
+                    );
+                }
+                buffer.push(b'}');
+                buffer.extend_from_slice(record_delimiter.as_bytes());
+            }
+        }
+    }
+}
@@ -0,0 +1,928 @@
+//! Parser of the SQL subset supported by S3 Select.
+//!
+//! ```sql
+//! SELECT <* | expression [AS alias], ...>
+//! FROM S3Object [[AS] alias]
+//! [WHERE expression]
+//! [LIMIT number]
+//! ```
+//!
+//! Expressions support literals, column references (`_1`, `name`,
+//! `s.address.city`, `"Quoted Name"`), arithmetic, comparisons, `AND`, `OR`,
+//! `NOT`, `LIKE`, `BETWEEN`, `IN`, `IS [NOT] NULL`, `CAST`, a few scalar
+//! functions and the `COUNT`, `SUM`, `AVG`, `MIN` and `MAX` aggregates.
+
+use super::eval::Value;
+use super::SelectError;
+
+/// Name of the table, it's the object which is queried.
+pub const TABLE_NAME: &str = "S3Object";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    QuotedIdent(String),
+    String(String),
+    Int(i64),
+    Float(f64),
+    Star,
+    Comma,
+    Dot,
+    LParen,
+    RParen,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    Plus,
+    Minus,
+    Slash,
+    Percent,
+    Concat,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, SelectError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '*' => Token::Star,
+            ',' => Token::Comma,
+            '.' => Token::Dot,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '/' => Token::Slash,
+            '%' => Token::Percent,
+            '=' => Token::Eq,
+            '!' => {
+                chars.next();
+                match chars.peek() {
+                    Some('=') => Token::NotEq,
+                    _ => return Err(unexpected("!")),
+                }
+            }
+            '<' => {
+                chars.next();
+                match chars.peek() {
+                    Some('=') => Token::LtEq,
+                    Some('>') => Token::NotEq,
+                    _ => {
+                        tokens.push(Token::Lt);
+                        continue;
+                    }
+                }
+            }
+            '>' => {
+                chars.next();
+                match chars.peek() {
+                    Some('=') => Token::GtEq,
+                    _ => {
+                        tokens.push(Token::Gt);
+                        continue;
+                    }
+                }
+            }
+            '|' => {
+                chars.next();
+                match chars.peek() {
+                    Some('|') => Token::Concat,
+                    _ => return Err(unexpected("|")),
+                }
+            }
+            '\'' | '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        // A doubled quote is an escaped quote.
+                        Some(x) if x == c && chars.peek() == Some(&c) => {
+                            chars.next();
+                            value.push(c);
+                        }
+                        Some(x) if x == c => break,
+                        Some(x) => value.push(x),
+                        None => {
+                            return Err(SelectError::Parse(
+                                "Unterminated quoted string or identifier"
+                                    .to_string(),
+                            ))
+                        }
+                    }
+                }
+                tokens.push(match c {
+                    '\'' => Token::String(value),
+                    _ => Token::QuotedIdent(value),
+                });
+                continue;
+            }
+            c if c.is_ascii_digit() => {
+                let mut value = String::new();
+                while let Some(&x) = chars.peek() {
+                    if x.is_ascii_digit() || x == '.' || x == 'e' || x == 'E' {
+                        value.push(x);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let token = match value.parse::<i64>() {
+                    Ok(x) => Token::Int(x),
+                    Err(_) => Token::Float(
+                        value.parse::<f64>().map_err(|_| unexpected(&value))?,
+                    ),
+                };
+                tokens.push(token);
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut value = String::new();
+                while let Some(&x) = chars.peek() {
+                    if x.is_alphanumeric() || x == '_' {
+                        value.push(x);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Ident(value));
+                continue;
+            }
+            c => return Err(unexpected(&c.to_string())),
+        };
+        chars.next();
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+fn unexpected(token: &str) -> SelectError {
+    SelectError::Parse(format!("Unexpected token found: {token}"))
+}
+
+/// A query over the object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub projection: Projection,
+    /// Alias of the table, it can be used as a prefix of the columns.
+    pub alias: Option<String>,
+    pub selection: Option<Expr>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Projection {
+    /// `SELECT *`, the records are returned as they are.
+    Wildcard,
+    Items(Vec<SelectItem>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectItem {
+    pub expr: Expr,
+    pub alias: Option<String>,
+}
+
+/// A part of the path to a column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Identifier {
+    pub name: String,
+    /// Quoted identifiers are case sensitive.
+    pub quoted: bool,
+}
+
+impl Identifier {
+    pub fn matches(&self, name: &str) -> bool {
+        match self.quoted {
+            true => self.name == name,
+            false => self.name.eq_ignore_ascii_case(name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOperator {
+    And,
+    Or,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    Plus,
+    Minus,
+    Multiply,
+    Divide,
+    Modulo,
+    Concat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOperator {
+    Not,
+    Minus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataType {
+    Bool,
+    Int,
+    Float,
+    String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Lower,
+    Upper,
+    CharLength,
+    Trim,
+    Coalesce,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Value),
+    /// Reference to a column of the record, prefixed or not by the alias of
+    /// the table.
+    Column(Vec<Identifier>),
+    Unary {
+        op: UnaryOperator,
+        expr: Box<Expr>,
+    },
+    Binary {
+        op: BinaryOperator,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    IsNull {
+        expr: Box<Expr>,
+        negated: bool,
+    },
+    Like {
+        expr: Box<Expr>,
+        pattern: Box<Expr>,
+        escape: Option<Box<Expr>>,
+        negated: bool,
+    },
+    Between {
+        expr: Box<Expr>,
+        low: Box<Expr>,
+        high: Box<Expr>,
+        negated: bool,
+    },
+    InList {
+        expr: Box<Expr>,
+        list: Vec<Expr>,
+        negated: bool,
+    },
+    Cast {
+        expr: Box<Expr>,
+        data_type: DataType,
+    },
+    Function {
+        function: Function,
+        args: Vec<Expr>,
+    },
+    /// An aggregate, the argument is `None` for `COUNT(*)`.
+    Aggregate {
+        function: Aggregate,
+        arg: Option<Box<Expr>>,
+    },
+}
+
+impl Expr {
+    /// The sub-expressions of the expression.
+    pub fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Literal(_) | Expr::Column(_) => vec![],
+            Expr::Aggregate { arg, .. } => arg.iter().map(|x| &**x).collect(),
+            Expr::Unary { expr, .. }
+            | Expr::IsNull { expr, .. }
+            | Expr::Cast { expr, .. } => vec![expr],
+            Expr::Binary { left, right, .. } => vec![left, right],
+            Expr::Like {
+                expr,
+                pattern,
+                escape,
+                ..
+            } => {
+                let mut children = vec![&**expr, &**pattern];
+                children.extend(escape.iter().map(|x| &**x));
+                children
+            }
+            Expr::Between {
+                expr, low, high, ..
+            } => vec![expr, low, high],
+            Expr::InList { expr, list, .. } => {
+                let mut children = vec![&**expr];
+                children.extend(list);
+                children
+            }
+            Expr::Function { args, .. } => args.iter().collect(),
+        }
+    }
+
+    pub fn has_aggregate(&self) -> bool {
+        matches!(self, Expr::Aggregate { .. })
+            || self.children().into_iter().any(Expr::has_aggregate)
+    }
+
+    /// Whether the expression references a column outside of an aggregate.
+    pub fn has_bare_column(&self) -> bool {
+        match self {
+            Expr::Column(_) => true,
+            Expr::Aggregate { .. } => false,
+            expr => expr.children().into_iter().any(Expr::has_bare_column),
+        }
+    }
+
+    /// Collect the aggregates of the expression.
+    pub fn aggregates<'a>(&'a self, aggregates: &mut Vec<&'a Expr>) {
+        match self {
+            Expr::Aggregate { .. } => aggregates.push(self),
+            expr => {
+                for child in expr.children() {
+                    child.aggregates(aggregates);
+                }
+            }
+        }
+    }
+}
+
+impl Query {
+    /// Whether the query returns a single row computed from aggregates.
+    pub fn is_aggregate(&self) -> bool {
+        match &self.projection {
+            Projection::Wildcard => false,
+            Projection::Items(items) => {
+                items.iter().any(|x| x.expr.has_aggregate())
+            }
+        }
+    }
+}
+
+/// Parse the expression of a `SelectObjectContent` request.
+pub fn parse(input: &str) -> Result<Query, SelectError> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let query = parser.parse_query()?;
+    if let Some(token) = parser.peek() {
+        return Err(unexpected(&format!("{token:?}")));
+    }
+
+    if query.is_aggregate() {
+        let Projection::Items(items) = &query.projection else {
+            unreachable!("a wildcard has no aggregate");
+        };
+        if items.iter().any(|x| x.expr.has_bare_column()) {
+            return Err(SelectError::UnsupportedSyntax(
+                "Aggregates can't be mixed with columns in the projection"
+                    .to_string(),
+            ));
+        }
+    }
+    if query.selection.as_ref().is_some_and(Expr::has_aggregate) {
+        return Err(SelectError::UnsupportedSyntax(
+            "Aggregates are not allowed in the WHERE clause".to_string(),
+        ));
+    }
+
+    Ok(query)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + n)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn is_keyword(token: Option<&Token>, keyword: &str) -> bool {
+        matches!(token, Some(Token::Ident(x)) if x.eq_ignore_ascii_case(keyword))
+    }
+
+    fn consume_keyword(&mut self, keyword: &str) -> bool {
+        if Self::is_keyword(self.peek(), keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), SelectError> {
+        match self.consume_keyword(keyword) {
+            true => Ok(()),
+            false => Err(self.expected(keyword)),
+        }
+    }
+
+    fn consume(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token) -> Result<(), SelectError> {
+        match self.consume(token) {
+            true => Ok(()),
+            false => Err(self.expected(&format!("{token:?}"))),
+        }
+    }
+
+    fn expected(&self, expected: &str) -> SelectError {
+        match self.peek() {
+            Some(token) => SelectError::Parse(format!(
+                "Expected {expected}, found: {token:?}"
+            )),
+            None => SelectError::Parse(format!(
+                "Expected {expected}, found the end of the expression"
+            )),
+        }
+    }
+
+    fn parse_query(&mut self) -> Result<Query, SelectError> {
+        self.expect_keyword("SELECT")?;
+        let projection = if self.consume(&Token::Star) {
+            Projection::Wildcard
+        } else {
+            let mut items = vec![self.parse_select_item()?];
+            while self.consume(&Token::Comma) {
+                items.push(self.parse_select_item()?);
+            }
+            Projection::Items(items)
+        };
+
+        self.expect_keyword("FROM")?;
+        match self.next() {
+            Some(Token::Ident(x)) if x.eq_ignore_ascii_case(TABLE_NAME) => {}
+            _ => {
+                return Err(SelectError::Parse(format!(
+                    "The table must be {TABLE_NAME}"
+                )))
+            }
+        }
+        let has_alias = self.consume_keyword("AS")
+            || (matches!(self.peek(), Some(Token::Ident(_)))
+                && !Self::is_keyword(self.peek(), "WHERE")
+                && !Self::is_keyword(self.peek(), "LIMIT"));
+        let alias = match has_alias {
+            true => Some(self.parse_alias()?),
+            false => None,
+        };
+
+        let selection = match self.consume_keyword("WHERE") {
+            true => Some(self.parse_expr()?),
+            false => None,
+        };
+
+        let limit = match self.consume_keyword("LIMIT") {
+            true => match self.next() {
+                Some(Token::Int(x)) if x >= 0 => Some(x as u64),
+                _ => {
+                    return Err(SelectError::Parse(
+                        "LIMIT expects a positive integer".to_string(),
+                    ))
+                }
+            },
+            false => None,
+        };
+
+        Ok(Query {
+            projection,
+            alias,
+            selection,
+            limit,
+        })
+    }
+
+    fn parse_alias(&mut self) -> Result<String, SelectError> {
+        match self.next() {
+            Some(Token::Ident(x)) | Some(Token::QuotedIdent(x)) => Ok(x),
+            _ => Err(SelectError::Parse(
+                "Expected an identifier for the alias".to_string(),
+            )),
+        }
+    }
+
+    fn parse_select_item(&mut self) -> Result<SelectItem, SelectError> {
+        let expr = self.parse_expr()?;
+        let alias = match self.consume_keyword("AS") {
+            true => Some(self.parse_alias()?),
+            false => None,
+        };
+        Ok(SelectItem { expr, alias })
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, SelectError> {
+        self.parse_or()
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, SelectError> {
+        let mut left = self.parse_and()?;
+        while self.consume_keyword("OR") {
+            let right = self.parse_and()?;
+            left = binary(BinaryOperator::Or, left, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, SelectError> {
+        let mut left = self.parse_not()?;
+        while self.consume_keyword("AND") {
+            let right = self.parse_not()?;
+            left = binary(BinaryOperator::And, left, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, SelectError> {
+        if self.consume_keyword("NOT") {
+            let expr = self.parse_not()?;
+            return Ok(Expr::Unary {
+                op: UnaryOperator::Not,
+                expr: Box::new(expr),
+            });
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, SelectError> {
+        let left = self.parse_concat()?;
+
+        let op = match self.peek() {
+            Some(Token::Eq) => Some(BinaryOperator::Eq),
+            Some(Token::NotEq) => Some(BinaryOperator::NotEq),
+            Some(Token::Lt) => Some(BinaryOperator::Lt),
+            Some(Token::LtEq) => Some(BinaryOperator::LtEq),
+            Some(Token::Gt) => Some(BinaryOperator::Gt),
+            Some(Token::GtEq) => Some(BinaryOperator::GtEq),
+            _ => None,
+        };
+        if let Some(op) = op {
+            self.pos += 1;
+            let right = self.parse_concat()?;
+            return Ok(binary(op, left, right));
+        }
+
+        if self.consume_keyword("IS") {
+            let negated = self.consume_keyword("NOT");
+            self.expect_keyword("NULL")?;
+            return Ok(Expr::IsNull {
+                expr: Box::new(left),
+                negated,
+            });
+        }
+
+        let negated = if Self::is_keyword(self.peek(), "NOT")
+            && ["LIKE", "BETWEEN", "IN"]
+                .iter()
+                .any(|x| Self::is_keyword(self.peek_nth(1), x))
+        {
+            self.pos += 1;
+            true
+        } else {
+            false
+        };
+
+        if self.consume_keyword("LIKE") {
+            let pattern = self.parse_concat()?;
+            let escape = match self.consume_keyword("ESCAPE") {
+                true => Some(Box::new(self.parse_concat()?)),
+                false => None,
+            };
+            return Ok(Expr::Like {
+                expr: Box::new(left),
+                pattern: Box::new(pattern),
+                escape,
+                negated,
+            });
+        }
+
+        if self.consume_keyword("BETWEEN") {
+            let low = self.parse_concat()?;
+            self.expect_keyword("AND")?;
+            let high = self.parse_concat()?;
+            return Ok(Expr::Between {
+                expr: Box::new(left),
+                low: Box::new(low),
+                high: Box::new(high),
+                negated,
+            });
+        }
+
+        if self.consume_keyword("IN") {
+            self.expect(&Token::LParen)?;
+            let list = self.parse_expr_list()?;
+            return Ok(Expr::InList {
+                expr: Box::new(left),
+                list,
+                negated,
+            });
+        }
+
+        Ok(left)
+    }
+
+    fn parse_concat(&mut self) -> Result<Expr, SelectError> {
+        let mut left = self.parse_additive()?;
+        while self.consume(&Token::Concat) {
+            let right = self.parse_additive()?;
+            left = binary(BinaryOperator::Concat, left, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, SelectError> {
+        let mut left = self.parse_multiplicative()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Plus) => BinaryOperator::Plus,
+                Some(Token::Minus) => BinaryOperator::Minus,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            let right = self.parse_multiplicative()?;
+            left = binary(op, left, right);
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr, SelectError> {
+        let mut left = self.parse_unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Star) => BinaryOperator::Multiply,
+                Some(Token::Slash) => BinaryOperator::Divide,
+                Some(Token::Percent) => BinaryOperator::Modulo,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            let right = self.parse_unary()?;
+            left = binary(op, left, right);
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, SelectError> {
+        if self.consume(&Token::Minus) {
+            let expr = self.parse_unary()?;
+            return Ok(match expr {
+                Expr::Literal(Value::Int(x)) => Expr::Literal(Value::Int(-x)),
+                Expr::Literal(Value::Float(x)) => {
+                    Expr::Literal(Value::Float(-x))
+                }
+                expr => Expr::Unary {
+                    op: UnaryOperator::Minus,
+                    expr: Box::new(expr),
+                },
+            });
+        }
+        if self.consume(&Token::Plus) {
+            return self.parse_unary();
+        }
+        self.parse_primary()
+    }
+
+    fn parse_expr_list(&mut self) -> Result<Vec<Expr>, SelectError> {
+        let mut list = Vec::new();
+        if self.consume(&Token::RParen) {
+            return Ok(list);
+        }
+        loop {
+            list.push(self.parse_expr()?);
+            if self.consume(&Token::RParen) {
+                return Ok(list);
+            }
+            self.expect(&Token::Comma)?;
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, SelectError> {
+        let token = match self.next() {
+            Some(token) => token,
+            None => {
+                return Err(SelectError::Parse(
+                    "Expected an expression, found the end of the expression"
+                        .to_string(),
+                ))
+            }
+        };
+
+        match token {
+            Token::Int(x) => Ok(Expr::Literal(Value::Int(x))),
+            Token::Float(x) => Ok(Expr::Literal(Value::Float(x))),
+            Token::String(x) => Ok(Expr::Literal(Value::String(x))),
+            Token::LParen => {
+                let expr = self.parse_expr()?;
+                self.expect(&Token::RParen)?;
+                Ok(expr)
+            }
+            Token::QuotedIdent(name) => {
+                self.parse_column(Identifier { name, quoted: true })
+            }
+            Token::Ident(name) => {
+                if self.peek() == Some(&Token::LParen) {
+                    self.pos += 1;
+                    return self.parse_call(&name);
+                }
+                match name.to_ascii_uppercase().as_str() {
+                    "TRUE" => Ok(Expr::Literal(Value::Bool(true))),
+                    "FALSE" => Ok(Expr::Literal(Value::Bool(false))),
+                    "NULL" => Ok(Expr::Literal(Value::Null)),
+                    "SELECT" | "FROM" | "WHERE" | "LIMIT" | "AND" | "OR"
+                    | "NOT" | "AS" | "IS" | "IN" | "LIKE" | "BETWEEN" => {
+                        Err(unexpected(&name))
+                    }
+                    _ => self.parse_column(Identifier {
+                        name,
+                        quoted: false,
+                    }),
+                }
+            }
+            token => Err(unexpected(&format!("{token:?}"))),
+        }
+    }
+
+    fn parse_column(&mut self, first: Identifier) -> Result<Expr, SelectError> {
+        let mut path = vec![first];
+        while self.consume(&Token::Dot) {
+            match self.next() {
+                Some(Token::Ident(name)) => path.push(Identifier {
+                    name,
+                    quoted: false,
+                }),
+                Some(Token::QuotedIdent(name)) => {
+                    path.push(Identifier { name, quoted: true })
+                }
+                _ => {
+                    return Err(SelectError::Parse(
+                        "Expected an identifier after '.'".to_string(),
+                    ))
+                }
+            }
+        }
+        Ok(Expr::Column(path))
+    }
+
+    fn parse_call(&mut self, name: &str) -> Result<Expr, SelectError> {
+        let aggregate = match name.to_ascii_uppercase().as_str() {
+            "COUNT" => Some(Aggregate::Count),
+            "SUM" => Some(Aggregate::Sum),
+            "AVG" => Some(Aggregate::Avg),
+            "MIN" => Some(Aggregate::Min),
+            "MAX" => Some(Aggregate::Max),
+            _ => None,
+        };
+        if let Some(function) = aggregate {
+            if function == Aggregate::Count && self.consume(&Token::Star) {
+                self.expect(&Token::RParen)?;
+                return Ok(Expr::Aggregate {
+                    function,
+                    arg: None,
+                });
+            }
+            let arg = self.parse_expr()?;
+            self.expect(&Token::RParen)?;
+            if arg.has_aggregate() {
+                return Err(SelectError::UnsupportedSyntax(
+                    "Aggregates can't be nested".to_string(),
+                ));
+            }
+            return Ok(Expr::Aggregate {
+                function,
+                arg: Some(Box::new(arg)),
+            });
+        }
+
+        if name.eq_ignore_ascii_case("CAST") {
+            let expr = self.parse_expr()?;
+            self.expect_keyword("AS")?;
+            let data_type = match self.next() {
+                Some(Token::Ident(x)) => {
+                    match x.to_ascii_uppercase().as_str() {
+                        "BOOL" | "BOOLEAN" => DataType::Bool,
+                        "INT" | "INTEGER" => DataType::Int,
+                        "FLOAT" | "DOUBLE" | "DECIMAL" | "NUMERIC" | "REAL" => {
+                            DataType::Float
+                        }
+                        "STRING" | "VARCHAR" | "CHAR" => DataType::String,
+                        _ => {
+                            return Err(SelectError::UnsupportedSyntax(
+                                format!("The type {x} is not supported"),
+                            ))
+                        }
+                    }
+                }
+                _ => return Err(self.expected("a type")),
+            };
+            self.expect(&Token::RParen)?;
+            return Ok(Expr::Cast {
+                expr: Box::new(expr),
+                data_type,
+            });
+        }
+
+        let (function, arity) = match name.to_ascii_uppercase().as_str() {
+            "LOWER" => (Function::Lower, Some(1)),
+            "UPPER" => (Function::Upper, Some(1)),
+            "CHAR_LENGTH" | "CHARACTER_LENGTH" => {
+                (Function::CharLength, Some(1))
+            }
+            "TRIM" => (Function::Trim, Some(1)),
+            "COALESCE" => (Function::Coalesce, None),
+            _ => {
+                return Err(SelectError::UnsupportedFunction(name.to_string()))
+            }
+        };
+        let args = self.parse_expr_list()?;
+        if arity.is_some_and(|x| x != args.len()) || args.is_empty() {
+            return Err(SelectError::Parse(format!(
+                "Wrong number of arguments for {name}"
+            )));
+        }
+        Ok(Expr::Function { function, args })
+    }
+}
+
+fn binary(op: BinaryOperator, left: Expr, right: Expr) -> Expr {
+    Expr::Binary {
+        op,
+        left: Box::new(left),
+        right: Box::new(right),
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), not(target_os = "wasi")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_queries() {
+        let query = parse(
+            "select s.name, CAST(s._2 AS INT) * 2 as double FROM S3Object s \
+             WHERE s.city = 'Paris' AND NOT s._3 LIKE 'a%' LIMIT 10",
+        )
+        .unwrap();
+        insta::assert_debug_snapshot!(query);
+
+        let query =
+            parse("SELECT COUNT(*), AVG(age) FROM s3object WHERE age > 10")
+                .unwrap();
+        assert!(query.is_aggregate());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(matches!(
+            parse("SELECT * FROM other"),
+            Err(SelectError::Parse(_))
+        ));
+        assert!(matches!(
+            parse("SELECT name, COUNT(*) FROM S3Object"),
+            Err(SelectError::UnsupportedSyntax(_))
+        ));
+        assert!(matches!(
+            parse("SELECT unknown(name) FROM S3Object"),
+            Err(SelectError::UnsupportedFunction(_))
+        ));
+        assert!(matches!(
+            parse("SELECT * FROM S3Object WHERE"),
+            Err(SelectError::Parse(_))
+        ));
+    }
+}
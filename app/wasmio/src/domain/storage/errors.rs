@@ -1,6 +1,8 @@
 use tracing::warn;
 
-use crate::infrastructure::storage::FSError;
+use crate::domain::select::SelectError;
+use crate::infrastructure::storage::encryption::CustomerKeyError;
+use crate::infrastructure::storage::{StorageError, StorageErrorKind};
 
 #[derive(Clone, Debug, thiserror::Error)]
 pub enum BucketStorageError {
@@ -12,15 +14,79 @@ pub enum BucketStorageError {
     NoBucket,
     #[error("No key")]
     NoKey,
+    #[error("No website configuration")]
+    NoWebsiteConfiguration,
+    #[error("No encryption configuration")]
+    NoEncryptionConfiguration,
+    #[error("The payload doesn't match the x-amz-content-sha256 header")]
+    ContentSha256Mismatch,
+    #[error("The signature of a chunk doesn't match")]
+    ChunkSignatureMismatch,
+    #[error("The body is shorter than announced")]
+    IncompleteBody,
+    #[error("A chunk of the payload is malformed")]
+    InvalidChunk,
+    #[error("The trailing headers are malformed")]
+    MalformedTrailer,
+    #[error("The checksum doesn't match the content")]
+    BadDigest,
+    #[error("The Content-MD5 is not valid")]
+    InvalidDigest,
+    #[error("The SSE-C parameters are not valid: {0}")]
+    InvalidCustomerKey(#[from] CustomerKeyError),
+    #[error("The element is encrypted, the SSE-C key is required")]
+    MissingCustomerKey,
+    #[error("The SSE-C key doesn't match the element")]
+    CustomerKeyMismatch,
+    #[error("The element is not encrypted with SSE-C")]
+    NotCustomerEncrypted,
+    #[error("The server-side encryption algorithm is not supported")]
+    UnsupportedEncryption,
+    #[error("Both SSE-C and SSE-S3 are requested")]
+    ConflictingEncryption,
+    #[error("No master key is configured for server-side encryption")]
+    EncryptionNotConfigured,
+    #[error("The uploaded content is larger than allowed")]
+    EntityTooLarge,
+    #[error("The uploaded content is smaller than allowed")]
+    EntityTooSmall,
+    #[error("The multipart/form-data body is malformed")]
+    MalformedForm,
+    #[error("The event is not supported for notifications")]
+    UnsupportedNotificationEvent,
+    #[error("The filter rules of the notification are not valid")]
+    InvalidFilterRule,
+    #[error("The requested range is outside of the element")]
+    InvalidRange,
+    #[error("The element has fewer parts than the requested one")]
+    InvalidPartNumber,
+    #[error("The part number must be between 1 and 10000")]
+    PartNumberOutOfRange,
+    #[error("Both a range and a part number are requested")]
+    RangeWithPartNumber,
+    #[error("The max-keys of a listing must not be negative")]
+    InvalidMaxKeys,
+    #[error("The continuation token of the listing is not valid")]
+    InvalidContinuationToken,
+    #[error("The target bucket for logging doesn't exist")]
+    InvalidTargetBucketForLogging,
+    #[error("The select request failed: {0}")]
+    Select(#[from] SelectError),
 }
 
-impl From<FSError> for BucketStorageError {
-    fn from(value: FSError) -> Self {
+impl<E: StorageError> From<E> for BucketStorageError {
+    fn from(value: E) -> Self {
         warn!("{value:?}");
-        match value {
-            FSError::AlreadyExist => Self::DatabaseAlreadyExist,
-            FSError::NoDatabase => Self::NoBucket,
-            _ => Self::Unknown,
+        match value.kind() {
+            StorageErrorKind::AlreadyExist => Self::DatabaseAlreadyExist,
+            StorageErrorKind::NoDatabase => Self::NoBucket,
+            StorageErrorKind::NoElement => Self::NoKey,
+            StorageErrorKind::EncryptionKey => Self::CustomerKeyMismatch,
+            StorageErrorKind::EncryptionNotConfigured => {
+                Self::EncryptionNotConfigured
+            }
+            StorageErrorKind::BadDigest => Self::BadDigest,
+            StorageErrorKind::Other => Self::Unknown,
         }
     }
 }
@@ -1,26 +1,92 @@
 use std::fmt::Debug;
+use std::ops::Range;
+use std::sync::Arc;
 
-use crate::infrastructure::storage::{BackendStorage, ElementInfo, FSStorage};
+use crate::domain::select::{Select, SelectError, SelectEvent};
+use crate::infrastructure::storage::checksum::ChecksumRequest;
+use crate::infrastructure::storage::encryption::{CustomerKey, AES256};
+use crate::infrastructure::storage::{
+    BackendStorage, DatabaseInfo, ElementContent, ElementInfo,
+    ElementProperties, FSStorage, MemoryStorage,
+};
 
 pub mod errors;
+pub mod notification;
 use axum::body::Body;
+use base64ct::{Base64, Encoding};
 use errors::BucketStorageError;
+use futures::stream::BoxStream;
 use futures::{StreamExt, TryStreamExt};
+use notification::EventName;
+use parking_lot::Mutex;
+use tokio::io::AsyncReadExt;
 use tokio_util::io::{ReaderStream, StreamReader};
 use tracing::{error, warn};
+use ulid::Ulid;
 use wasmio_aws_types::types::{
-    CreateBucketOutput, CreateBucketOutputBuilder, CreateBucketRequest,
+    BucketLoggingStatus, Checksum, CreateBucketOutput,
+    CreateBucketOutputBuilder, CreateBucketRequest,
+    DeleteBucketEncryptionRequest, DeleteBucketWebsiteRequest,
     DeleteObjectOutput, DeleteObjectOutputBuilder, DeleteObjectRequest,
-    GetObjectOutput, GetObjectRequest, ListObjectsV2Output,
-    ListObjectsV2Request, Object, PutObjectOutput, PutObjectOutputBuilder,
-    PutObjectRequest,
+    GetBucketEncryptionRequest, GetBucketLocationOutput,
+    GetBucketLocationOutputBuilder, GetBucketLocationRequest,
+    GetBucketLoggingRequest, GetBucketNotificationConfigurationRequest,
+    GetBucketWebsiteRequest, GetObjectAttributesOutput,
+    GetObjectAttributesOutputBuilder, GetObjectAttributesRequest,
+    GetObjectOutput, GetObjectRequest, HeadBucketOutput,
+    HeadBucketOutputBuilder, HeadBucketRequest, ListObjectsV2Output,
+    ListObjectsV2Request, LoggingEnabled, NotificationConfiguration, Object,
+    PutBucketEncryptionRequest, PutBucketLoggingRequest,
+    PutBucketNotificationConfigurationRequest, PutBucketWebsiteRequest,
+    PutObjectOutput, PutObjectOutputBuilder, PutObjectRequest,
+    QueueConfiguration, SelectObjectContentRequest,
+    ServerSideEncryptionConfiguration, WebsiteConfiguration,
 };
 
+/// The default and maximum number of keys of a listing.
+const MAX_KEYS: i64 = 1000;
+
+/// Value of `x-amz-checksum-mode` to get the checksum of an object.
+const CHECKSUM_ENABLED: &str = "ENABLED";
+
+/// Maximum number of parts of an object.
+const MAX_PARTS: i64 = 10_000;
+
+/// The unit of the ranges accepted by `GetObject`.
+const ACCEPT_RANGES: &str = "bytes";
+
 pub trait BackendDriver:
     BackendStorage + Debug + Send + Sync + Clone + 'static
 {
 }
 impl BackendDriver for FSStorage {}
+impl BackendDriver for MemoryStorage {}
+
+/// Statistics of a bucket, for the administrators.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct BucketStats {
+    pub objects: u64,
+    /// Bytes of the content of the objects.
+    pub bytes: u64,
+    /// Bytes of the objects stored in chunks, counted for each object.
+    pub chunk_referenced_bytes: u64,
+    /// Bytes of the chunks stored, an identical chunk is counted once.
+    pub chunk_stored_bytes: u64,
+    /// How many times the stored chunks are referenced.
+    pub dedup_ratio: f64,
+}
+
+impl From<&DatabaseInfo> for BucketStats {
+    fn from(db_info: &DatabaseInfo) -> Self {
+        Self {
+            objects: db_info.number_element,
+            bytes: db_info.total_size,
+            chunk_referenced_bytes: db_info.chunks.referenced_bytes,
+            chunk_stored_bytes: db_info.chunks.stored_bytes,
+            dedup_ratio: db_info.chunks.dedup_ratio(),
+        }
+    }
+}
 
 /// The [BucketStorage] is the struct shared in the application which allow you
 /// to access to some [Bucket] and interact with those.
@@ -45,16 +111,29 @@ where
 
     pub async fn create_new_bucket(
         &self,
-        CreateBucketRequest { bucket, .. }: CreateBucketRequest,
+        CreateBucketRequest {
+            bucket,
+            create_bucket_configuration,
+            ..
+        }: CreateBucketRequest,
     ) -> Result<CreateBucketOutput, BucketStorageError> {
-        let db_info = self.backend_storage.new_database(&bucket).await?;
+        let region =
+            create_bucket_configuration.and_then(|x| x.location_constraint);
+        let db_info =
+            self.backend_storage.new_database(&bucket, region).await?;
+        let location = format!("/{name}", name = db_info.name());
 
         CreateBucketOutputBuilder::default()
-            .location(format!("/{name}", name = db_info.name()))
+            .location(location)
             .build()
             .map_err(|_err| BucketStorageError::Unknown)
     }
 
+    /// Insert an object, the `checksum` is computed while the body is
+    /// streamed and checked against the expected one.
+    ///
+    /// The object is encrypted with SSE-S3 when it's asked or when it's the
+    /// default encryption of the bucket, unless a SSE-C key is given.
     pub async fn put_object(
         &self,
         PutObjectRequest {
@@ -62,25 +141,108 @@ where
             key,
             body,
             metadata,
+            content_type,
+            website_redirect_location,
+            content_md5,
+            server_side_encryption,
+            sse_customer_algorithm,
+            sse_customer_key,
+            sse_customer_key_md5,
             ..
         }: PutObjectRequest,
+        checksum: Option<ChecksumRequest>,
     ) -> Result<PutObjectOutput, BucketStorageError> {
         let body = body.ok_or(BucketStorageError::Unknown)?;
-        let body_err = body
-            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err));
+        let content_md5 = content_md5
+            .map(|x| match Base64::decode_vec(&x) {
+                Ok(x) if x.len() == 16 => Ok(x),
+                _ => Err(BucketStorageError::InvalidDigest),
+            })
+            .transpose()?;
+        let customer_key = customer_key(
+            sse_customer_algorithm,
+            sse_customer_key,
+            sse_customer_key_md5,
+        )?;
+        let server_side_encryption = match server_side_encryption {
+            Some(algorithm) if algorithm == AES256 => true,
+            Some(_) => return Err(BucketStorageError::UnsupportedEncryption),
+            None if customer_key.is_some() => false,
+            None => self
+                .bucket_info(&bucket)
+                .await?
+                .encryption
+                .as_ref()
+                .is_some_and(is_default_encrypted),
+        };
+        if server_side_encryption && customer_key.is_some() {
+            return Err(BucketStorageError::ConflictingEncryption);
+        }
+        // The body can fail because of the request validation, in this case
+        // the error is kept to be returned instead of the storage one.
+        let body_error = Arc::new(Mutex::new(None));
+        let body_err = body.map_err({
+            let body_error = body_error.clone();
+            move |err| {
+                if let Some(err) = find_storage_error(&err) {
+                    *body_error.lock() = Some(err);
+                }
+                std::io::Error::new(std::io::ErrorKind::Other, err)
+            }
+        });
         let mut body_reader = StreamReader::new(body_err);
 
-        self.backend_storage
+        let result = self
+            .backend_storage
             .insert_element_in_database(
                 &bucket,
                 &key,
-                metadata.unwrap_or_default(),
+                ElementProperties {
+                    metadatas: metadata.unwrap_or_default(),
+                    content_type,
+                    website_redirect_location,
+                    checksum,
+                    content_md5,
+                    customer_key,
+                    server_side_encryption,
+                },
                 &mut body_reader,
             )
-            .await?;
+            .await;
+
+        if let Some(err) = body_error.lock().take() {
+            return Err(err);
+        }
+        let elt = result?;
+        let e_tag = elt.e_tag();
+        let sse_customer_key_md5 = elt
+            .encryption
+            .as_ref()
+            .and_then(|x| x.customer_key_md5())
+            .map(|x| x.to_string());
+        let server_side_encryption = managed_encryption_algorithm(&elt);
+        let Checksum {
+            checksum_crc32,
+            checksum_crc32c,
+            checksum_sha1,
+            checksum_sha256,
+        } = elt
+            .additional_checksum
+            .map(Checksum::from)
+            .unwrap_or_default();
 
         PutObjectOutputBuilder::default()
-            .e_tag(Some("unimplemented".to_string()))
+            .e_tag(Some(e_tag))
+            .checksum_crc32(checksum_crc32)
+            .checksum_crc32c(checksum_crc32c)
+            .checksum_sha1(checksum_sha1)
+            .checksum_sha256(checksum_sha256)
+            .server_side_encryption(server_side_encryption)
+            .sse_customer_algorithm(
+                sse_customer_key_md5.as_ref().map(|_| AES256.to_string()),
+            )
+            .sse_customer_key_md5(sse_customer_key_md5)
+            .size(Some(elt.size as i64))
             .build()
             .map_err(|_err| BucketStorageError::Unknown)
     }
@@ -104,29 +266,49 @@ where
 
     pub async fn list_object_v2(
         &self,
-        ListObjectsV2Request { bucket, .. }: ListObjectsV2Request,
+        ListObjectsV2Request {
+            bucket,
+            prefix,
+            start_after,
+            max_keys,
+            continuation_token,
+            ..
+        }: ListObjectsV2Request,
     ) -> Result<ListObjectsV2Output, BucketStorageError> {
+        let max_keys = match max_keys {
+            Some(max_keys) if max_keys < 0 => {
+                return Err(BucketStorageError::InvalidMaxKeys)
+            }
+            max_keys => max_keys.unwrap_or(MAX_KEYS).min(MAX_KEYS),
+        };
+        // The listing goes on after the last key of the previous page.
+        let start = match &continuation_token {
+            Some(token) => Some(decode_continuation_token(token)?),
+            None => start_after.clone(),
+        };
+
+        // One more key tells whether the listing is truncated.
         let mut s = self
             .backend_storage
-            .list_element_in_database(&bucket, None)
+            .list_element_in_database(
+                &bucket,
+                prefix.as_deref(),
+                start.as_deref(),
+                Some(max_keys as usize + 1),
+            )
             .await
             .map_err(|_| BucketStorageError::Unknown)?;
 
         let mut contents: Vec<Object> = Vec::new();
         while let Some(elt) = s.next().await {
             match elt {
-                Ok(ElementInfo {
-                    name,
-                    last_modified,
-                    size,
-                    checksum,
-                    ..
-                }) => {
+                Ok(elt) => {
                     contents.push(Object {
-                        key: Some(name),
-                        size: Some(size as i64),
-                        last_modified: Some(last_modified.to_rfc3339()),
-                        e_tag: Some(checksum),
+                        e_tag: Some(elt.e_tag()),
+                        key: Some(elt.name),
+                        size: Some(elt.size as i64),
+                        last_modified: Some(elt.last_modified.to_rfc3339()),
+                        storage_class: Some("STANDARD".to_string()),
                         ..Default::default()
                     });
                 }
@@ -136,8 +318,27 @@ where
             }
         }
 
+        let is_truncated = contents.len() as i64 > max_keys;
+        contents.truncate(max_keys as usize);
+        let next_continuation_token = match is_truncated {
+            true => contents
+                .last()
+                .and_then(|x| x.key.as_deref())
+                .or(start.as_deref())
+                .map(|x| Base64::encode_string(x.as_bytes())),
+            false => None,
+        };
+
         let result = ListObjectsV2Output {
+            name: Some(bucket),
+            key_count: Some(contents.len() as i64),
+            max_keys: Some(max_keys),
+            is_truncated: Some(is_truncated),
             contents: Some(contents),
+            prefix,
+            start_after,
+            continuation_token,
+            next_continuation_token,
             ..Default::default()
         };
         Ok(result)
@@ -145,33 +346,33 @@ where
 
     pub async fn get_object(
         &self,
-        GetObjectRequest { bucket, key, .. }: GetObjectRequest,
+        GetObjectRequest {
+            bucket,
+            key,
+            checksum_mode,
+            part_number,
+            range,
+            sse_customer_algorithm,
+            sse_customer_key,
+            sse_customer_key_md5,
+            ..
+        }: GetObjectRequest,
     ) -> Result<GetObjectOutput, BucketStorageError> {
-        // TODO: Ensure the file exist before reading it, right now, the error
-        // is not bubbled up as we do an async read.
-        let (mut asyncwriter, asyncreader) = tokio::io::duplex(8192);
-
-        // Ugly shit
-        let s = self.clone();
-        let b = bucket.clone();
-        let k = key.clone();
-        tokio::spawn(async move {
-            if let Err(err) = s
-                .backend_storage
-                .get_element_in_database(&b, &k, &mut asyncwriter)
-                .await
-            {
-                warn!("{err:?}");
+        if let Some(part_number) = part_number {
+            if !(1..=MAX_PARTS).contains(&part_number) {
+                return Err(BucketStorageError::PartNumberOutOfRange);
             }
-        });
+            if range.is_some() {
+                return Err(BucketStorageError::RangeWithPartNumber);
+            }
+        }
+        let customer_key = customer_key(
+            sse_customer_algorithm,
+            sse_customer_key,
+            sse_customer_key_md5,
+        )?;
 
-        let ElementInfo {
-            size,
-            last_modified,
-            checksum,
-            metadatas,
-            ..
-        } = match self
+        let elt = match self
             .backend_storage
             .get_element_metadata_in_database(&bucket, &key)
             .await?
@@ -181,22 +382,82 @@ where
                 return Err(BucketStorageError::NoKey);
             }
         };
+        let sse_customer_key_md5 =
+            check_customer_key(&elt, customer_key.as_ref())?;
+        let range = match (part_number, range) {
+            (Some(part_number), _) => part_range(part_number, elt.size)?,
+            (None, Some(range)) => parse_range(&range, elt.size)?,
+            (None, None) => None,
+        };
 
-        let body = Body::from_stream(ReaderStream::new(asyncreader));
+        // The element is opened before answering, so its errors are returned
+        // instead of an interrupted body.
+        let ElementContent {
+            info: elt,
+            reader,
+            len,
+        } = self
+            .backend_storage
+            .get_element_in_database(
+                &bucket,
+                &key,
+                customer_key.as_ref(),
+                range.clone(),
+            )
+            .await?;
+        let content_range = range.map(|range| {
+            format!(
+                "bytes {start}-{end}/{size}",
+                start = range.start,
+                end = (range.start + len).saturating_sub(1),
+                size = elt.size,
+            )
+        });
+
+        let whole_element = len == elt.size;
+        let e_tag = elt.e_tag();
+        let server_side_encryption = managed_encryption_algorithm(&elt);
+        let ElementInfo {
+            last_modified,
+            metadatas,
+            content_type,
+            website_redirect_location,
+            additional_checksum,
+            ..
+        } = elt;
+
+        // The checksum is only returned when it's asked, and it doesn't apply
+        // to a partial content.
+        let Checksum {
+            checksum_crc32,
+            checksum_crc32c,
+            checksum_sha1,
+            checksum_sha256,
+        } = additional_checksum
+            .filter(|_| {
+                whole_element
+                    && checksum_mode.is_some_and(|x| {
+                        x.eq_ignore_ascii_case(CHECKSUM_ENABLED)
+                    })
+            })
+            .map(Checksum::from)
+            .unwrap_or_default();
+
+        let body = Body::from_stream(ReaderStream::new(reader.take(len)));
 
         Ok(GetObjectOutput {
-            accept_ranges: None,
+            accept_ranges: Some(ACCEPT_RANGES.to_string()),
             body: Some(body),
             bucket_key_enabled: None,
             cache_control: None,
             content_disposition: None,
             content_encoding: None,
             content_language: None,
-            content_length: Some(size as i64),
-            content_range: None,
-            content_type: None,
+            content_length: Some(len as i64),
+            content_range,
+            content_type,
             delete_marker: None,
-            e_tag: Some(checksum),
+            e_tag: Some(e_tag),
             expiration: None,
             expires: None,
             last_modified: Some(last_modified.to_rfc3339()),
@@ -205,18 +466,623 @@ where
             object_lock_legal_hold_status: None,
             object_lock_mode: None,
             object_lock_retain_until_date: None,
-            parts_count: None,
+            // The elements are stored in a single part.
+            parts_count: part_number.map(|_| 1),
             replication_status: None,
             request_charged: None,
             restore: None,
-            sse_customer_algorithm: None,
-            sse_customer_key_md5: None,
+            sse_customer_algorithm: sse_customer_key_md5
+                .as_ref()
+                .map(|_| AES256.to_string()),
+            sse_customer_key_md5,
             ssekms_key_id: None,
-            server_side_encryption: None,
+            server_side_encryption,
             storage_class: None,
             tag_count: None,
             version_id: None,
-            website_redirect_location: None,
+            checksum_crc32,
+            checksum_crc32c,
+            checksum_sha1,
+            checksum_sha256,
+            website_redirect_location,
+        })
+    }
+
+    pub async fn get_object_attributes(
+        &self,
+        GetObjectAttributesRequest {
+            bucket,
+            key,
+            expected_attributes,
+            sse_customer_algorithm,
+            sse_customer_key,
+            sse_customer_key_md5,
+            ..
+        }: GetObjectAttributesRequest,
+    ) -> Result<GetObjectAttributesOutput, BucketStorageError> {
+        let customer_key = customer_key(
+            sse_customer_algorithm,
+            sse_customer_key,
+            sse_customer_key_md5,
+        )?;
+        let elt = self
+            .backend_storage
+            .get_element_metadata_in_database(&bucket, &key)
+            .await?
+            .ok_or(BucketStorageError::NoKey)?;
+        check_customer_key(&elt, customer_key.as_ref())?;
+        // The entity tag isn't quoted in the attributes.
+        let e_tag = elt.e_tag().trim_matches('"').to_string();
+        let ElementInfo {
+            size,
+            last_modified,
+            additional_checksum,
+            ..
+        } = elt;
+
+        let expected = |name: &str| {
+            expected_attributes
+                .iter()
+                .any(|x| x.eq_ignore_ascii_case(name))
+        };
+
+        GetObjectAttributesOutputBuilder::default()
+            .e_tag(expected("ETag").then_some(e_tag))
+            .checksum(
+                additional_checksum
+                    .filter(|_| expected("Checksum"))
+                    .map(Checksum::from),
+            )
+            .storage_class(
+                expected("StorageClass").then(|| "STANDARD".to_string()),
+            )
+            .object_size(expected("ObjectSize").then_some(size as i64))
+            .last_modified(Some(last_modified.to_rfc3339()))
+            .build()
+            .map_err(|_err| BucketStorageError::Unknown)
+    }
+
+    pub async fn object_exists(
+        &self,
+        bucket: &str,
+        key: &str,
+    ) -> Result<bool, BucketStorageError> {
+        Ok(self
+            .backend_storage
+            .get_element_metadata_in_database(bucket, key)
+            .await?
+            .is_some())
+    }
+
+    pub async fn select_object_content(
+        &self,
+        request: SelectObjectContentRequest,
+    ) -> Result<
+        BoxStream<'static, Result<SelectEvent, SelectError>>,
+        BucketStorageError,
+    > {
+        let select = Select::new(&request)?;
+        let SelectObjectContentRequest {
+            bucket,
+            key,
+            sse_customer_algorithm,
+            sse_customer_key,
+            sse_customer_key_md5,
+            ..
+        } = request;
+        let customer_key = customer_key(
+            sse_customer_algorithm,
+            sse_customer_key,
+            sse_customer_key_md5,
+        )?;
+
+        let elt = self
+            .backend_storage
+            .get_element_metadata_in_database(&bucket, &key)
+            .await?
+            .ok_or(BucketStorageError::NoKey)?;
+        check_customer_key(&elt, customer_key.as_ref())?;
+
+        let ElementContent { reader, .. } = self
+            .backend_storage
+            .get_element_in_database(&bucket, &key, customer_key.as_ref(), None)
+            .await?;
+        Ok(select.run(reader))
+    }
+
+    /// Load the [DatabaseInfo] of a bucket, failing when it doesn't exist.
+    async fn bucket_info(
+        &self,
+        bucket: &str,
+    ) -> Result<DatabaseInfo, BucketStorageError> {
+        self.backend_storage
+            .database_metadata(bucket)
+            .await?
+            .ok_or(BucketStorageError::NoBucket)
+    }
+
+    pub async fn put_bucket_website(
+        &self,
+        PutBucketWebsiteRequest {
+            bucket,
+            website_configuration,
+            ..
+        }: PutBucketWebsiteRequest,
+    ) -> Result<(), BucketStorageError> {
+        let db_info = self.bucket_info(&bucket).await?;
+        self.backend_storage
+            .update_database(DatabaseInfo {
+                website: Some(website_configuration),
+                ..db_info
+            })
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_bucket_website(
+        &self,
+        GetBucketWebsiteRequest { bucket, .. }: GetBucketWebsiteRequest,
+    ) -> Result<WebsiteConfiguration, BucketStorageError> {
+        self.bucket_info(&bucket)
+            .await?
+            .website
+            .ok_or(BucketStorageError::NoWebsiteConfiguration)
+    }
+
+    pub async fn delete_bucket_website(
+        &self,
+        DeleteBucketWebsiteRequest { bucket, .. }: DeleteBucketWebsiteRequest,
+    ) -> Result<(), BucketStorageError> {
+        let db_info = self.bucket_info(&bucket).await?;
+        self.backend_storage
+            .update_database(DatabaseInfo {
+                website: None,
+                ..db_info
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// The location constraint is the region of the bucket, it's not set
+    /// for the buckets of `us-east-1`.
+    pub async fn get_bucket_location(
+        &self,
+        GetBucketLocationRequest { bucket, .. }: GetBucketLocationRequest,
+    ) -> Result<GetBucketLocationOutput, BucketStorageError> {
+        let db_info = self.bucket_info(&bucket).await?;
+
+        GetBucketLocationOutputBuilder::default()
+            .location_constraint(db_info.region)
+            .build()
+            .map_err(|_err| BucketStorageError::Unknown)
+    }
+
+    pub async fn head_bucket(
+        &self,
+        HeadBucketRequest { bucket, .. }: HeadBucketRequest,
+    ) -> Result<HeadBucketOutput, BucketStorageError> {
+        let db_info = self.bucket_info(&bucket).await?;
+
+        HeadBucketOutputBuilder::default()
+            .bucket_region(db_info.region)
+            .build()
+            .map_err(|_err| BucketStorageError::Unknown)
+    }
+
+    pub async fn bucket_stats(
+        &self,
+        bucket: &str,
+    ) -> Result<BucketStats, BucketStorageError> {
+        let db_info = self.bucket_info(bucket).await?;
+        Ok(BucketStats::from(&db_info))
+    }
+
+    /// Compute the statistics of a bucket again from its objects, in case
+    /// they drifted.
+    pub async fn recompute_bucket_stats(
+        &self,
+        bucket: &str,
+    ) -> Result<BucketStats, BucketStorageError> {
+        let db_info = self
+            .backend_storage
+            .recompute_database_stats(bucket)
+            .await?;
+        Ok(BucketStats::from(&db_info))
+    }
+
+    pub async fn put_bucket_notification_configuration(
+        &self,
+        PutBucketNotificationConfigurationRequest {
+            bucket,
+            mut notification_configuration,
+            ..
+        }: PutBucketNotificationConfigurationRequest,
+    ) -> Result<(), BucketStorageError> {
+        notification::validate_configuration(&notification_configuration)?;
+        for queue in &mut notification_configuration.queue_configurations {
+            queue.id.get_or_insert_with(|| Ulid::new().to_string());
+        }
+
+        let db_info = self.bucket_info(&bucket).await?;
+        // An empty configuration turns off the notifications.
+        let notification = Some(notification_configuration)
+            .filter(|x| !x.queue_configurations.is_empty());
+        self.backend_storage
+            .update_database(DatabaseInfo {
+                notification,
+                ..db_info
+            })
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_bucket_notification_configuration(
+        &self,
+        GetBucketNotificationConfigurationRequest { bucket, .. }: GetBucketNotificationConfigurationRequest,
+    ) -> Result<NotificationConfiguration, BucketStorageError> {
+        Ok(self
+            .bucket_info(&bucket)
+            .await?
+            .notification
+            .unwrap_or_default())
+    }
+
+    /// The configurations of the destinations which must receive the event
+    /// of the element `key`.
+    pub async fn notification_destinations(
+        &self,
+        bucket: &str,
+        event: EventName,
+        key: &str,
+    ) -> Result<Vec<QueueConfiguration>, BucketStorageError> {
+        let Some(configuration) = self.bucket_info(bucket).await?.notification
+        else {
+            return Ok(Vec::new());
+        };
+
+        Ok(
+            notification::matching_configurations(&configuration, event, key)
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Enable the access logs of a bucket, they are written in the target
+    /// bucket which must exist. An empty status disables them.
+    pub async fn put_bucket_logging(
+        &self,
+        PutBucketLoggingRequest {
+            bucket,
+            bucket_logging_status,
+            ..
+        }: PutBucketLoggingRequest,
+    ) -> Result<(), BucketStorageError> {
+        let db_info = self.bucket_info(&bucket).await?;
+        let logging = bucket_logging_status.logging_enabled;
+        if let Some(logging) = &logging {
+            let target = self
+                .backend_storage
+                .database_metadata(&logging.target_bucket)
+                .await?;
+            if target.is_none() {
+                return Err(BucketStorageError::InvalidTargetBucketForLogging);
+            }
+        }
+
+        self.backend_storage
+            .update_database(DatabaseInfo { logging, ..db_info })
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_bucket_logging(
+        &self,
+        GetBucketLoggingRequest { bucket, .. }: GetBucketLoggingRequest,
+    ) -> Result<BucketLoggingStatus, BucketStorageError> {
+        Ok(BucketLoggingStatus {
+            logging_enabled: self.bucket_info(&bucket).await?.logging,
         })
     }
+
+    /// Where the access logs of a bucket are written, if they are enabled.
+    pub async fn logging_target(
+        &self,
+        bucket: &str,
+    ) -> Result<Option<LoggingEnabled>, BucketStorageError> {
+        Ok(self.bucket_info(bucket).await?.logging)
+    }
+
+    /// Set the default encryption of a bucket, only SSE-S3 is supported.
+    pub async fn put_bucket_encryption(
+        &self,
+        PutBucketEncryptionRequest {
+            bucket,
+            server_side_encryption_configuration,
+            ..
+        }: PutBucketEncryptionRequest,
+    ) -> Result<(), BucketStorageError> {
+        let supported = server_side_encryption_configuration
+            .rules
+            .iter()
+            .filter_map(|x| x.apply_server_side_encryption_by_default.as_ref())
+            .all(|x| {
+                x.sse_algorithm == AES256 && x.kms_master_key_id.is_none()
+            });
+        if !supported {
+            return Err(BucketStorageError::UnsupportedEncryption);
+        }
+
+        let db_info = self.bucket_info(&bucket).await?;
+        self.backend_storage
+            .update_database(DatabaseInfo {
+                encryption: Some(server_side_encryption_configuration),
+                ..db_info
+            })
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_bucket_encryption(
+        &self,
+        GetBucketEncryptionRequest { bucket, .. }: GetBucketEncryptionRequest,
+    ) -> Result<ServerSideEncryptionConfiguration, BucketStorageError> {
+        self.bucket_info(&bucket)
+            .await?
+            .encryption
+            .ok_or(BucketStorageError::NoEncryptionConfiguration)
+    }
+
+    pub async fn delete_bucket_encryption(
+        &self,
+        DeleteBucketEncryptionRequest { bucket, .. }: DeleteBucketEncryptionRequest,
+    ) -> Result<(), BucketStorageError> {
+        let db_info = self.bucket_info(&bucket).await?;
+        self.backend_storage
+            .update_database(DatabaseInfo {
+                encryption: None,
+                ..db_info
+            })
+            .await?;
+        Ok(())
+    }
+}
+
+/// Whether the default encryption of a bucket asks for SSE-S3.
+fn is_default_encrypted(
+    configuration: &ServerSideEncryptionConfiguration,
+) -> bool {
+    configuration
+        .rules
+        .iter()
+        .filter_map(|x| x.apply_server_side_encryption_by_default.as_ref())
+        .any(|x| x.sse_algorithm == AES256)
+}
+
+/// The algorithm of an element encrypted with SSE-S3, to echo it.
+fn managed_encryption_algorithm(elt: &ElementInfo) -> Option<String> {
+    elt.encryption
+        .as_ref()
+        .filter(|x| x.is_managed())
+        .map(|_| AES256.to_string())
+}
+
+/// The range of a part of an element. The elements are stored in a single
+/// part, so the first part is the whole element and the others don't exist.
+fn part_range(
+    part_number: i64,
+    size: u64,
+) -> Result<Option<Range<u64>>, BucketStorageError> {
+    if part_number > 1 {
+        return Err(BucketStorageError::InvalidPartNumber);
+    }
+    Ok((size > 0).then_some(0..size))
+}
+
+/// Parse the `Range` header of a request, only a single range of bytes is
+/// supported. Like S3, the header is ignored when it's not valid.
+fn parse_range(
+    range: &str,
+    size: u64,
+) -> Result<Option<Range<u64>>, BucketStorageError> {
+    let Some((start, end)) = range
+        .trim()
+        .strip_prefix("bytes=")
+        .and_then(|x| x.split_once('-'))
+    else {
+        return Ok(None);
+    };
+
+    let (start, end) = match (start.trim(), end.trim()) {
+        // The last bytes of the element.
+        ("", suffix) => match suffix.parse::<u64>() {
+            Ok(suffix) => (size.saturating_sub(suffix), size),
+            Err(_) => return Ok(None),
+        },
+        (start, "") => match start.parse::<u64>() {
+            Ok(start) => (start, size),
+            Err(_) => return Ok(None),
+        },
+        (start, end) => match (start.parse::<u64>(), end.parse::<u64>()) {
+            (Ok(start), Ok(end)) if start <= end => {
+                (start, end.saturating_add(1).min(size))
+            }
+            _ => return Ok(None),
+        },
+    };
+
+    if start >= size {
+        return Err(BucketStorageError::InvalidRange);
+    }
+    Ok(Some(start..end))
+}
+
+/// Parse the SSE-C key of a request, when one of the parameters is given.
+fn customer_key(
+    algorithm: Option<String>,
+    key: Option<String>,
+    key_md5: Option<String>,
+) -> Result<Option<CustomerKey>, BucketStorageError> {
+    if algorithm.is_none() && key.is_none() && key_md5.is_none() {
+        return Ok(None);
+    }
+    Ok(Some(CustomerKey::parse(
+        &algorithm.unwrap_or_default(),
+        &key.unwrap_or_default(),
+        &key_md5.unwrap_or_default(),
+    )?))
+}
+
+/// Check an element encrypted with SSE-C is accessed with the same key,
+/// returns the MD5 of the key to echo it.
+fn check_customer_key(
+    elt: &ElementInfo,
+    key: Option<&CustomerKey>,
+) -> Result<Option<String>, BucketStorageError> {
+    let key_md5 = elt.encryption.as_ref().and_then(|x| x.customer_key_md5());
+    match (key_md5, key) {
+        (Some(key_md5), Some(key)) if key_md5 == key.key_md5() => {
+            Ok(Some(key_md5.to_string()))
+        }
+        (Some(_), Some(_)) => Err(BucketStorageError::CustomerKeyMismatch),
+        (Some(_), None) => Err(BucketStorageError::MissingCustomerKey),
+        (None, Some(_)) => Err(BucketStorageError::NotCustomerEncrypted),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Find a [BucketStorageError] raised while reading the body of a request,
+/// the body wraps the errors of its stream.
+fn find_storage_error(err: &axum::Error) -> Option<BucketStorageError> {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(err) = source {
+        if let Some(err) = err.downcast_ref::<BucketStorageError>() {
+            return Some(err.clone());
+        }
+        source = err.source();
+    }
+    None
+}
+
+/// The key a listing goes on after, from the `NextContinuationToken` of
+/// the previous page.
+fn decode_continuation_token(
+    token: &str,
+) -> Result<String, BucketStorageError> {
+    Base64::decode_vec(token)
+        .ok()
+        .and_then(|x| String::from_utf8(x).ok())
+        .ok_or(BucketStorageError::InvalidContinuationToken)
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), not(target_os = "wasi")))]
+mod tests {
+    use wasmio_aws_types::types::ListObjectsV2RequestBuilder;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_list_object_v2_pages() {
+        let backend = MemoryStorage::new();
+        backend.new_database("bucket", None).await.unwrap();
+        for key in ["a", "b/1", "b/2", "b/3", "c"] {
+            backend
+                .insert_element_in_database(
+                    "bucket",
+                    key,
+                    Default::default(),
+                    &mut std::io::Cursor::new(b""),
+                )
+                .await
+                .unwrap();
+        }
+        let storage = BucketStorage::new(backend);
+
+        let list = |max_keys: Option<i64>, token: Option<String>| {
+            let request = ListObjectsV2RequestBuilder::default()
+                .bucket("bucket")
+                .prefix(Some("b/".to_string()))
+                .max_keys(max_keys)
+                .continuation_token(token)
+                .build()
+                .unwrap();
+            storage.list_object_v2(request)
+        };
+        let keys = |output: &ListObjectsV2Output| {
+            output
+                .contents
+                .iter()
+                .flatten()
+                .flat_map(|x| x.key.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let first = list(Some(2), None).await.unwrap();
+        assert_eq!(keys(&first), ["b/1", "b/2"]);
+        assert_eq!(first.is_truncated, Some(true));
+        assert_eq!(first.key_count, Some(2));
+        assert_eq!(first.max_keys, Some(2));
+        let token = first.next_continuation_token.clone();
+        assert!(token.is_some());
+
+        let second = list(Some(2), token.clone()).await.unwrap();
+        assert_eq!(keys(&second), ["b/3"]);
+        assert_eq!(second.is_truncated, Some(false));
+        assert_eq!(second.continuation_token, token);
+        assert!(second.next_continuation_token.is_none());
+
+        let all = list(None, None).await.unwrap();
+        assert_eq!(keys(&all), ["b/1", "b/2", "b/3"]);
+        assert_eq!(all.max_keys, Some(MAX_KEYS));
+        assert_eq!(all.is_truncated, Some(false));
+
+        let none = list(Some(0), None).await.unwrap();
+        assert!(keys(&none).is_empty());
+        assert_eq!(none.is_truncated, Some(true));
+        let next = list(Some(3), none.next_continuation_token).await.unwrap();
+        assert_eq!(keys(&next), ["b/1", "b/2", "b/3"]);
+
+        assert!(matches!(
+            list(Some(-1), None).await,
+            Err(BucketStorageError::InvalidMaxKeys)
+        ));
+        assert!(matches!(
+            list(None, Some("not base64!".to_string())).await,
+            Err(BucketStorageError::InvalidContinuationToken)
+        ));
+    }
+
+    #[test]
+    fn test_part_range() {
+        assert_eq!(part_range(1, 10).unwrap(), Some(0..10));
+        assert_eq!(part_range(1, 0).unwrap(), None);
+        assert!(matches!(
+            part_range(2, 10),
+            Err(BucketStorageError::InvalidPartNumber)
+        ));
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-4", 10).unwrap(), Some(0..5));
+        assert_eq!(parse_range("bytes=5-100", 10).unwrap(), Some(5..10));
+        assert_eq!(parse_range("bytes=7-", 10).unwrap(), Some(7..10));
+        assert_eq!(parse_range("bytes=-3", 10).unwrap(), Some(7..10));
+        assert_eq!(parse_range("bytes=-30", 10).unwrap(), Some(0..10));
+
+        // Ignored as they are not valid.
+        assert_eq!(parse_range("bytes=4-2", 10).unwrap(), None);
+        assert_eq!(parse_range("bytes=0-1,3-4", 10).unwrap(), None);
+        assert_eq!(parse_range("items=0-1", 10).unwrap(), None);
+
+        for range in ["bytes=10-", "bytes=12-20", "bytes=-0"] {
+            assert!(matches!(
+                parse_range(range, 10),
+                Err(BucketStorageError::InvalidRange)
+            ));
+        }
+        assert!(matches!(
+            parse_range("bytes=0-", 0),
+            Err(BucketStorageError::InvalidRange)
+        ));
+    }
 }
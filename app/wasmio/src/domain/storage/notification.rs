@@ -0,0 +1,184 @@
+//! Matching of the bucket events against the notification configuration of
+//! the bucket.
+//!
+//! See [`Event notification types`](https://docs.aws.amazon.com/AmazonS3/latest/userguide/notification-how-to-event-types-and-destinations.html)
+use wasmio_aws_types::types::{NotificationConfiguration, QueueConfiguration};
+
+use super::errors::BucketStorageError;
+
+/// The events which can be configured, the others are never emitted.
+const SUPPORTED_EVENTS: [&str; 7] = [
+    "s3:ObjectCreated:*",
+    "s3:ObjectCreated:Put",
+    "s3:ObjectCreated:Post",
+    "s3:ObjectCreated:Copy",
+    "s3:ObjectCreated:CompleteMultipartUpload",
+    "s3:ObjectRemoved:*",
+    "s3:ObjectRemoved:Delete",
+];
+
+const PREFIX_RULE: &str = "prefix";
+const SUFFIX_RULE: &str = "suffix";
+
+/// An event emitted on a bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+#[allow(clippy::enum_variant_names)]
+pub enum EventName {
+    #[strum(serialize = "ObjectCreated:Put")]
+    ObjectCreatedPut,
+    #[strum(serialize = "ObjectCreated:Post")]
+    ObjectCreatedPost,
+    #[strum(serialize = "ObjectRemoved:Delete")]
+    ObjectRemovedDelete,
+}
+
+impl EventName {
+    /// Whether the event is selected by a configured event, which can end
+    /// with a wildcard.
+    fn is_selected_by(&self, configured: &str) -> bool {
+        let Some(configured) = configured.strip_prefix("s3:") else {
+            return false;
+        };
+        let name = self.to_string();
+        match configured.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == configured,
+        }
+    }
+}
+
+/// Check the events and the filters of the configuration, the destinations
+/// are checked by the application.
+pub fn validate_configuration(
+    configuration: &NotificationConfiguration,
+) -> Result<(), BucketStorageError> {
+    for queue in &configuration.queue_configurations {
+        if queue.events.is_empty()
+            || queue
+                .events
+                .iter()
+                .any(|x| !SUPPORTED_EVENTS.contains(&x.as_str()))
+        {
+            return Err(BucketStorageError::UnsupportedNotificationEvent);
+        }
+
+        let rules = filter_rules(queue);
+        let count = |name| {
+            rules
+                .iter()
+                .filter(|(x, _)| x.eq_ignore_ascii_case(name))
+                .count()
+        };
+        if count(PREFIX_RULE) > 1
+            || count(SUFFIX_RULE) > 1
+            || count(PREFIX_RULE) + count(SUFFIX_RULE) != rules.len()
+        {
+            return Err(BucketStorageError::InvalidFilterRule);
+        }
+    }
+    Ok(())
+}
+
+/// The configurations of the queues which must receive the event.
+pub fn matching_configurations<'a>(
+    configuration: &'a NotificationConfiguration,
+    event: EventName,
+    key: &'a str,
+) -> impl Iterator<Item = &'a QueueConfiguration> {
+    configuration
+        .queue_configurations
+        .iter()
+        .filter(move |queue| {
+            queue.events.iter().any(|x| event.is_selected_by(x))
+                && filter_rules(queue).iter().all(|(name, value)| {
+                    match name.to_ascii_lowercase().as_str() {
+                        PREFIX_RULE => key.starts_with(value),
+                        SUFFIX_RULE => key.ends_with(value),
+                        _ => false,
+                    }
+                })
+        })
+}
+
+fn filter_rules(queue: &QueueConfiguration) -> Vec<(&str, &str)> {
+    queue
+        .filter
+        .iter()
+        .filter_map(|x| x.key.as_ref())
+        .flat_map(|x| &x.filter_rules)
+        .map(|x| (x.name.as_str(), x.value.as_str()))
+        .collect()
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), not(target_os = "wasi")))]
+mod tests {
+    use wasmio_aws_types::types::{
+        FilterRule, NotificationConfigurationFilter, S3KeyFilter,
+    };
+
+    use super::*;
+
+    fn queue(events: &[&str], rules: &[(&str, &str)]) -> QueueConfiguration {
+        QueueConfiguration {
+            id: None,
+            queue_arn: "arn:wasmio:sqs:us-east-1:ingest:webhook".to_string(),
+            events: events.iter().map(|x| x.to_string()).collect(),
+            filter: Some(NotificationConfigurationFilter {
+                key: Some(S3KeyFilter {
+                    filter_rules: rules
+                        .iter()
+                        .map(|(name, value)| FilterRule {
+                            name: name.to_string(),
+                            value: value.to_string(),
+                        })
+                        .collect(),
+                }),
+            }),
+        }
+    }
+
+    #[test]
+    fn validate_notification_configuration() {
+        let configuration = |queue| NotificationConfiguration {
+            queue_configurations: vec![queue],
+        };
+
+        let valid = queue(
+            &["s3:ObjectCreated:*", "s3:ObjectRemoved:Delete"],
+            &[("prefix", "images/"), ("Suffix", ".jpg")],
+        );
+        assert!(validate_configuration(&configuration(valid)).is_ok());
+
+        let unsupported = queue(&["s3:ObjectRestore:Post"], &[]);
+        assert!(matches!(
+            validate_configuration(&configuration(unsupported)),
+            Err(BucketStorageError::UnsupportedNotificationEvent)
+        ));
+
+        let twice =
+            queue(&["s3:ObjectCreated:*"], &[("prefix", "a"), ("prefix", "b")]);
+        assert!(matches!(
+            validate_configuration(&configuration(twice)),
+            Err(BucketStorageError::InvalidFilterRule)
+        ));
+    }
+
+    #[test]
+    fn match_notification_configuration() {
+        let configuration = NotificationConfiguration {
+            queue_configurations: vec![
+                queue(&["s3:ObjectCreated:*"], &[("prefix", "images/")]),
+                queue(&["s3:ObjectRemoved:Delete"], &[("suffix", ".jpg")]),
+            ],
+        };
+        let count = |event, key| {
+            matching_configurations(&configuration, event, key).count()
+        };
+
+        assert_eq!(count(EventName::ObjectCreatedPut, "images/a.jpg"), 1);
+        assert_eq!(count(EventName::ObjectCreatedPost, "images/a.png"), 1);
+        assert_eq!(count(EventName::ObjectCreatedPut, "docs/a.jpg"), 0);
+        assert_eq!(count(EventName::ObjectRemovedDelete, "images/a.jpg"), 1);
+        assert_eq!(count(EventName::ObjectRemovedDelete, "images/a.png"), 0);
+    }
+}
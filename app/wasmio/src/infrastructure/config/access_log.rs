@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+use super::deserialize_interval;
+
+/// Buffering of the server access logs, they are written in the target
+/// bucket configured on each bucket with `PutBucketLogging`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AccessLogConfig {
+    /// Interval between two writes of the buffered logs, in seconds.
+    #[serde(
+        default = "default_flush_interval",
+        deserialize_with = "deserialize_interval"
+    )]
+    pub flush_interval: u64,
+    /// The logs are written before the interval when this number of lines
+    /// is buffered.
+    #[serde(default = "default_max_buffered_lines")]
+    pub max_buffered_lines: usize,
+}
+
+impl Default for AccessLogConfig {
+    fn default() -> Self {
+        Self {
+            flush_interval: default_flush_interval(),
+            max_buffered_lines: default_max_buffered_lines(),
+        }
+    }
+}
+
+const fn default_flush_interval() -> u64 {
+    300
+}
+
+const fn default_max_buffered_lines() -> usize {
+    10_000
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), not(target_os = "wasi")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flush_interval_is_not_zero() {
+        let cfg: AccessLogConfig =
+            serde_json::from_value(serde_json::json!({})).unwrap();
+        assert_eq!(cfg.flush_interval, 300);
+        let cfg: AccessLogConfig =
+            serde_json::from_value(serde_json::json!({ "flush_interval": 2 }))
+                .unwrap();
+        assert_eq!(cfg.flush_interval, 2);
+        assert!(serde_json::from_value::<AccessLogConfig>(
+            serde_json::json!({ "flush_interval": 0 })
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// Authentication of the requests with AWS Signature Version 4, when it's not
+/// configured, every request is accepted.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AuthConfig {
+    /// The credentials allowed to access the server.
+    pub credentials: Vec<Credential>,
+    /// Maximum difference allowed between the server time and the time of
+    /// the request, in seconds.
+    #[serde(default = "default_max_skew")]
+    pub max_skew: i64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Credential {
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+/// S3 rejects requests which are more than 15 minutes away from the server
+/// time.
+const fn default_max_skew() -> i64 {
+    15 * 60
+}
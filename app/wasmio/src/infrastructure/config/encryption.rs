@@ -0,0 +1,13 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Encryption at rest with a master key managed by the server (SSE-S3), every
+/// element gets its own data key which is stored encrypted with the master
+/// key.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EncryptionConfig {
+    /// A file containing the 256-bit master key encoded in base64, it can be
+    /// generated with `openssl rand -base64 32`.
+    pub master_key_file: PathBuf,
+}
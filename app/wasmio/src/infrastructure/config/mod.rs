@@ -4,17 +4,57 @@ use std::str::FromStr;
 
 use anyhow::Context;
 use config::Config;
-use serde::{Deserialize, Serialize};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+
+mod access_log;
+pub use access_log::AccessLogConfig;
+
+mod auth;
+pub use auth::AuthConfig;
+
+mod encryption;
+pub use encryption::EncryptionConfig;
+
+mod notification;
+pub use notification::{NotificationConfig, WebhookConfig};
 
 mod storage;
 pub use storage::StorageConfig;
 
+mod website;
+pub use website::WebsiteConfig;
+
 /// Configuration file for the application.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Cfg {
     pub bind_addr: SocketAddr,
 
+    /// Requests on `bucket.{domain}` are served from `bucket`
+    /// (virtual-hosted-style), path-style requests are always served.
+    pub domain: Option<String>,
+
+    /// Region of the server, every bucket is created in it.
+    #[serde(default = "default_region")]
+    pub region: String,
+
     pub storage: StorageConfig,
+
+    /// Website endpoint, disabled when not set.
+    pub website: Option<WebsiteConfig>,
+
+    /// Authentication of the requests, disabled when not set.
+    pub auth: Option<AuthConfig>,
+
+    /// Encryption at rest with a server-managed key, disabled when not set.
+    pub encryption: Option<EncryptionConfig>,
+
+    /// Delivery of the bucket events to webhooks, disabled when not set.
+    pub notification: Option<NotificationConfig>,
+
+    /// Buffering of the server access logs of the buckets.
+    #[serde(default)]
+    pub access_log: AccessLogConfig,
 }
 
 impl Cfg {
@@ -42,9 +82,38 @@ impl Cfg {
     pub fn hack() -> anyhow::Result<Cfg> {
         Ok(Cfg {
             bind_addr: SocketAddr::from_str("0.0.0.0:80")?,
-            storage: StorageConfig {
+            domain: None,
+            region: default_region(),
+            storage: StorageConfig::Fs {
                 path: PathBuf::new().join("public").join("data"),
+                chunk_size: None,
+                segment_object_size: None,
+                compaction_interval: 600,
             },
+            website: None,
+            auth: None,
+            encryption: None,
+            notification: None,
+            access_log: AccessLogConfig::default(),
         })
     }
 }
+
+/// The default region of S3, the location constraint of its buckets is
+/// empty.
+pub fn default_region() -> String {
+    DEFAULT_REGION.to_string()
+}
+
+pub const DEFAULT_REGION: &str = "us-east-1";
+
+/// An interval in seconds between two runs of a background task, the task
+/// would run without pause with an interval of 0.
+fn deserialize_interval<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<u64, D::Error> {
+    match u64::deserialize(deserializer)? {
+        0 => Err(D::Error::custom("an interval must be at least 1 second")),
+        interval => Ok(interval),
+    }
+}
@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::deserialize_interval;
+
+/// Delivery of the bucket events to webhooks, the buckets reference the
+/// webhooks in their notification configuration with the ARN
+/// `arn:wasmio:sqs:{region}:{id}:webhook`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NotificationConfig {
+    /// The events are stored in this directory until they are delivered, so
+    /// they are not lost when the server restarts.
+    pub queue_dir: PathBuf,
+    /// Number of attempts to deliver an event before it's left in the queue
+    /// until the next retry.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Interval between two retries of the queued events, in seconds.
+    #[serde(
+        default = "default_retry_interval",
+        deserialize_with = "deserialize_interval"
+    )]
+    pub retry_interval: u64,
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WebhookConfig {
+    pub id: String,
+    /// The events are sent to this endpoint with a `POST`, only `http` is
+    /// supported.
+    pub endpoint: String,
+}
+
+const fn default_max_attempts() -> u32 {
+    3
+}
+
+const fn default_retry_interval() -> u64 {
+    60
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), not(target_os = "wasi")))]
+mod tests {
+    use super::*;
+
+    fn retry_interval(value: serde_json::Value) -> Option<u64> {
+        let mut cfg = serde_json::json!({ "queue_dir": "events" });
+        if !value.is_null() {
+            cfg["retry_interval"] = value;
+        }
+        serde_json::from_value::<NotificationConfig>(cfg)
+            .ok()
+            .map(|x| x.retry_interval)
+    }
+
+    #[test]
+    fn retry_interval_is_not_zero() {
+        assert_eq!(retry_interval(serde_json::Value::Null), Some(60));
+        assert_eq!(retry_interval(5.into()), Some(5));
+        assert_eq!(retry_interval(0.into()), None);
+    }
+}
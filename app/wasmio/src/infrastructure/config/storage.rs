@@ -2,7 +2,60 @@ use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
+use super::deserialize_interval;
+
+/// The backend the elements are kept in, named by `backend`.
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct StorageConfig {
-    pub path: PathBuf,
+#[serde(tag = "backend", rename_all = "lowercase")]
+pub enum StorageConfig {
+    /// Files in the `path` directory.
+    Fs {
+        path: PathBuf,
+        /// Split the elements in chunks of this size in bytes, the identical
+        /// chunks of a bucket are stored once. Disabled when not set.
+        #[serde(default)]
+        chunk_size: Option<u64>,
+        /// Append the elements up to this size in bytes to large segment
+        /// files, instead of a file for each of them. Disabled when not set.
+        #[serde(default)]
+        segment_object_size: Option<u64>,
+        /// Interval between two compactions of the segments, in seconds.
+        #[serde(
+            default = "default_compaction_interval",
+            deserialize_with = "deserialize_interval"
+        )]
+        compaction_interval: u64,
+    },
+    /// In memory, everything is lost when the process stops.
+    Memory,
+}
+
+const fn default_compaction_interval() -> u64 {
+    600
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), not(target_os = "wasi")))]
+mod tests {
+    use super::*;
+
+    fn compaction_interval(value: serde_json::Value) -> Option<u64> {
+        let mut cfg = serde_json::json!({ "backend": "fs", "path": "data" });
+        if !value.is_null() {
+            cfg["compaction_interval"] = value;
+        }
+        match serde_json::from_value(cfg) {
+            Ok(StorageConfig::Fs {
+                compaction_interval,
+                ..
+            }) => Some(compaction_interval),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn compaction_interval_is_not_zero() {
+        assert_eq!(compaction_interval(serde_json::Value::Null), Some(600));
+        assert_eq!(compaction_interval(60.into()), Some(60));
+        assert_eq!(compaction_interval(0.into()), None);
+    }
 }
@@ -0,0 +1,14 @@
+use std::net::SocketAddr;
+
+use serde::{Deserialize, Serialize};
+
+/// The website endpoint serves buckets with a website configuration as static
+/// websites.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WebsiteConfig {
+    /// The bind address of the website endpoint.
+    pub bind_addr: SocketAddr,
+    /// When set, requests on `bucket.{domain}` are served from `bucket`,
+    /// otherwise the whole host is used as the bucket name.
+    pub domain: Option<String>,
+}
@@ -2,4 +2,5 @@ pub mod axum;
 pub mod config;
 pub mod constant;
 pub mod instrumentation;
+pub mod notification;
 pub mod storage;
@@ -0,0 +1,84 @@
+//! The message delivered for an event, in the format of the S3 event
+//! notifications.
+//!
+//! See [`Event message structure`](https://docs.aws.amazon.com/AmazonS3/latest/userguide/notification-content-structure.html)
+use serde::{Deserialize, Serialize};
+
+pub const EVENT_VERSION: &str = "2.1";
+pub const EVENT_SOURCE: &str = "aws:s3";
+pub const S3_SCHEMA_VERSION: &str = "1.0";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Event {
+    pub records: Vec<EventRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventRecord {
+    pub event_version: String,
+    pub event_source: String,
+    pub aws_region: String,
+    /// The time when the request finished, in ISO-8601 format.
+    pub event_time: String,
+    /// The event type, without the `s3:` prefix.
+    pub event_name: String,
+    pub user_identity: Identity,
+    pub request_parameters: RequestParameters,
+    pub response_elements: ResponseElements,
+    pub s3: S3Entity,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Identity {
+    pub principal_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestParameters {
+    #[serde(rename = "sourceIPAddress")]
+    pub source_ip_address: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResponseElements {
+    #[serde(rename = "x-amz-request-id")]
+    pub request_id: String,
+    #[serde(rename = "x-amz-id-2")]
+    pub host_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct S3Entity {
+    pub s3_schema_version: String,
+    /// The ID of the configuration which selected the event.
+    pub configuration_id: String,
+    pub bucket: BucketEntity,
+    pub object: ObjectEntity,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BucketEntity {
+    pub name: String,
+    pub owner_identity: Identity,
+    pub arn: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectEntity {
+    /// The key is URL encoded.
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(rename = "eTag", skip_serializing_if = "Option::is_none")]
+    pub e_tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+    /// Orders the events of a key, a later event has a greater sequencer.
+    pub sequencer: String,
+}
@@ -0,0 +1,476 @@
+//! Delivery of the bucket events to webhooks.
+//!
+//! An event is stored in the [EventQueue] before being sent, it's only
+//! removed once the webhook acknowledged it, so the events which couldn't be
+//! delivered are retried periodically, even after a restart.
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Request, Uri};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tracing::{info, warn};
+use ulid::Ulid;
+
+pub mod event;
+mod queue;
+
+use event::Event;
+use queue::{EventQueue, QueuedEvent};
+
+use super::config::{NotificationConfig, WebhookConfig};
+
+const ARN_PREFIX: &str = "arn:wasmio:sqs:";
+const ARN_SUFFIX: &str = ":webhook";
+
+/// Delay before the second attempt of a delivery, doubled at each attempt.
+const RETRY_BACKOFF: Duration = Duration::from_secs(1);
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    inner: Arc<NotifierInner>,
+}
+
+#[derive(Debug)]
+struct NotifierInner {
+    /// Endpoint by webhook ID.
+    targets: HashMap<String, Uri>,
+    region: String,
+    queue: EventQueue,
+    client: Client<HttpConnector>,
+    max_attempts: u32,
+    /// Events being delivered, so the retries don't send them twice.
+    in_flight: Mutex<HashSet<Ulid>>,
+    metrics: Metrics,
+}
+
+#[derive(Debug, Default)]
+struct Metrics {
+    pending: AtomicU64,
+    delivered: AtomicU64,
+    failed: AtomicU64,
+    dropped: AtomicU64,
+}
+
+/// Counters of the deliveries since the server started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DeliveryMetrics {
+    /// Events waiting in the queue.
+    pub pending: u64,
+    pub delivered: u64,
+    /// Failed attempts, an event can fail several times before being
+    /// delivered.
+    pub failed: u64,
+    /// Events removed from the queue as their webhook is not configured
+    /// anymore.
+    pub dropped: u64,
+}
+
+impl Notifier {
+    /// Open the queue and start the retries of the queued events, the events
+    /// left by a previous run are sent right away.
+    pub async fn start(
+        cfg: &NotificationConfig,
+        region: &str,
+    ) -> anyhow::Result<Self> {
+        let targets = cfg
+            .webhooks
+            .iter()
+            .map(|webhook: &WebhookConfig| {
+                let endpoint = webhook
+                    .endpoint
+                    .parse::<Uri>()
+                    .ok()
+                    .filter(|x| x.scheme_str() == Some("http"))
+                    .with_context(|| {
+                        format!(
+                            "The endpoint of the webhook `{}` must be an http \
+                             URL.",
+                            webhook.id
+                        )
+                    })?;
+                Ok((webhook.id.clone(), endpoint))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        let queue = EventQueue::open(&cfg.queue_dir).with_context(|| {
+            format!("Can't open the event queue {:?}", cfg.queue_dir)
+        })?;
+        let pending = queue.list().await?.len() as u64;
+
+        let notifier = Self {
+            inner: Arc::new(NotifierInner {
+                targets,
+                region: region.to_string(),
+                queue,
+                client: Client::new(),
+                max_attempts: cfg.max_attempts.max(1),
+                in_flight: Mutex::new(HashSet::new()),
+                metrics: Metrics {
+                    pending: AtomicU64::new(pending),
+                    ..Metrics::default()
+                },
+            }),
+        };
+        notifier.spawn_retries(Duration::from_secs(cfg.retry_interval));
+
+        Ok(notifier)
+    }
+
+    /// The ID of the webhook referenced by the ARN
+    /// `arn:wasmio:sqs:{region}:{id}:webhook`, the region can be omitted.
+    pub fn target_of<'a>(&self, arn: &'a str) -> Option<&'a str> {
+        let (region, id) = arn
+            .strip_prefix(ARN_PREFIX)?
+            .strip_suffix(ARN_SUFFIX)?
+            .split_once(':')?;
+        if !region.is_empty() && region != self.inner.region {
+            return None;
+        }
+        self.inner.targets.contains_key(id).then_some(id)
+    }
+
+    /// Queue an event for the webhook `target`, it's delivered in the
+    /// background.
+    pub async fn send(&self, target: &str, event: Event) -> anyhow::Result<()> {
+        let id = self
+            .inner
+            .queue
+            .push(&QueuedEvent {
+                target: target.to_string(),
+                event,
+            })
+            .await?;
+        self.inner.metrics.pending.fetch_add(1, Ordering::Relaxed);
+
+        tokio::spawn(self.clone().deliver(id));
+        Ok(())
+    }
+
+    pub fn metrics(&self) -> DeliveryMetrics {
+        let metrics = &self.inner.metrics;
+        DeliveryMetrics {
+            pending: metrics.pending.load(Ordering::Relaxed),
+            delivered: metrics.delivered.load(Ordering::Relaxed),
+            failed: metrics.failed.load(Ordering::Relaxed),
+            dropped: metrics.dropped.load(Ordering::Relaxed),
+        }
+    }
+
+    fn spawn_retries(&self, interval: Duration) {
+        let notifier = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match notifier.inner.queue.list().await {
+                    Ok(ids) => {
+                        for id in ids {
+                            notifier.clone().deliver(id).await;
+                        }
+                    }
+                    Err(err) => warn!("Can't list the queued events: {err}"),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+    }
+
+    /// Try to deliver a queued event, it stays in the queue when every
+    /// attempt failed.
+    async fn deliver(self, id: Ulid) {
+        if !self.inner.in_flight.lock().insert(id) {
+            return;
+        }
+        self.try_deliver(id).await;
+        self.inner.in_flight.lock().remove(&id);
+    }
+
+    async fn try_deliver(&self, id: Ulid) {
+        let inner = &self.inner;
+        let QueuedEvent { target, event } = match inner.queue.get(id).await {
+            Ok(event) => event,
+            // Already delivered by a concurrent attempt.
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return,
+            Err(err) => {
+                warn!("Can't read the queued event {id}: {err}");
+                return;
+            }
+        };
+
+        let Some(endpoint) = inner.targets.get(&target) else {
+            warn!(
+                message = "The webhook of the event is not configured",
+                event = %id,
+                target = %target,
+            );
+            self.remove(id, &inner.metrics.dropped).await;
+            return;
+        };
+        let body = match serde_json::to_vec(&event) {
+            Ok(body) => body,
+            Err(err) => {
+                warn!("Can't serialize the event {id}: {err}");
+                return;
+            }
+        };
+
+        for attempt in 0..inner.max_attempts {
+            if attempt > 0 {
+                tokio::time::sleep(RETRY_BACKOFF * 2u32.pow(attempt - 1)).await;
+            }
+
+            match self.post(endpoint, body.clone()).await {
+                Ok(()) => {
+                    info!(
+                        message = "Event delivered",
+                        event = %id,
+                        target = %target,
+                    );
+                    self.remove(id, &inner.metrics.delivered).await;
+                    return;
+                }
+                Err(err) => {
+                    inner.metrics.failed.fetch_add(1, Ordering::Relaxed);
+                    warn!(
+                        message = "Can't deliver the event",
+                        event = %id,
+                        target = %target,
+                        attempt = attempt + 1,
+                        error = %err,
+                    );
+                }
+            }
+        }
+    }
+
+    async fn post(&self, endpoint: &Uri, body: Vec<u8>) -> anyhow::Result<()> {
+        let request = Request::post(endpoint)
+            .header(hyper::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))?;
+        let response = tokio::time::timeout(
+            DELIVERY_TIMEOUT,
+            self.inner.client.request(request),
+        )
+        .await
+        .context("The delivery timed out")??;
+
+        if !response.status().is_success() {
+            anyhow::bail!("The webhook answered {}", response.status());
+        }
+        Ok(())
+    }
+
+    /// Remove a handled event from the queue, `counter` is the outcome of
+    /// the event.
+    async fn remove(&self, id: Ulid, counter: &AtomicU64) {
+        if let Err(err) = self.inner.queue.remove(id).await {
+            warn!("Can't remove the event {id} from the queue: {err}");
+            return;
+        }
+        counter.fetch_add(1, Ordering::Relaxed);
+        self.inner.metrics.pending.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), not(target_os = "wasi")))]
+mod tests {
+    use std::net::SocketAddr;
+
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use axum::Router;
+    use tokio::net::TcpListener;
+
+    use super::event::*;
+    use super::*;
+
+    fn event(key: &str) -> Event {
+        Event {
+            records: vec![EventRecord {
+                event_version: EVENT_VERSION.to_string(),
+                event_source: EVENT_SOURCE.to_string(),
+                aws_region: "us-east-1".to_string(),
+                event_time: "2024-01-01T00:00:00.000Z".to_string(),
+                event_name: "ObjectCreated:Put".to_string(),
+                user_identity: Identity {
+                    principal_id: "wasmio".to_string(),
+                },
+                request_parameters: RequestParameters {
+                    source_ip_address: "127.0.0.1".to_string(),
+                },
+                response_elements: ResponseElements {
+                    request_id: "request".to_string(),
+                    host_id: "host".to_string(),
+                },
+                s3: S3Entity {
+                    s3_schema_version: S3_SCHEMA_VERSION.to_string(),
+                    configuration_id: "config".to_string(),
+                    bucket: BucketEntity {
+                        name: "bucket".to_string(),
+                        owner_identity: Identity {
+                            principal_id: "wasmio".to_string(),
+                        },
+                        arn: "arn:aws:s3:::bucket".to_string(),
+                    },
+                    object: ObjectEntity {
+                        key: key.to_string(),
+                        size: Some(2),
+                        e_tag: Some("etag".to_string()),
+                        version_id: None,
+                        sequencer: "0".to_string(),
+                    },
+                },
+            }],
+        }
+    }
+
+    /// A webhook answering with the `statuses` in order, then with `200`.
+    async fn webhook(
+        statuses: Vec<StatusCode>,
+    ) -> (SocketAddr, Arc<Mutex<Vec<Event>>>) {
+        #[derive(Clone)]
+        struct WebhookState {
+            statuses: Arc<Mutex<Vec<StatusCode>>>,
+            received: Arc<Mutex<Vec<Event>>>,
+        }
+
+        async fn receive(
+            State(state): State<WebhookState>,
+            axum::Json(event): axum::Json<Event>,
+        ) -> StatusCode {
+            let mut statuses = state.statuses.lock();
+            if statuses.is_empty() {
+                state.received.lock().push(event);
+                return StatusCode::OK;
+            }
+            statuses.remove(0)
+        }
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let router = Router::new().route("/events", post(receive)).with_state(
+            WebhookState {
+                statuses: Arc::new(Mutex::new(statuses)),
+                received: received.clone(),
+            },
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        (addr, received)
+    }
+
+    fn config(dir: &std::path::Path, addr: SocketAddr) -> NotificationConfig {
+        NotificationConfig {
+            queue_dir: dir.to_path_buf(),
+            max_attempts: 3,
+            retry_interval: 3600,
+            webhooks: vec![WebhookConfig {
+                id: "ingest".to_string(),
+                endpoint: format!("http://{addr}/events"),
+            }],
+        }
+    }
+
+    async fn wait_empty_queue(notifier: &Notifier) {
+        for _ in 0..100 {
+            if notifier.metrics().pending == 0 {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("Events still queued: {:?}", notifier.metrics());
+    }
+
+    #[tokio::test]
+    async fn test_queue_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = EventQueue::open(dir.path()).unwrap();
+
+        let first = QueuedEvent {
+            target: "ingest".to_string(),
+            event: event("a"),
+        };
+        let second = QueuedEvent {
+            target: "ingest".to_string(),
+            event: event("b"),
+        };
+        let first_id = queue.push(&first).await.unwrap();
+        let second_id = queue.push(&second).await.unwrap();
+
+        assert_eq!(queue.list().await.unwrap(), vec![first_id, second_id]);
+        assert_eq!(queue.get(second_id).await.unwrap(), second);
+
+        queue.remove(first_id).await.unwrap();
+        assert_eq!(queue.list().await.unwrap(), vec![second_id]);
+    }
+
+    #[tokio::test]
+    async fn test_deliver_event_with_retries() {
+        let dir = tempfile::tempdir().unwrap();
+        let (addr, received) =
+            webhook(vec![StatusCode::INTERNAL_SERVER_ERROR]).await;
+        let notifier = Notifier::start(&config(dir.path(), addr), "us-east-1")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            notifier.target_of("arn:wasmio:sqs:us-east-1:ingest:webhook"),
+            Some("ingest")
+        );
+        assert_eq!(
+            notifier.target_of("arn:wasmio:sqs::ingest:webhook"),
+            Some("ingest")
+        );
+        assert_eq!(
+            notifier.target_of("arn:wasmio:sqs:eu-west-1:ingest:webhook"),
+            None
+        );
+        assert_eq!(notifier.target_of("arn:wasmio:sqs::other:webhook"), None);
+
+        notifier.send("ingest", event("a")).await.unwrap();
+        wait_empty_queue(&notifier).await;
+
+        assert_eq!(*received.lock(), vec![event("a")]);
+        assert_eq!(
+            notifier.metrics(),
+            DeliveryMetrics {
+                pending: 0,
+                delivered: 1,
+                failed: 1,
+                dropped: 0,
+            }
+        );
+        assert!(notifier.inner.queue.list().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_deliver_queued_events_on_start() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = EventQueue::open(dir.path()).unwrap();
+        for (target, key) in [("ingest", "a"), ("removed", "b")] {
+            queue
+                .push(&QueuedEvent {
+                    target: target.to_string(),
+                    event: event(key),
+                })
+                .await
+                .unwrap();
+        }
+
+        let (addr, received) = webhook(Vec::new()).await;
+        let notifier = Notifier::start(&config(dir.path(), addr), "us-east-1")
+            .await
+            .unwrap();
+        wait_empty_queue(&notifier).await;
+
+        assert_eq!(*received.lock(), vec![event("a")]);
+        assert_eq!(notifier.metrics().dropped, 1);
+        assert_eq!(notifier.metrics().pending, 0);
+    }
+}
@@ -0,0 +1,89 @@
+//! Events waiting to be delivered, one file by event so an event is only
+//! removed once it's delivered.
+use std::path::{Path, PathBuf};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use super::event::Event;
+
+const EXTENSION: &str = "json";
+const TMP_EXTENSION: &str = "tmp";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedEvent {
+    /// ID of the webhook receiving the event.
+    pub target: String,
+    pub event: Event,
+}
+
+#[derive(Debug)]
+pub struct EventQueue {
+    dir: PathBuf,
+    /// The IDs are monotonic so the events are listed in the order they were
+    /// pushed, even within the same millisecond.
+    last_id: Mutex<Ulid>,
+}
+
+impl EventQueue {
+    pub fn open(dir: &Path) -> std::io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            last_id: Mutex::new(Ulid::nil()),
+        })
+    }
+
+    fn path(&self, id: Ulid) -> PathBuf {
+        self.dir.join(id.to_string()).with_extension(EXTENSION)
+    }
+
+    /// Store an event, it's written in a temporary file first so a partial
+    /// event is never read.
+    pub async fn push(&self, event: &QueuedEvent) -> std::io::Result<Ulid> {
+        let id = {
+            let mut last_id = self.last_id.lock();
+            let id = Ulid::new();
+            *last_id = match id > *last_id {
+                true => id,
+                false => last_id.increment().unwrap_or(id),
+            };
+            *last_id
+        };
+        let content = serde_json::to_vec(event)?;
+
+        let tmp = self.path(id).with_extension(TMP_EXTENSION);
+        tokio::fs::write(&tmp, content).await?;
+        tokio::fs::rename(&tmp, self.path(id)).await?;
+        Ok(id)
+    }
+
+    pub async fn get(&self, id: Ulid) -> std::io::Result<QueuedEvent> {
+        let content = tokio::fs::read(self.path(id)).await?;
+        Ok(serde_json::from_slice(&content)?)
+    }
+
+    pub async fn remove(&self, id: Ulid) -> std::io::Result<()> {
+        tokio::fs::remove_file(self.path(id)).await
+    }
+
+    /// The queued events, from the oldest to the newest.
+    pub async fn list(&self) -> std::io::Result<Vec<Ulid>> {
+        let mut ids = Vec::new();
+        let mut entries = tokio::fs::read_dir(&self.dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|x| x.to_str()) != Some(EXTENSION) {
+                continue;
+            }
+            let id = path
+                .file_stem()
+                .and_then(|x| x.to_str())
+                .and_then(|x| Ulid::from_string(x).ok());
+            ids.extend(id);
+        }
+        ids.sort();
+        Ok(ids)
+    }
+}
@@ -0,0 +1,54 @@
+use std::path::{Path, PathBuf};
+
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+
+/// The temporary file where the content of `path` is written before it
+/// replaces it.
+pub fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Replace the content of `path`, it has either its previous or its new
+/// content whenever the process stops.
+pub async fn write_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let temp = temp_path(path);
+    let mut file = File::create(&temp).await?;
+    file.write_all(content).await?;
+    persist(file, &temp, path).await
+}
+
+/// Move the `file` written at `temp` to `path` once its content is on the
+/// disk, so `path` is never partially written.
+pub async fn persist(
+    file: File,
+    temp: &Path,
+    path: &Path,
+) -> std::io::Result<()> {
+    file.sync_all().await?;
+    drop(file);
+    tokio::fs::rename(temp, path).await?;
+    sync_parent(path).await
+}
+
+/// Create the directory `path` when it doesn't exist, it's durable once it
+/// returns like the files moved in it.
+pub async fn create_dir(path: &Path) -> std::io::Result<()> {
+    if tokio::fs::try_exists(path).await? {
+        return Ok(());
+    }
+    tokio::fs::create_dir_all(path).await?;
+    sync_parent(path).await
+}
+
+/// The rename is only durable once the directory is synced.
+pub async fn sync_parent(path: &Path) -> std::io::Result<()> {
+    // A directory can't be opened as a file with WASI.
+    #[cfg(not(target_os = "wasi"))]
+    if let Some(parent) = path.parent() {
+        File::open(parent).await?.sync_all().await?;
+    }
+    Ok(())
+}
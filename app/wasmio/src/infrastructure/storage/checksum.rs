@@ -0,0 +1,197 @@
+//! Additional checksums computed while an element is inserted.
+//!
+//! See [`Checking object integrity`](https://docs.aws.amazon.com/AmazonS3/latest/userguide/checking-object-integrity.html)
+use std::sync::Arc;
+
+use base64ct::{Base64, Encoding};
+use crc::{Crc, Digest as CrcDigest, CRC_32_ISCSI, CRC_32_ISO_HDLC};
+use parking_lot::Mutex;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use wasmio_aws_types::types::Checksum;
+
+static CRC32: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+static CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+
+/// The algorithms supported for the additional checksums.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    strum::Display,
+    strum::EnumString,
+    strum::EnumIter,
+)]
+#[strum(ascii_case_insensitive)]
+pub enum ChecksumAlgorithm {
+    CRC32,
+    CRC32C,
+    SHA1,
+    SHA256,
+}
+
+impl ChecksumAlgorithm {
+    /// The `x-amz-checksum-*` header holding a checksum of this algorithm.
+    pub const fn header_name(&self) -> &'static str {
+        match self {
+            Self::CRC32 => "x-amz-checksum-crc32",
+            Self::CRC32C => "x-amz-checksum-crc32c",
+            Self::SHA1 => "x-amz-checksum-sha1",
+            Self::SHA256 => "x-amz-checksum-sha256",
+        }
+    }
+
+    /// Find the algorithm from the name of a `x-amz-checksum-*` header.
+    pub fn from_header_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        <Self as strum::IntoEnumIterator>::iter()
+            .find(|x| x.header_name() == name)
+    }
+
+    pub fn hasher(&self) -> ChecksumHasher {
+        match self {
+            Self::CRC32 => ChecksumHasher::Crc32(CRC32.digest()),
+            Self::CRC32C => ChecksumHasher::Crc32c(CRC32C.digest()),
+            Self::SHA1 => ChecksumHasher::Sha1(Sha1::new()),
+            Self::SHA256 => ChecksumHasher::Sha256(Sha256::new()),
+        }
+    }
+}
+
+/// Compute a checksum over a streamed content.
+pub enum ChecksumHasher {
+    Crc32(CrcDigest<'static, u32>),
+    Crc32c(CrcDigest<'static, u32>),
+    Sha1(Sha1),
+    Sha256(Sha256),
+}
+
+impl ChecksumHasher {
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Crc32(x) | Self::Crc32c(x) => x.update(data),
+            Self::Sha1(x) => x.update(data),
+            Self::Sha256(x) => x.update(data),
+        }
+    }
+
+    /// The checksum encoded in base64, as sent in the headers.
+    pub fn finalize(self) -> String {
+        match self {
+            Self::Crc32(x) | Self::Crc32c(x) => {
+                Base64::encode_string(&x.finalize().to_be_bytes())
+            }
+            Self::Sha1(x) => Base64::encode_string(&x.finalize()),
+            Self::Sha256(x) => Base64::encode_string(&x.finalize()),
+        }
+    }
+}
+
+/// A checksum of an element.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ElementChecksum {
+    pub algorithm: ChecksumAlgorithm,
+    /// Encoded in base64.
+    pub value: String,
+}
+
+impl From<ElementChecksum> for Checksum {
+    fn from(ElementChecksum { algorithm, value }: ElementChecksum) -> Self {
+        let value = Some(value);
+        match algorithm {
+            ChecksumAlgorithm::CRC32 => Checksum {
+                checksum_crc32: value,
+                ..Default::default()
+            },
+            ChecksumAlgorithm::CRC32C => Checksum {
+                checksum_crc32c: value,
+                ..Default::default()
+            },
+            ChecksumAlgorithm::SHA1 => Checksum {
+                checksum_sha1: value,
+                ..Default::default()
+            },
+            ChecksumAlgorithm::SHA256 => Checksum {
+                checksum_sha256: value,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// The expected value of a checksum, shared as it's only known once the
+/// content is read when it's sent in a trailing header.
+#[derive(Debug, Clone, Default)]
+pub struct ExpectedChecksum(Arc<Mutex<Option<String>>>);
+
+impl ExpectedChecksum {
+    pub fn new(value: Option<String>) -> Self {
+        Self(Arc::new(Mutex::new(value)))
+    }
+
+    pub fn set(&self, value: String) {
+        *self.0.lock() = Some(value);
+    }
+
+    pub fn get(&self) -> Option<String> {
+        self.0.lock().clone()
+    }
+}
+
+/// A checksum to compute while an element is inserted, the insertion fails
+/// when it doesn't match the expected value.
+#[derive(Debug, Clone)]
+pub struct ChecksumRequest {
+    pub algorithm: ChecksumAlgorithm,
+    pub expected: ExpectedChecksum,
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), not(target_os = "wasi")))]
+mod tests {
+    use super::*;
+
+    fn checksum(algorithm: ChecksumAlgorithm, data: &[u8]) -> String {
+        let mut hasher = algorithm.hasher();
+        let (a, b) = data.split_at(data.len() / 2);
+        hasher.update(a);
+        hasher.update(b);
+        hasher.finalize()
+    }
+
+    #[test]
+    fn compute_checksums() {
+        let data = b"hello world";
+        insta::assert_debug_snapshot!([
+            checksum(ChecksumAlgorithm::CRC32, data),
+            checksum(ChecksumAlgorithm::CRC32C, data),
+            checksum(ChecksumAlgorithm::SHA1, data),
+            checksum(ChecksumAlgorithm::SHA256, data),
+        ], @r###"
+        [
+            "DUoRhQ==",
+            "yZRlqg==",
+            "Kq5sNclPz7QV2+lfQIuc6R7oRu0=",
+            "uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek=",
+        ]
+        "###);
+    }
+
+    #[test]
+    fn parse_algorithms() {
+        assert_eq!(
+            "crc32c".parse::<ChecksumAlgorithm>().ok(),
+            Some(ChecksumAlgorithm::CRC32C)
+        );
+        assert_eq!(
+            ChecksumAlgorithm::from_header_name("X-Amz-Checksum-Sha1"),
+            Some(ChecksumAlgorithm::SHA1)
+        );
+        assert_eq!(ChecksumAlgorithm::from_header_name("x-amz-checksum"), None);
+        assert_eq!(ChecksumAlgorithm::SHA256.to_string(), "SHA256");
+    }
+}
@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+use std::io::{ErrorKind, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, ReadBuf};
+
+use super::atomic::write_atomic;
+use super::lock::{FileLock, FileLocks};
+
+/// A chunk of the content of an element, it's stored once whatever the
+/// number of elements it's part of.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ElementChunk {
+    /// The SHA256 digest of the stored chunk, hex encoded.
+    pub hash: String,
+    pub size: u64,
+}
+
+/// Usage of the chunks of a database.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkStats {
+    /// Bytes of the chunks referenced by the elements, counted for each
+    /// reference.
+    pub referenced_bytes: u64,
+    /// Bytes of the chunks stored, counted once.
+    pub stored_bytes: u64,
+}
+
+impl ChunkStats {
+    /// Account for the chunks `added` and `removed` since the stats were
+    /// read.
+    pub fn update(&self, added: &ChunkStats, removed: &ChunkStats) -> Self {
+        Self {
+            referenced_bytes: (self.referenced_bytes + added.referenced_bytes)
+                .saturating_sub(removed.referenced_bytes),
+            stored_bytes: (self.stored_bytes + added.stored_bytes)
+                .saturating_sub(removed.stored_bytes),
+        }
+    }
+
+    /// How many times the stored bytes are referenced, `1` when nothing is
+    /// deduplicated.
+    pub fn dedup_ratio(&self) -> f64 {
+        match self.stored_bytes {
+            0 => 1.0,
+            stored_bytes => self.referenced_bytes as f64 / stored_bytes as f64,
+        }
+    }
+}
+
+/// The chunks of a database, each chunk is a file named by its digest with
+/// the number of elements referencing it alongside.
+///
+/// The references are only updated while the store is locked.
+#[derive(Debug, Clone)]
+pub struct ChunkStore {
+    dir: PathBuf,
+    locks: FileLocks,
+}
+
+impl ChunkStore {
+    pub fn new(dir: PathBuf, locks: FileLocks) -> Self {
+        Self { dir, locks }
+    }
+
+    /// The chunks are sharded by the first bytes of their digest, to not
+    /// have too many files in a directory.
+    pub fn chunk_path(&self, hash: &str) -> PathBuf {
+        self.dir.join(&hash[..2]).join(hash)
+    }
+
+    fn refs_path(&self, hash: &str) -> PathBuf {
+        self.dir.join(&hash[..2]).join(format!("{hash}.refs"))
+    }
+
+    async fn lock(&self) -> std::io::Result<FileLock> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        self.locks.lock(&self.dir.join("lock")).await
+    }
+
+    async fn refs(&self, hash: &str) -> std::io::Result<u64> {
+        match tokio::fs::read_to_string(self.refs_path(hash)).await {
+            Ok(refs) => refs.trim().parse().map_err(|_| {
+                std::io::Error::new(ErrorKind::InvalidData, "invalid refs")
+            }),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(0),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Split the content of `path` in chunks of `chunk_size` bytes and store
+    /// the ones which are not already stored, they are counted in `added`.
+    pub async fn store(
+        &self,
+        path: &Path,
+        chunk_size: u64,
+        added: &mut ChunkStats,
+    ) -> std::io::Result<Vec<ElementChunk>> {
+        let mut content = File::open(path).await?;
+        let mut chunks = Vec::new();
+        let mut buffer = Vec::with_capacity(chunk_size as usize);
+
+        loop {
+            buffer.clear();
+            (&mut content)
+                .take(chunk_size)
+                .read_to_end(&mut buffer)
+                .await?;
+            if buffer.is_empty() {
+                break;
+            }
+
+            let hash = hex::encode(Sha256::digest(&buffer));
+            let size = buffer.len() as u64;
+
+            let _lock = self.lock().await?;
+            let refs = self.refs(&hash).await?;
+            if refs == 0 {
+                tokio::fs::create_dir_all(self.dir.join(&hash[..2])).await?;
+                write_atomic(&self.chunk_path(&hash), &buffer).await?;
+                added.stored_bytes += size;
+            }
+            write_atomic(
+                &self.refs_path(&hash),
+                (refs + 1).to_string().as_bytes(),
+            )
+            .await?;
+            added.referenced_bytes += size;
+
+            chunks.push(ElementChunk { hash, size });
+        }
+
+        Ok(chunks)
+    }
+
+    /// Remove a reference to each of the `chunks`, a chunk is removed when
+    /// nothing references it anymore. They are counted in `removed`.
+    pub async fn release(
+        &self,
+        chunks: &[ElementChunk],
+        removed: &mut ChunkStats,
+    ) -> std::io::Result<()> {
+        for chunk in chunks {
+            let _lock = self.lock().await?;
+            let refs = self.refs(&chunk.hash).await?;
+            removed.referenced_bytes += chunk.size;
+            match refs {
+                0 | 1 => {
+                    remove_if_exists(&self.chunk_path(&chunk.hash)).await?;
+                    remove_if_exists(&self.refs_path(&chunk.hash)).await?;
+                    removed.stored_bytes += chunk.size;
+                }
+                refs => {
+                    write_atomic(
+                        &self.refs_path(&chunk.hash),
+                        (refs - 1).to_string().as_bytes(),
+                    )
+                    .await?
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Set the references of the chunks to `refs`, by digest, the chunks
+    /// which are not referenced are removed.
+    pub async fn rebuild(
+        &self,
+        refs: &HashMap<String, u64>,
+    ) -> std::io::Result<()> {
+        let mut shards = match tokio::fs::read_dir(&self.dir).await {
+            Ok(shards) => shards,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err),
+        };
+
+        let _lock = self.lock().await?;
+        while let Some(shard) = shards.next_entry().await? {
+            if !shard.file_type().await?.is_dir() {
+                continue;
+            }
+            let mut chunks = tokio::fs::read_dir(shard.path()).await?;
+            while let Some(chunk) = chunks.next_entry().await? {
+                let path = chunk.path();
+                match path.extension() {
+                    // Being written when the process stopped.
+                    Some(extension) if extension == "tmp" => {
+                        remove_if_exists(&path).await?;
+                    }
+                    // The chunk was removed before its references.
+                    Some(extension) if extension == "refs" => {
+                        let chunk_path = path.with_extension("");
+                        if tokio::fs::metadata(chunk_path).await.is_err() {
+                            remove_if_exists(&path).await?;
+                        }
+                    }
+                    Some(_) => {}
+                    None => {
+                        let hash =
+                            chunk.file_name().to_string_lossy().to_string();
+                        match refs.get(&hash) {
+                            Some(refs) => {
+                                write_atomic(
+                                    &self.refs_path(&hash),
+                                    refs.to_string().as_bytes(),
+                                )
+                                .await?;
+                            }
+                            None => {
+                                remove_if_exists(&path).await?;
+                                remove_if_exists(&self.refs_path(&hash))
+                                    .await?;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Bytes of the chunks stored.
+    pub async fn stored_bytes(&self) -> std::io::Result<u64> {
+        let mut shards = match tokio::fs::read_dir(&self.dir).await {
+            Ok(shards) => shards,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(err),
+        };
+
+        let mut stored_bytes = 0;
+        while let Some(shard) = shards.next_entry().await? {
+            if !shard.file_type().await?.is_dir() {
+                continue;
+            }
+            let mut chunks = tokio::fs::read_dir(shard.path()).await?;
+            while let Some(chunk) = chunks.next_entry().await? {
+                if chunk.path().extension().is_none() {
+                    stored_bytes += chunk.metadata().await?.len();
+                }
+            }
+        }
+
+        Ok(stored_bytes)
+    }
+
+    /// Open the chunks of an element as a single content.
+    pub async fn open(
+        &self,
+        chunks: &[ElementChunk],
+    ) -> std::io::Result<ChunkReader> {
+        let chunks = chunks
+            .iter()
+            .map(|x| (self.chunk_path(&x.hash), x.size))
+            .collect::<Vec<_>>();
+        // The first chunk is opened right away, so a missing chunk is known
+        // before anything is read.
+        let current = match chunks.first() {
+            Some((path, _)) => Some(File::open(path).await?),
+            None => None,
+        };
+
+        Ok(ChunkReader {
+            size: chunks.iter().map(|(_, size)| size).sum(),
+            chunks,
+            position: 0,
+            current,
+            opening: None,
+        })
+    }
+}
+
+pub async fn remove_if_exists(path: &Path) -> std::io::Result<()> {
+    match tokio::fs::remove_file(path).await {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+/// Reads the chunks of an element one after the other.
+pub struct ChunkReader {
+    /// Path and size of each chunk.
+    chunks: Vec<(PathBuf, u64)>,
+    size: u64,
+    /// Position in the whole content.
+    position: u64,
+    /// The chunk at `position`, it's opened when it's read.
+    current: Option<File>,
+    /// The chunk at `position` while it's opened.
+    opening: Option<BoxFuture<'static, std::io::Result<File>>>,
+}
+
+impl ChunkReader {
+    /// The chunk at `position` and the offset of `position` in it.
+    fn locate(&self) -> Option<(usize, u64)> {
+        let mut start = 0;
+        for (index, (_, size)) in self.chunks.iter().enumerate() {
+            if self.position < start + size {
+                return Some((index, self.position - start));
+            }
+            start += size;
+        }
+        None
+    }
+}
+
+impl AsyncRead for ChunkReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        let Some((index, offset)) = this.locate() else {
+            return Poll::Ready(Ok(()));
+        };
+        let remaining = this.chunks[index].1 - offset;
+
+        let current = match &mut this.current {
+            Some(current) => current,
+            None => {
+                let path = this.chunks[index].0.clone();
+                let opening = this.opening.get_or_insert_with(|| {
+                    Box::pin(async move {
+                        let mut file = File::open(path).await?;
+                        file.seek(SeekFrom::Start(offset)).await?;
+                        Ok(file)
+                    })
+                });
+                let file = ready!(opening.as_mut().poll(cx));
+                this.opening = None;
+                this.current.insert(file?)
+            }
+        };
+
+        let filled = buf.filled().len();
+        ready!(Pin::new(current).poll_read(cx, buf))?;
+        let read = (buf.filled().len() - filled) as u64;
+        if read == 0 && buf.remaining() > 0 {
+            return Poll::Ready(Err(ErrorKind::UnexpectedEof.into()));
+        }
+
+        this.position += read;
+        if read >= remaining {
+            this.current = None;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncSeek for ChunkReader {
+    fn start_seek(
+        self: Pin<&mut Self>,
+        position: SeekFrom,
+    ) -> std::io::Result<()> {
+        let this = self.get_mut();
+        let position = match position {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::Current(offset) => {
+                this.position.checked_add_signed(offset)
+            }
+            SeekFrom::End(offset) => this.size.checked_add_signed(offset),
+        }
+        .ok_or_else(|| {
+            std::io::Error::new(ErrorKind::InvalidInput, "invalid seek")
+        })?;
+
+        if position != this.position {
+            this.position = position;
+            this.current = None;
+            this.opening = None;
+        }
+        Ok(())
+    }
+
+    fn poll_complete(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<std::io::Result<u64>> {
+        Poll::Ready(Ok(self.position))
+    }
+}
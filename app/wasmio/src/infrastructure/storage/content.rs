@@ -0,0 +1,216 @@
+use std::ops::Range;
+
+use base64ct::{Base64, Encoding};
+use bytes::BytesMut;
+use chrono::Utc;
+use futures::TryStreamExt;
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use super::checksum::{ChecksumHasher, ChecksumRequest, ElementChecksum};
+use super::encryption::{
+    ContentCipher, CustomerKey, ElementEncryption, MasterKey,
+};
+use super::{ElementInfo, ElementProperties};
+
+/// Errors of the content of an element, whatever the backend storing it.
+#[derive(Debug, thiserror::Error)]
+pub enum ContentError {
+    #[error("The checksum doesn't match the content")]
+    BadDigest,
+    #[error("The encryption key doesn't match the element")]
+    EncryptionKey,
+    #[error("No master key is configured to encrypt the element")]
+    EncryptionNotConfigured,
+    #[error("IO: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Writes the content of a new element, its digests are computed and it's
+/// encrypted while it's written.
+///
+/// It's created before the destination is opened, so an element which can't
+/// be encrypted doesn't replace anything.
+pub struct ContentWriter {
+    properties: ElementProperties,
+    cipher: Option<ContentCipher>,
+    encryption: Option<ElementEncryption>,
+}
+
+impl ContentWriter {
+    pub fn new(
+        properties: ElementProperties,
+        master_key: Option<&MasterKey>,
+    ) -> Result<Self, ContentError> {
+        let (cipher, encryption) = match (
+            properties.customer_key.as_ref(),
+            properties.server_side_encryption,
+        ) {
+            (Some(key), _) => {
+                let (cipher, encryption) = ContentCipher::new_customer(key);
+                (Some(cipher), Some(encryption))
+            }
+            (None, true) => {
+                let master_key =
+                    master_key.ok_or(ContentError::EncryptionNotConfigured)?;
+                let (cipher, encryption) =
+                    ContentCipher::new_managed(master_key);
+                (Some(cipher), Some(encryption))
+            }
+            (None, false) => (None, None),
+        };
+
+        Ok(Self {
+            properties,
+            cipher,
+            encryption,
+        })
+    }
+
+    /// Copy `content` into `out`, the dates of the returned element are now.
+    pub async fn write<R, W>(
+        self,
+        name: &str,
+        content: &mut R,
+        out: &mut W,
+    ) -> Result<ElementInfo, ContentError>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        let Self {
+            properties:
+                ElementProperties {
+                    metadatas,
+                    content_type,
+                    website_redirect_location,
+                    checksum,
+                    content_md5,
+                    customer_key,
+                    server_side_encryption: _,
+                },
+            mut cipher,
+            encryption,
+        } = self;
+        let now = Utc::now();
+
+        // The entity tag of an element encrypted with SSE-C is the digest of
+        // the encrypted content, to not leak anything about the content.
+        let mut encrypted_md5_hasher =
+            customer_key.as_ref().map(|_| Md5::new());
+
+        // TODO: test based on `cat public/data/test-bucket/test.txt.0.part.0 |
+        // openssl sha256 -binary | base64`
+        let mut hasher = Sha256::new();
+        let mut md5_hasher = Md5::new();
+        let mut checksum_hasher =
+            checksum.as_ref().map(|x| x.algorithm.hasher());
+
+        let stream = tokio_util::io::ReaderStream::new(content);
+        let mut ar = tokio_util::io::StreamReader::new(stream.map_ok(|x| {
+            hasher.update(&*x);
+            md5_hasher.update(&*x);
+            if let Some(checksum_hasher) = &mut checksum_hasher {
+                checksum_hasher.update(&x);
+            }
+            match &mut cipher {
+                Some(cipher) => {
+                    let mut x = BytesMut::from(&x[..]);
+                    cipher.apply(&mut x);
+                    if let Some(encrypted_md5_hasher) =
+                        &mut encrypted_md5_hasher
+                    {
+                        encrypted_md5_hasher.update(&x);
+                    }
+                    x.freeze()
+                }
+                None => x,
+            }
+        }));
+
+        let size = tokio::io::copy(&mut ar, out).await?;
+        let hash = Base64::encode_string(&hasher.finalize());
+        let md5 = md5_hasher.finalize();
+        if content_md5.is_some_and(|x| x != md5.as_slice()) {
+            return Err(ContentError::BadDigest);
+        }
+        let md5 = match encrypted_md5_hasher {
+            Some(encrypted_md5_hasher) => encrypted_md5_hasher.finalize(),
+            None => md5,
+        };
+
+        Ok(ElementInfo {
+            name: name.to_string(),
+            size,
+            created_at: now,
+            last_modified: now,
+            checksum: hash,
+            md5: hex::encode(md5),
+            metadatas,
+            content_type,
+            website_redirect_location,
+            additional_checksum: additional_checksum(
+                checksum,
+                checksum_hasher,
+            )?,
+            encryption,
+            chunks: None,
+            version: None,
+            segment: None,
+        })
+    }
+}
+
+/// The expected value can come from a trailer, it's only known once the
+/// whole content is read.
+fn additional_checksum(
+    checksum: Option<ChecksumRequest>,
+    checksum_hasher: Option<ChecksumHasher>,
+) -> Result<Option<ElementChecksum>, ContentError> {
+    match (checksum, checksum_hasher) {
+        (Some(checksum), Some(checksum_hasher)) => {
+            let value = checksum_hasher.finalize();
+            if checksum.expected.get().is_some_and(|x| x != value) {
+                return Err(ContentError::BadDigest);
+            }
+            Ok(Some(ElementChecksum {
+                algorithm: checksum.algorithm,
+                value,
+            }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// The cipher to read the content of an element, an element encrypted with
+/// SSE-C is only readable with the same `customer_key`.
+pub fn element_cipher(
+    elt: &ElementInfo,
+    customer_key: Option<&CustomerKey>,
+    master_key: Option<&MasterKey>,
+) -> Result<Option<ContentCipher>, ContentError> {
+    match &elt.encryption {
+        Some(ElementEncryption::Managed { .. }) if master_key.is_none() => {
+            Err(ContentError::EncryptionNotConfigured)
+        }
+        Some(encryption) => {
+            ContentCipher::for_element(encryption, customer_key, master_key)
+                .map(Some)
+                .ok_or(ContentError::EncryptionKey)
+        }
+        None => Ok(None),
+    }
+}
+
+/// The bytes of an element of `size` to read, `range` is clamped to the
+/// element and it's the whole element without a range.
+pub fn clamp_range(range: Option<Range<u64>>, size: u64) -> Range<u64> {
+    match range {
+        Some(range) => {
+            let start = range.start.min(size);
+            start..range.end.clamp(start, size)
+        }
+        None => 0..size,
+    }
+}
@@ -1,22 +1,89 @@
 use chrono::{DateTime, Utc};
+use wasmio_aws_types::types::{
+    LoggingEnabled, NotificationConfiguration,
+    ServerSideEncryptionConfiguration, WebsiteConfiguration,
+};
+
+use super::ChunkStats;
 
 /// List of database info available
-#[derive(Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
 pub struct DatabaseInfo {
     pub name: String,
     pub number_element: u64,
+    /// Bytes of the content of the elements.
+    #[serde(default)]
+    pub total_size: u64,
     pub created_at: DateTime<Utc>,
+    /// Configuration used when the database is served as a website.
+    #[serde(default)]
+    pub website: Option<WebsiteConfiguration>,
+    /// Default encryption of the elements inserted in the database.
+    #[serde(default)]
+    pub encryption: Option<ServerSideEncryptionConfiguration>,
+    /// Region of the database, it's not set in `us-east-1`, the default
+    /// region, which is the one of the databases created before the region
+    /// was stored.
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Destinations of the events of the database.
+    #[serde(default)]
+    pub notification: Option<NotificationConfiguration>,
+    /// Where the access logs of the database are written.
+    #[serde(default)]
+    pub logging: Option<LoggingEnabled>,
+    /// Usage of the chunks of the elements stored in chunks.
+    #[serde(default)]
+    pub chunks: ChunkStats,
 }
 
 impl DatabaseInfo {
-    pub fn new_database(name: String) -> Self {
+    pub fn new_database(name: String, region: Option<String>) -> Self {
         Self {
             name,
             number_element: 0,
+            total_size: 0,
             created_at: Utc::now(),
+            website: None,
+            encryption: None,
+            region,
+            notification: None,
+            logging: None,
+            chunks: ChunkStats::default(),
         }
     }
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// Account for an element inserted in place of `previous`.
+    pub fn insert_element(&mut self, size: u64, previous: Option<u64>) {
+        match previous {
+            Some(previous) => {
+                self.total_size =
+                    (self.total_size + size).saturating_sub(previous);
+            }
+            None => {
+                self.number_element += 1;
+                self.total_size += size;
+            }
+        }
+    }
+
+    /// Account for an element of `size` bytes deleted.
+    pub fn delete_element(&mut self, size: u64) {
+        self.number_element = self.number_element.saturating_sub(1);
+        self.total_size = self.total_size.saturating_sub(size);
+    }
+
+    /// Keep the statistics of the elements of `current`, they are only
+    /// changed with the elements.
+    pub fn with_stats_of(self, current: &DatabaseInfo) -> Self {
+        Self {
+            number_element: current.number_element,
+            total_size: current.total_size,
+            chunks: current.chunks.clone(),
+            ..self
+        }
+    }
 }
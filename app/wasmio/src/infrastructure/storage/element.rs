@@ -1,8 +1,17 @@
 use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
 
 use chrono::{DateTime, Utc};
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
 
-#[derive(Debug, serde::Serialize, serde::Deserialize, Default)]
+use super::checksum::{ChecksumRequest, ElementChecksum};
+use super::chunk::ElementChunk;
+use super::encryption::{ContentCipher, CustomerKey, ElementEncryption};
+use super::segment::ElementSegment;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 pub struct ElementInfo {
     pub name: String,
     pub size: u64,
@@ -10,5 +19,116 @@ pub struct ElementInfo {
     pub last_modified: DateTime<Utc>,
     /// Only using sha256 for now
     pub checksum: String,
+    /// The MD5 digest of the content, hex encoded.
+    #[serde(default)]
+    pub md5: String,
     pub metadatas: HashMap<String, String>,
+    #[serde(default)]
+    pub content_type: Option<String>,
+    /// Redirection used when the bucket is served as a website.
+    #[serde(default)]
+    pub website_redirect_location: Option<String>,
+    /// The additional checksum requested when the element was inserted.
+    #[serde(default)]
+    pub additional_checksum: Option<ElementChecksum>,
+    /// Set when the content is encrypted.
+    #[serde(default)]
+    pub encryption: Option<ElementEncryption>,
+    /// The chunks of the content, when it's stored in chunks.
+    #[serde(default)]
+    pub chunks: Option<Vec<ElementChunk>>,
+    /// The version of the file of the content, each insertion writes a new
+    /// file so the previous content is kept until the element is replaced.
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Where the content is in the segments, when it's stored in a segment.
+    #[serde(default)]
+    pub segment: Option<ElementSegment>,
+}
+
+impl ElementInfo {
+    /// The quoted entity tag of the element, the MD5 digest of its content.
+    /// Elements inserted before the digest was stored use their checksum.
+    pub fn e_tag(&self) -> String {
+        match self.md5.is_empty() {
+            true => format!("\"{}\"", self.checksum),
+            false => format!("\"{}\"", self.md5),
+        }
+    }
+}
+
+/// Properties given alongside the content when an element is inserted.
+#[derive(Debug, Clone, Default)]
+pub struct ElementProperties {
+    pub metadatas: HashMap<String, String>,
+    pub content_type: Option<String>,
+    pub website_redirect_location: Option<String>,
+    pub checksum: Option<ChecksumRequest>,
+    /// The MD5 digest sent in `Content-MD5`, the insertion fails when it
+    /// doesn't match the content.
+    pub content_md5: Option<Vec<u8>>,
+    /// The content is encrypted with this key (SSE-C).
+    pub customer_key: Option<CustomerKey>,
+    /// The content is encrypted with a key managed by the storage (SSE-S3).
+    pub server_side_encryption: bool,
+}
+
+/// An element opened to read its content.
+pub struct ElementContent<R> {
+    pub info: ElementInfo,
+    /// Positioned at the start of the requested range.
+    pub reader: R,
+    /// Number of bytes of the requested range.
+    pub len: u64,
+}
+
+/// Reader of the content of an element, it's decrypted while it's read when
+/// the element is encrypted.
+pub struct ContentReader<R> {
+    inner: R,
+    cipher: Option<ContentCipher>,
+}
+
+impl<R> ContentReader<R> {
+    pub fn new(inner: R, cipher: Option<ContentCipher>) -> Self {
+        Self { inner, cipher }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for ContentReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        let filled = buf.filled().len();
+        ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+        if let Some(cipher) = &mut this.cipher {
+            cipher.apply(&mut buf.filled_mut()[filled..]);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<R: AsyncSeek + Unpin> AsyncSeek for ContentReader<R> {
+    fn start_seek(
+        self: Pin<&mut Self>,
+        position: SeekFrom,
+    ) -> std::io::Result<()> {
+        Pin::new(&mut self.get_mut().inner).start_seek(position)
+    }
+
+    fn poll_complete(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<std::io::Result<u64>> {
+        let this = self.get_mut();
+        let position = ready!(Pin::new(&mut this.inner).poll_complete(cx))?;
+        // The keystream follows the position in the content.
+        if let Some(cipher) = &mut this.cipher {
+            cipher.seek(position);
+        }
+        Poll::Ready(Ok(position))
+    }
 }
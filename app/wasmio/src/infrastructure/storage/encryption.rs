@@ -0,0 +1,336 @@
+//! Encryption of the content of the elements.
+//!
+//! The content is encrypted with AES-256 in CTR mode, it keeps the size of the
+//! content and allows to decrypt it from any offset.
+//!
+//! The key is either given by the customer with each request (SSE-C) or a
+//! random data key generated for each element, stored encrypted with the
+//! master key of the server (SSE-S3).
+//!
+//! See [`Using server-side encryption with customer-provided keys`](https://docs.aws.amazon.com/AmazonS3/latest/userguide/ServerSideEncryptionCustomerKeys.html)
+//! and [`Using server-side encryption with Amazon S3 managed keys`](https://docs.aws.amazon.com/AmazonS3/latest/userguide/UsingServerSideEncryption.html)
+use std::path::Path;
+
+use aes::cipher::{KeyIvInit, StreamCipher, StreamCipherSeek};
+use aes::Aes256;
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::Aes256Gcm;
+use anyhow::Context;
+use base64ct::{Base64, Encoding};
+use md5::{Digest, Md5};
+use rand::RngCore;
+
+type Aes256Ctr = ctr::Ctr128BE<Aes256>;
+
+/// The only algorithm supported by SSE-C and SSE-S3.
+pub const AES256: &str = "AES256";
+
+const IV_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// A key provided by the customer to encrypt an element, it's never stored.
+#[derive(Clone)]
+pub struct CustomerKey {
+    key: [u8; 32],
+    /// The MD5 digest of the key, encoded in base64.
+    key_md5: String,
+}
+
+impl std::fmt::Debug for CustomerKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomerKey")
+            .field("key_md5", &self.key_md5)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CustomerKeyError {
+    #[error("The encryption algorithm is not supported")]
+    Algorithm,
+    #[error("The key is not a 256-bit key encoded in base64")]
+    Key,
+    #[error("The MD5 of the key doesn't match")]
+    KeyMd5,
+}
+
+impl CustomerKey {
+    /// Parse the key from the `x-amz-server-side-encryption-customer-*`
+    /// headers.
+    pub fn parse(
+        algorithm: &str,
+        key: &str,
+        key_md5: &str,
+    ) -> Result<Self, CustomerKeyError> {
+        if algorithm != AES256 {
+            return Err(CustomerKeyError::Algorithm);
+        }
+
+        let key: [u8; 32] = Base64::decode_vec(key)
+            .ok()
+            .and_then(|x| x.try_into().ok())
+            .ok_or(CustomerKeyError::Key)?;
+
+        let computed = Base64::encode_string(&Md5::digest(key));
+        if computed != key_md5 {
+            return Err(CustomerKeyError::KeyMd5);
+        }
+
+        Ok(Self {
+            key,
+            key_md5: computed,
+        })
+    }
+
+    pub fn key_md5(&self) -> &str {
+        &self.key_md5
+    }
+}
+
+/// The master key of the server, it only encrypts the data keys of the
+/// elements (SSE-S3).
+pub struct MasterKey(Aes256Gcm);
+
+impl std::fmt::Debug for MasterKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MasterKey").finish_non_exhaustive()
+    }
+}
+
+impl MasterKey {
+    pub fn new(key: [u8; 32]) -> Self {
+        Self(Aes256Gcm::new(&key.into()))
+    }
+
+    /// Load the master key from a file containing the key encoded in base64.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| {
+            format!("Can't read the master key file {}", path.display())
+        })?;
+        let key: [u8; 32] = Base64::decode_vec(content.trim())
+            .ok()
+            .and_then(|x| x.try_into().ok())
+            .context(
+                "The master key must be a 256-bit key encoded in base64",
+            )?;
+        Ok(Self::new(key))
+    }
+
+    /// Encrypt a data key, the nonce is prepended to the result which is
+    /// encoded in base64.
+    fn wrap(&self, data_key: &[u8; 32]) -> String {
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let encrypted = self
+            .0
+            .encrypt(&nonce.into(), data_key.as_slice())
+            .expect("Encrypting 32 bytes can't fail");
+        Base64::encode_string(&[nonce.as_slice(), &encrypted].concat())
+    }
+
+    /// Decrypt a data key, `None` when it wasn't encrypted with this key.
+    fn unwrap(&self, wrapped: &str) -> Option<[u8; 32]> {
+        let wrapped = Base64::decode_vec(wrapped).ok()?;
+        if wrapped.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, encrypted) = wrapped.split_at(NONCE_LEN);
+        let nonce: [u8; NONCE_LEN] = nonce.try_into().ok()?;
+        self.0
+            .decrypt(&nonce.into(), encrypted)
+            .ok()?
+            .try_into()
+            .ok()
+    }
+}
+
+/// How the content of an element is encrypted.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type")]
+pub enum ElementEncryption {
+    /// SSE-C, only the MD5 of the key is stored to check the key given to
+    /// read the element.
+    Customer {
+        key_md5: String,
+        /// Hex encoded.
+        iv: String,
+    },
+    /// SSE-S3, the data key of the element encrypted with the master key.
+    Managed {
+        wrapped_key: String,
+        /// Hex encoded.
+        iv: String,
+    },
+}
+
+impl ElementEncryption {
+    /// The MD5 of the customer key, when encrypted with SSE-C.
+    pub fn customer_key_md5(&self) -> Option<&str> {
+        match self {
+            Self::Customer { key_md5, .. } => Some(key_md5),
+            Self::Managed { .. } => None,
+        }
+    }
+
+    /// Whether the element is encrypted with a server-managed key.
+    pub fn is_managed(&self) -> bool {
+        matches!(self, Self::Managed { .. })
+    }
+}
+
+/// Encrypt or decrypt a content, the operation is the same both ways.
+pub struct ContentCipher(Aes256Ctr);
+
+impl ContentCipher {
+    /// Create the cipher of a new element with a random IV.
+    pub fn new_customer(key: &CustomerKey) -> (Self, ElementEncryption) {
+        let mut iv = [0u8; IV_LEN];
+        rand::thread_rng().fill_bytes(&mut iv);
+
+        let cipher = Self(Aes256Ctr::new(&key.key.into(), &iv.into()));
+        let encryption = ElementEncryption::Customer {
+            key_md5: key.key_md5.clone(),
+            iv: hex::encode(iv),
+        };
+        (cipher, encryption)
+    }
+
+    /// Create the cipher of a new element with a random data key encrypted
+    /// with the master key.
+    pub fn new_managed(master_key: &MasterKey) -> (Self, ElementEncryption) {
+        let mut data_key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut data_key);
+        let mut iv = [0u8; IV_LEN];
+        rand::thread_rng().fill_bytes(&mut iv);
+
+        let cipher = Self(Aes256Ctr::new(&data_key.into(), &iv.into()));
+        let encryption = ElementEncryption::Managed {
+            wrapped_key: master_key.wrap(&data_key),
+            iv: hex::encode(iv),
+        };
+        (cipher, encryption)
+    }
+
+    /// Create the cipher to read an element, `None` when the key doesn't
+    /// match.
+    pub fn for_element(
+        encryption: &ElementEncryption,
+        key: Option<&CustomerKey>,
+        master_key: Option<&MasterKey>,
+    ) -> Option<Self> {
+        match encryption {
+            ElementEncryption::Customer { key_md5, iv } => {
+                let key = key.filter(|x| &x.key_md5 == key_md5)?;
+                let iv: [u8; IV_LEN] = hex::decode(iv).ok()?.try_into().ok()?;
+                Some(Self(Aes256Ctr::new(&key.key.into(), &iv.into())))
+            }
+            ElementEncryption::Managed { wrapped_key, iv } => {
+                let data_key = master_key?.unwrap(wrapped_key)?;
+                let iv: [u8; IV_LEN] = hex::decode(iv).ok()?.try_into().ok()?;
+                Some(Self(Aes256Ctr::new(&data_key.into(), &iv.into())))
+            }
+        }
+    }
+
+    pub fn apply(&mut self, data: &mut [u8]) {
+        self.0.apply_keystream(data);
+    }
+
+    /// Move the cipher to an offset of the content.
+    pub fn seek(&mut self, offset: u64) {
+        self.0.seek(offset);
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), not(target_os = "wasi")))]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=";
+    const KEY_MD5: &str = "hRasmdxgYDKV3nvbahU1MA==";
+
+    #[test]
+    fn parse_customer_key() {
+        let key = CustomerKey::parse(AES256, KEY, KEY_MD5).unwrap();
+        assert_eq!(key.key_md5(), KEY_MD5);
+
+        assert_eq!(
+            CustomerKey::parse("aws:kms", KEY, KEY_MD5).unwrap_err(),
+            CustomerKeyError::Algorithm
+        );
+        assert_eq!(
+            CustomerKey::parse(AES256, "AAAA", KEY_MD5).unwrap_err(),
+            CustomerKeyError::Key
+        );
+        assert_eq!(
+            CustomerKey::parse(AES256, KEY, "AAAAAAAAAAAAAAAAAAAAAA==")
+                .unwrap_err(),
+            CustomerKeyError::KeyMd5
+        );
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let key = CustomerKey::parse(AES256, KEY, KEY_MD5).unwrap();
+        let (mut cipher, encryption) = ContentCipher::new_customer(&key);
+
+        let mut data = b"hello world".to_vec();
+        // The content can be encrypted chunk by chunk.
+        let (a, b) = data.split_at_mut(5);
+        cipher.apply(a);
+        cipher.apply(b);
+        assert_ne!(data, b"hello world");
+
+        assert!(ContentCipher::for_element(&encryption, None, None).is_none());
+        let mut cipher =
+            ContentCipher::for_element(&encryption, Some(&key), None).unwrap();
+        cipher.apply(&mut data);
+        assert_eq!(data, b"hello world");
+
+        // The content can be decrypted from any offset.
+        let mut cipher =
+            ContentCipher::for_element(&encryption, Some(&key), None).unwrap();
+        cipher.apply(&mut data);
+        cipher.seek(6);
+        cipher.apply(&mut data[6..]);
+        assert_eq!(&data[6..], b"world");
+    }
+
+    #[test]
+    fn envelope_encryption() {
+        let master_key = MasterKey::new([7; 32]);
+        let (mut cipher, encryption) = ContentCipher::new_managed(&master_key);
+        assert!(encryption.is_managed());
+        assert_eq!(encryption.customer_key_md5(), None);
+
+        let mut data = b"hello world".to_vec();
+        cipher.apply(&mut data);
+        assert_ne!(data, b"hello world");
+
+        // Only the master key which encrypted the data key can read it.
+        assert!(ContentCipher::for_element(&encryption, None, None).is_none());
+        let other = MasterKey::new([8; 32]);
+        assert!(ContentCipher::for_element(&encryption, None, Some(&other))
+            .is_none());
+
+        let mut cipher =
+            ContentCipher::for_element(&encryption, None, Some(&master_key))
+                .unwrap();
+        cipher.apply(&mut data);
+        assert_eq!(data, b"hello world");
+    }
+
+    #[test]
+    fn load_master_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("master.key");
+
+        std::fs::write(&path, format!("{KEY}\n")).unwrap();
+        assert!(MasterKey::from_file(&path).is_ok());
+
+        std::fs::write(&path, "AAAA").unwrap();
+        assert!(MasterKey::from_file(&path).is_err());
+
+        assert!(MasterKey::from_file(&dir.path().join("missing")).is_err());
+    }
+}
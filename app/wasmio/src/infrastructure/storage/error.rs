@@ -0,0 +1,22 @@
+/// What went wrong in a backend, the errors of every backend are handled
+/// the same way from it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageErrorKind {
+    /// The database already exists.
+    AlreadyExist,
+    NoDatabase,
+    NoElement,
+    /// The content doesn't match the expected checksum.
+    BadDigest,
+    /// The encryption key doesn't match the element.
+    EncryptionKey,
+    /// No master key is configured to encrypt the element.
+    EncryptionNotConfigured,
+    /// Anything else, it's specific to the backend.
+    Other,
+}
+
+/// The error of a [BackendStorage](super::BackendStorage).
+pub trait StorageError: std::error::Error + Send + Sync + 'static {
+    fn kind(&self) -> StorageErrorKind;
+}
@@ -1,46 +1,125 @@
 #![allow(dead_code)]
-use std::collections::HashMap;
-use std::io::ErrorKind;
-#[cfg(not(target_os = "wasi"))]
-use std::os::fd::AsRawFd;
-use std::path::PathBuf;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::future::Future;
+use std::io::{ErrorKind, SeekFrom};
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
 use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
 
 use axum::async_trait;
-use base64ct::{Base64, Encoding};
-use chrono::Utc;
-use futures::future::join;
-use futures::{Stream, TryStreamExt};
-#[cfg(not(target_os = "wasi"))]
-use libc::flock;
-#[cfg(not(target_os = "wasi"))]
-use libc::{LOCK_EX, LOCK_UN};
+use futures::Stream;
 use sha2::{Digest, Sha256};
 use tokio::fs::File;
-use tokio::io::{AsyncRead, AsyncWrite};
-use tracing::warn;
-
-use super::{BackendStorage, DatabaseInfo, ElementInfo};
+use tokio::io::{AsyncRead, AsyncSeek, AsyncSeekExt, ReadBuf};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use ulid::Ulid;
+
+use super::atomic::{create_dir, persist, sync_parent, write_atomic};
+use super::chunk::{remove_if_exists, ChunkReader, ChunkStats, ChunkStore};
+use super::content::{
+    clamp_range, element_cipher, ContentError, ContentWriter,
+};
+use super::encryption::{CustomerKey, MasterKey};
+use super::index::{ElementIndex, IndexError};
+use super::journal::{Journal, Operation};
+use super::lock::{FileLock, FileLocks};
+use super::segment::{SegmentReader, SegmentStore, COMPACTION_RATIO};
+use super::{
+    BackendStorage, ContentReader, DatabaseInfo, ElementContent, ElementInfo,
+    ElementProperties, StorageError, StorageErrorKind,
+};
 
 /// We have a FSStorage implemented which aims to store files inside the FS.
 ///
 /// A database is composed of multiple files:
 ///   - A Folder where every elements are going to be inside
 ///   - A `.meta` which will give us a fast path to retrieve data on a database
+///   - An `.index` in the folder with the Info about the elements (metadatas),
+///     ordered by their name, see [ElementIndex].
 ///
 /// An element is compose of multiples files:
 ///   - Files representing the content, splitted into multiple parts (right now
 ///     no multipart, so no
 ///   splitting).
-///   - A `.meta` which contain the Info about the element, (meadatas)
+///
+/// The files of an element are named by the digest of its key, in a folder
+/// by the first two characters of the digest, any key can be stored without
+/// escaping the database. The key itself is kept in the index.
+///
+/// The content of an element is written aside and synced before it's moved
+/// in place, under a new version, so an element is always either the
+/// previous or the new one, whenever the process stops.
+///
+/// When the elements are stored in chunks, their content is split into chunks
+/// stored once in the `.chunks` folder of the database, see [ChunkStore].
+///
+/// When the segments are enabled, the content of the small elements is
+/// appended to the segment files of the `.segments` folder of the database
+/// instead of a file of its own, see [SegmentStore]. The space of the
+/// elements deleted or replaced is reclaimed by [FSStorage::compact].
+///
+/// The operations changing several files are recorded in the `.journal`
+/// folder while they are done, the ones interrupted are recovered with
+/// [FSStorage::recover] when the storage starts.
 #[derive(Debug, Clone)]
 pub struct FSStorage {
     base_path: PathBuf,
+    /// Encrypts the data keys of the elements stored with SSE-S3.
+    master_key: Option<Arc<MasterKey>>,
+    /// Size of the chunks of the elements, they are stored in a single file
+    /// when it's not set.
+    chunk_size: Option<u64>,
+    /// The elements up to this size in bytes are stored in segments.
+    segment_object_size: Option<u64>,
+    /// The indexes opened, by database.
+    indexes: Arc<Mutex<HashMap<String, ElementIndex>>>,
+    /// The locks of the databases, of the elements and of the chunks.
+    locks: FileLocks,
+    journal: Journal,
 }
 
 impl FSStorage {
     pub fn new(base: PathBuf) -> Self {
-        Self { base_path: base }
+        Self {
+            master_key: None,
+            chunk_size: None,
+            segment_object_size: None,
+            indexes: Default::default(),
+            locks: FileLocks::new(),
+            journal: Journal::new(base.join(".journal")),
+            base_path: base,
+        }
+    }
+
+    /// Store the new elements in chunks of `chunk_size` bytes, the identical
+    /// chunks of a database are stored once.
+    pub fn with_chunk_size(self, chunk_size: u64) -> Self {
+        Self {
+            chunk_size: Some(chunk_size.max(1)),
+            ..self
+        }
+    }
+
+    /// Store the elements up to `object_size` bytes in segments, the
+    /// content of many elements is appended to the same file.
+    pub fn with_segments(self, object_size: u64) -> Self {
+        Self {
+            segment_object_size: Some(object_size),
+            ..self
+        }
+    }
+
+    /// Allow to encrypt the elements at rest with this master key.
+    pub fn with_master_key(self, master_key: MasterKey) -> Self {
+        Self {
+            master_key: Some(Arc::new(master_key)),
+            ..self
+        }
     }
 
     pub fn database_path(&self, db_name: &str) -> PathBuf {
@@ -55,72 +134,498 @@ impl FSStorage {
         self.base_path.join(format!("{name}.lock", name = db_name))
     }
 
-    // For now it's only one part, but later it could grow with versions too
-    pub fn file_path(&self, db_name: &str, file_name: &str) -> PathBuf {
+    pub fn database_path_index(&self, db_name: &str) -> PathBuf {
+        self.base_path.join(db_name).join(".index")
+    }
+
+    /// The content of the elements inserted before they were versioned,
+    /// named by their key. It's not set when the key would be out of the
+    /// folder of the database.
+    pub fn file_path(&self, db_name: &str, file_name: &str) -> Option<PathBuf> {
+        let name = format!("{file_name}.part");
+        Path::new(&name)
+            .components()
+            .all(|x| matches!(x, Component::Normal(_) | Component::CurDir))
+            .then(|| self.base_path.join(db_name).join(name))
+    }
+
+    /// The content of a version of an element. The files are sharded by the
+    /// first bytes of the digest of the key, like the chunks.
+    pub fn file_path_version(
+        &self,
+        db_name: &str,
+        file_name: &str,
+        version: &str,
+    ) -> PathBuf {
+        let key = file_key(file_name);
+        self.base_path
+            .join(db_name)
+            .join(&key[..2])
+            .join(format!("{key}.{version}.part"))
+    }
+
+    /// The file of the content of an element, when it's not in chunks or in
+    /// a segment.
+    pub fn element_path(
+        &self,
+        db_name: &str,
+        elt: &ElementInfo,
+    ) -> Option<PathBuf> {
+        match &elt.version {
+            Some(version) => {
+                Some(self.file_path_version(db_name, &elt.name, version))
+            }
+            None => self.file_path(db_name, &elt.name),
+        }
+    }
+
+    /// The content being uploaded, before it's moved in place or split into
+    /// chunks.
+    pub fn file_path_upload(&self, db_name: &str, file_name: &str) -> PathBuf {
         self.base_path
             .join(db_name)
-            .join(format!("{file_name}.part"))
+            .join(format!(".{}.upload", file_key(file_name)))
+    }
+
+    pub fn chunk_store(&self, db_name: &str) -> ChunkStore {
+        ChunkStore::new(
+            self.base_path.join(db_name).join(".chunks"),
+            self.locks.clone(),
+        )
+    }
+
+    pub fn segment_store(&self, db_name: &str) -> SegmentStore {
+        SegmentStore::new(self.base_path.join(db_name).join(".segments"))
     }
 
+    /// The lock of an element, named by the digest of its key.
     pub fn file_path_lock(&self, db_name: &str, file_name: &str) -> PathBuf {
         self.base_path
             .join(db_name)
-            .join(format!(".{file_name}.lock"))
+            .join(".locks")
+            .join(format!("{}.lock", file_key(file_name)))
     }
 
+    /// The metadata of an element, before they were kept in the index.
     pub fn file_meta(&self, db_name: &str, file_name: &str) -> PathBuf {
         self.base_path
             .join(db_name)
             .join(format!("{file_name}.meta"))
     }
 
-    pub async fn update_database(
+    /// The index of the elements of a database, it's opened once.
+    pub async fn element_index(
         &self,
-        db: DatabaseInfo,
+        db: &str,
+    ) -> Result<ElementIndex, <Self as BackendStorage>::Error> {
+        let mut indexes = self.indexes.lock().await;
+        if let Some(index) = indexes.get(db) {
+            return Ok(index.clone());
+        }
+
+        if tokio::fs::metadata(self.database_path(db)).await.is_err() {
+            return Err(FSError::NoDatabase);
+        }
+        let index = ElementIndex::open(self.database_path_index(db)).await?;
+        self.migrate_file_metadata(db, &index).await?;
+
+        indexes.insert(db.to_string(), index.clone());
+        Ok(index)
+    }
+
+    /// Move the `.meta` files of the elements stored before the index into
+    /// it, and their content to the files named by the digest of their key.
+    /// They are removed once they are indexed, so it's only done once even
+    /// when it's interrupted.
+    async fn migrate_file_metadata(
+        &self,
+        db: &str,
+        index: &ElementIndex,
+    ) -> Result<(), <Self as BackendStorage>::Error> {
+        let mut read_dir = tokio::fs::read_dir(self.database_path(db)).await?;
+        let mut elements = Vec::new();
+        let mut paths = Vec::new();
+        while let Some(entry) = read_dir.next_entry().await? {
+            let name = entry
+                .file_name()
+                .into_string()
+                .map_err(|_err| FSError::Weird)?;
+            if name.ends_with(".meta") {
+                let content = tokio::fs::read_to_string(entry.path()).await?;
+                elements.push(serde_json::from_str::<ElementInfo>(&content)?);
+                paths.push(entry.path());
+            }
+        }
+        if elements.is_empty() {
+            return Ok(());
+        }
+
+        // Their content is moved with the files named by the digest of the
+        // key, under a version known in advance so it's found again when the
+        // migration is interrupted.
+        let version = Ulid::nil().to_string();
+        for elt in &mut elements {
+            if elt.chunks.is_some() || elt.segment.is_some() {
+                continue;
+            }
+            let path = self.file_path_version(db, &elt.name, &version);
+            if let Some(legacy) = self.file_path(db, &elt.name) {
+                if tokio::fs::try_exists(&legacy).await? {
+                    if let Some(shard) = path.parent() {
+                        create_dir(shard).await?;
+                    }
+                    tokio::fs::rename(&legacy, &path).await?;
+                    sync_parent(&path).await?;
+                }
+            }
+            elt.version = Some(version.clone());
+        }
+
+        index.insert(elements).await?;
+        for path in paths {
+            remove_if_exists(&path).await?;
+        }
+        Ok(())
+    }
+
+    /// Run an operation recorded in the journal. It's left in the journal
+    /// when it fails unexpectedly, as if the process stopped, so what it
+    /// changed is recovered. A content which couldn't be read didn't change
+    /// anything.
+    async fn journaled<T>(
+        &self,
+        operation: Operation,
+        run: impl Future<Output = Result<T, FSError>> + Send,
+    ) -> Result<T, FSError> {
+        let entry = self.journal.begin(&operation).await?;
+        let result = run.await;
+        match &result {
+            Err(FSError::Content(_)) => self.journal.end(entry).await?,
+            Err(err) if err.kind() == StorageErrorKind::Other => {}
+            _ => self.journal.end(entry).await?,
+        }
+        result
+    }
+
+    /// Recover the operations interrupted when the process stopped, it's
+    /// done before the storage is used.
+    ///
+    /// The index of a database tells whether an operation on its elements was
+    /// done, the database is repaired to match it. A database whose creation
+    /// was interrupted is removed.
+    pub async fn recover(&self) -> Result<(), FSError> {
+        let pending = self.journal.pending().await?;
+
+        let mut databases = BTreeSet::new();
+        for (_, operation) in &pending {
+            let db = operation.database();
+            if self.database_metadata(db).await?.is_some() {
+                databases.insert(db);
+            } else if let Operation::NewDatabase { .. } = operation {
+                warn!(message = "Removing a database not created", db);
+                self.indexes.lock().await.remove(db);
+                match tokio::fs::remove_dir_all(self.database_path(db)).await {
+                    Err(err) if err.kind() != ErrorKind::NotFound => {
+                        return Err(err.into())
+                    }
+                    _ => {}
+                }
+            }
+        }
+        for db in databases {
+            warn!(message = "Repairing a database", db);
+            self.repair_database(db).await?;
+        }
+
+        for (entry, _) in pending {
+            self.journal.end(entry).await?;
+        }
+        Ok(())
+    }
+
+    /// Match the files of a database with its index: the content it doesn't
+    /// reference is removed, the references of the chunks and the statistics
+    /// of the database are computed again.
+    async fn repair_database(&self, db: &str) -> Result<(), FSError> {
+        let elements =
+            self.element_index(db).await?.list(None, None, None).await?;
+
+        let mut files = HashSet::new();
+        let mut refs = HashMap::<String, u64>::new();
+        for elt in &elements {
+            match (&elt.chunks, &elt.segment) {
+                (Some(chunks), _) => {
+                    for chunk in chunks {
+                        *refs.entry(chunk.hash.clone()).or_default() += 1;
+                    }
+                }
+                // What is not referenced in the segments is reclaimed when
+                // they are compacted.
+                (None, Some(_)) => {}
+                (None, None) => {
+                    files.extend(self.element_path(db, elt));
+                }
+            }
+        }
+
+        // The legacy files are in the folder of the database, the others in
+        // its shards.
+        let mut dirs = vec![self.database_path(db)];
+        while let Some(dir) = dirs.pop() {
+            let mut read_dir = tokio::fs::read_dir(&dir).await?;
+            while let Some(entry) = read_dir.next_entry().await? {
+                let path = entry.path();
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                if dir == self.database_path(db) && is_shard(&name) {
+                    dirs.push(path);
+                    continue;
+                }
+                let unreferenced =
+                    name.ends_with(".part") && !files.contains(&path);
+                if unreferenced || name.ends_with(".upload") {
+                    remove_if_exists(&path).await?;
+                }
+            }
+        }
+        self.chunk_store(db).rebuild(&refs).await?;
+
+        self.recompute_database_stats(db).await?;
+        Ok(())
+    }
+
+    async fn write_database(
+        &self,
+        db: &DatabaseInfo,
     ) -> Result<(), <Self as BackendStorage>::Error> {
-        tokio::fs::write(
-            self.database_path_meta(db.name()),
-            serde_json::to_string(&db)?,
+        write_atomic(
+            &self.database_path_meta(db.name()),
+            serde_json::to_string(db)?.as_bytes(),
         )
         .await?;
         Ok(())
     }
 
-    pub async fn lock_for_write_db(
+    /// Update the metadata of a database, it must be locked with
+    /// [FSStorage::lock_for_write_db] so no update is lost.
+    async fn update_database_with(
         &self,
         db: &str,
-    ) -> Result<LockGuard, <Self as BackendStorage>::Error> {
-        let path = self.database_path_lock(db);
-        let file = tokio::fs::OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(&path)
-            .await?;
-        #[cfg(not(target_os = "wasi"))]
-        unsafe {
-            flock(file.as_raw_fd(), LOCK_EX)
+        update: impl FnOnce(&mut DatabaseInfo) + Send,
+    ) -> Result<DatabaseInfo, <Self as BackendStorage>::Error> {
+        let mut db_info = self
+            .database_metadata(db)
+            .await?
+            .ok_or(FSError::NoDatabase)?;
+        update(&mut db_info);
+        self.write_database(&db_info).await?;
+        Ok(db_info)
+    }
+
+    /// Remove the content of an element which is not referenced anymore, the
+    /// chunks released are counted in `removed`.
+    async fn remove_content(
+        &self,
+        db: &str,
+        elt: &ElementInfo,
+        removed: &mut ChunkStats,
+    ) -> Result<(), <Self as BackendStorage>::Error> {
+        match (&elt.chunks, &elt.segment) {
+            (Some(chunks), _) => {
+                self.chunk_store(db).release(chunks, removed).await?
+            }
+            // Its bytes are reclaimed when its segment is compacted.
+            (None, Some(_)) => {}
+            (None, None) => {
+                if let Some(path) = self.element_path(db, elt) {
+                    remove_if_exists(&path).await?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn open_content(
+        &self,
+        db: &str,
+        elt: &ElementInfo,
+    ) -> std::io::Result<FSContent> {
+        match (&elt.chunks, &elt.segment) {
+            (Some(chunks), _) => self
+                .chunk_store(db)
+                .open(chunks)
+                .await
+                .map(FSContent::Chunks),
+            (None, Some(segment)) => self
+                .segment_store(db)
+                .open(segment)
+                .await
+                .map(FSContent::Segment),
+            (None, None) => match self.element_path(db, elt) {
+                Some(path) => File::open(path).await.map(FSContent::File),
+                None => Err(ErrorKind::NotFound.into()),
+            },
+        }
+    }
+
+    /// Move the elements of the segments of a database where at least
+    /// [COMPACTION_RATIO] of the bytes are not referenced anymore to a new
+    /// segment, and remove them. The bytes reclaimed are returned.
+    ///
+    /// The elements are moved in the index once they are copied, and the
+    /// segments are removed after: when it's interrupted, either the copies
+    /// or the previous segments are not referenced anymore and they are
+    /// removed by the next compaction.
+    pub async fn compact_segments(&self, db: &str) -> Result<u64, FSError> {
+        let store = self.segment_store(db);
+        // The elements are not changed while they are moved.
+        let _lock = self.lock_for_write_db(db).await?;
+        let segments = store.segments().await?;
+        if segments.is_empty() {
+            return Ok(0);
+        }
+        let index = self.element_index(db).await?;
+        let elements = index.list(None, None, None).await?;
+
+        let mut referenced = HashMap::<&str, u64>::new();
+        for segment in elements.iter().flat_map(|x| &x.segment) {
+            *referenced.entry(&segment.segment).or_default() += segment.size;
+        }
+        let compacted = segments
+            .iter()
+            .filter(|x| {
+                let referenced = referenced.get(x.id.as_str()).copied();
+                let unreferenced = x.size - referenced.unwrap_or(0).min(x.size);
+                unreferenced as f64 >= x.size as f64 * COMPACTION_RATIO
+            })
+            .collect::<Vec<_>>();
+        if compacted.is_empty() {
+            return Ok(0);
+        }
+
+        let mut moved = Vec::new();
+        for elt in &elements {
+            let Some(segment) = &elt.segment else {
+                continue;
+            };
+            if !compacted.iter().any(|x| x.id == segment.segment) {
+                continue;
+            }
+            let mut content = store.open(segment).await?;
+            // The first one starts a segment, so no compacted segment is
+            // appended to.
+            let segment = match moved.is_empty() {
+                true => store.append_new(&mut content).await?,
+                false => store.append(&mut content).await?,
+            };
+            moved.push(ElementInfo {
+                segment: Some(segment),
+                ..elt.clone()
+            });
+        }
+        let moved_bytes = moved
+            .iter()
+            .flat_map(|x| &x.segment)
+            .map(|x| x.size)
+            .sum::<u64>();
+        index.insert(moved).await?;
+
+        let mut reclaimed = 0;
+        for segment in compacted {
+            store.remove(&segment.id).await?;
+            reclaimed += segment.size;
+        }
+        Ok(reclaimed.saturating_sub(moved_bytes))
+    }
+
+    /// Compact the segments of every database, see
+    /// [FSStorage::compact_segments].
+    pub async fn compact(&self) -> Result<u64, FSError> {
+        let mut reclaimed = 0;
+        for db in self.database_names().await? {
+            reclaimed += self.compact_segments(&db).await?;
+        }
+        Ok(reclaimed)
+    }
+
+    /// Whether a database has segments, they are compacted even when the
+    /// segments are not enabled anymore.
+    pub async fn has_segments(&self) -> Result<bool, FSError> {
+        for db in self.database_names().await? {
+            if !self.segment_store(&db).segments().await?.is_empty() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// The databases stored, by their `.meta`.
+    async fn database_names(&self) -> Result<Vec<String>, FSError> {
+        let mut read_dir = match tokio::fs::read_dir(&self.base_path).await {
+            Ok(read_dir) => read_dir,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                return Ok(Vec::new());
+            }
+            Err(err) => return Err(err.into()),
         };
-        Ok(LockGuard { file })
+        let mut names = Vec::new();
+        while let Some(entry) = read_dir.next_entry().await? {
+            let name = entry.file_name();
+            if let Some(db) =
+                name.to_str().and_then(|x| x.strip_suffix(".meta"))
+            {
+                names.push(db.to_string());
+            }
+        }
+        Ok(names)
+    }
+
+    /// Compact the segments every `interval`, in the background. Nothing is
+    /// spawned when the segments are not enabled and none are stored.
+    ///
+    /// The task never ends, unless it panics.
+    pub async fn spawn_compaction(
+        &self,
+        interval: Duration,
+    ) -> Result<Option<JoinHandle<()>>, FSError> {
+        if self.segment_object_size.is_none() && !self.has_segments().await? {
+            return Ok(None);
+        }
+
+        let storage = self.clone();
+        Ok(Some(tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                match storage.compact().await {
+                    Ok(0) => {}
+                    Ok(reclaimed) => {
+                        info!(message = "Segments compacted", reclaimed)
+                    }
+                    Err(err) => {
+                        warn!(message = "Segments not compacted", %err)
+                    }
+                }
+            }
+        })))
+    }
+
+    pub async fn lock_for_write_db(
+        &self,
+        db: &str,
+    ) -> Result<FileLock, <Self as BackendStorage>::Error> {
+        Ok(self.locks.lock(&self.database_path_lock(db)).await?)
     }
 
     pub async fn lock_for_element(
         &self,
         db: &str,
         elt: &str,
-    ) -> Result<LockGuard, <Self as BackendStorage>::Error> {
+    ) -> Result<FileLock, <Self as BackendStorage>::Error> {
         let path = self.file_path_lock(db, elt);
-        let file = tokio::fs::OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(&path)
-            .await?;
-        #[cfg(not(target_os = "wasi"))]
-        unsafe {
-            flock(file.as_raw_fd(), LOCK_EX)
-        };
-        Ok(LockGuard { file })
+        if let Some(locks) = path.parent() {
+            tokio::fs::create_dir_all(locks).await?;
+        }
+        Ok(self.locks.lock(&path).await?)
     }
 
     pub async fn load_file_metadata(
@@ -128,29 +633,70 @@ impl FSStorage {
         db_name: &str,
         file_name: &str,
     ) -> Result<Option<ElementInfo>, <Self as BackendStorage>::Error> {
-        let metadata_path = self.file_meta(db_name, file_name);
-
-        if (tokio::fs::metadata(&metadata_path).await).is_err() {
-            return Ok(None);
+        match self.element_index(db_name).await {
+            Ok(index) => Ok(index.get(file_name).await?),
+            Err(FSError::NoDatabase) => Ok(None),
+            Err(err) => Err(err),
         }
+    }
+}
 
-        let content = tokio::fs::read_to_string(metadata_path).await?;
-        let data_info = serde_json::from_str(&content)?;
+/// The name of the files of an element, the hex encoded SHA256 digest of its
+/// key.
+///
+/// A key can't be used as it is: it can have slashes, `..` or be longer than
+/// the names of files. The key is kept in the index.
+fn file_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
 
-        Ok(Some(data_info))
-    }
+/// Whether a file of the folder of a database is a shard of its elements.
+fn is_shard(name: &str) -> bool {
+    name.len() == 2 && name.bytes().all(|x| x.is_ascii_hexdigit())
 }
 
-pub struct LockGuard {
-    file: File,
+/// The content of an element, in a single file or in chunks.
+pub enum FSContent {
+    File(File),
+    Chunks(ChunkReader),
+    Segment(SegmentReader),
 }
 
-impl Drop for LockGuard {
-    fn drop(&mut self) {
-        #[cfg(not(target_os = "wasi"))]
-        unsafe {
-            flock(self.file.as_raw_fd(), LOCK_UN)
-        };
+impl AsyncRead for FSContent {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::File(file) => Pin::new(file).poll_read(cx, buf),
+            Self::Chunks(chunks) => Pin::new(chunks).poll_read(cx, buf),
+            Self::Segment(segment) => Pin::new(segment).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncSeek for FSContent {
+    fn start_seek(
+        self: Pin<&mut Self>,
+        position: SeekFrom,
+    ) -> std::io::Result<()> {
+        match self.get_mut() {
+            Self::File(file) => Pin::new(file).start_seek(position),
+            Self::Chunks(chunks) => Pin::new(chunks).start_seek(position),
+            Self::Segment(segment) => Pin::new(segment).start_seek(position),
+        }
+    }
+
+    fn poll_complete(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<std::io::Result<u64>> {
+        match self.get_mut() {
+            Self::File(file) => Pin::new(file).poll_complete(cx),
+            Self::Chunks(chunks) => Pin::new(chunks).poll_complete(cx),
+            Self::Segment(segment) => Pin::new(segment).poll_complete(cx),
+        }
     }
 }
 
@@ -161,14 +707,61 @@ pub enum FSError {
     AlreadyExist,
     #[error("No database")]
     NoDatabase,
+    #[error("No element")]
+    NoElement,
     #[error("fallback serde: {0}")]
     Serde(#[from] serde_json::Error),
+    #[error("{0}")]
+    Index(#[from] IndexError),
     #[error("IO: {0}")]
     Other(std::io::Error),
+    /// The content of an element couldn't be read or written aside, like
+    /// when the client is disconnected.
+    #[error("Content: {0}")]
+    Content(std::io::Error),
+    #[error("The checksum doesn't match the content")]
+    BadDigest,
+    #[error("The encryption key doesn't match the element")]
+    EncryptionKey,
+    #[error("No master key is configured to encrypt the element")]
+    EncryptionNotConfigured,
     #[error("weird one, to investigate")]
     Weird,
 }
 
+impl StorageError for FSError {
+    fn kind(&self) -> StorageErrorKind {
+        match self {
+            Self::AlreadyExist => StorageErrorKind::AlreadyExist,
+            Self::NoDatabase => StorageErrorKind::NoDatabase,
+            Self::NoElement => StorageErrorKind::NoElement,
+            Self::BadDigest => StorageErrorKind::BadDigest,
+            Self::EncryptionKey => StorageErrorKind::EncryptionKey,
+            Self::EncryptionNotConfigured => {
+                StorageErrorKind::EncryptionNotConfigured
+            }
+            Self::Serde(_)
+            | Self::Index(_)
+            | Self::Other(_)
+            | Self::Content(_)
+            | Self::Weird => StorageErrorKind::Other,
+        }
+    }
+}
+
+impl From<ContentError> for FSError {
+    fn from(value: ContentError) -> Self {
+        match value {
+            ContentError::BadDigest => Self::BadDigest,
+            ContentError::EncryptionKey => Self::EncryptionKey,
+            ContentError::EncryptionNotConfigured => {
+                Self::EncryptionNotConfigured
+            }
+            ContentError::Io(err) => err.into(),
+        }
+    }
+}
+
 impl From<std::io::Error> for FSError {
     fn from(value: std::io::Error) -> Self {
         match value.kind() {
@@ -181,27 +774,29 @@ impl From<std::io::Error> for FSError {
 #[async_trait]
 impl BackendStorage for FSStorage {
     type Error = FSError;
+    type Reader = ContentReader<FSContent>;
 
     async fn new_database(
         &self,
         name: &str,
+        region: Option<String>,
     ) -> Result<DatabaseInfo, Self::Error> {
-        let new_db = DatabaseInfo::new_database(name.to_string());
-
-        let write_metadata = tokio::fs::write(
-            self.database_path_meta(new_db.name()),
-            serde_json::to_string(&new_db)?,
-        );
-        let write_dir =
-            tokio::fs::create_dir(self.database_path(new_db.name()));
-
-        let (write_metadata_task, write_dir_task) =
-            join(write_metadata, write_dir).await;
-
-        write_metadata_task?;
-        write_dir_task?;
-
-        Ok(new_db)
+        let operation = Operation::NewDatabase {
+            db: name.to_string(),
+        };
+        self.journaled(operation, async {
+            let new_db = DatabaseInfo::new_database(name.to_string(), region);
+
+            // The database only exists once its metadata are written, the
+            // folder is created first so an existing database is
+            // not replaced.
+            tokio::fs::create_dir(self.database_path(new_db.name())).await?;
+            self.element_index(new_db.name()).await?;
+            self.write_database(&new_db).await?;
+
+            Ok(new_db)
+        })
+        .await
     }
 
     async fn database_metadata(
@@ -223,63 +818,178 @@ impl BackendStorage for FSStorage {
         Ok(Some(data_info))
     }
 
+    async fn update_database(
+        &self,
+        db: DatabaseInfo,
+    ) -> Result<(), Self::Error> {
+        let name = db.name.clone();
+        let _lock = self.lock_for_write_db(&name).await?;
+        self.update_database_with(&name, |current| {
+            *current = db.with_stats_of(current)
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn recompute_database_stats(
+        &self,
+        name: &str,
+    ) -> Result<DatabaseInfo, Self::Error> {
+        // The elements are inserted and deleted while the database is locked,
+        // they don't change while they are counted.
+        let _lock = self.lock_for_write_db(name).await?;
+        let elements = self
+            .element_index(name)
+            .await?
+            .list(None, None, None)
+            .await?;
+        let chunks = ChunkStats {
+            referenced_bytes: elements
+                .iter()
+                .flat_map(|x| x.chunks.iter().flatten())
+                .map(|x| x.size)
+                .sum(),
+            stored_bytes: self.chunk_store(name).stored_bytes().await?,
+        };
+
+        self.update_database_with(name, |db_info| {
+            db_info.number_element = elements.len() as u64;
+            db_info.total_size = elements.iter().map(|x| x.size).sum();
+            db_info.chunks = chunks;
+        })
+        .await
+    }
+
     async fn insert_element_in_database<R: AsyncRead + Unpin + Send>(
         &self,
         db: &str,
         name_elt: &str,
-        metadatas: HashMap<String, String>,
+        properties: ElementProperties,
         content: &mut R,
     ) -> Result<ElementInfo, Self::Error> {
-        let now = Utc::now();
-        let _lock = self.lock_for_element(db, name_elt).await?;
-
-        if self.database_metadata(db).await?.is_none() {
-            return Err(FSError::NoDatabase);
-        }
-
-        let ressource_path = self.file_path(db, name_elt);
-        let metadata_path = self.file_meta(db, name_elt);
-
-        let mut file_content = tokio::fs::File::create(ressource_path).await?;
-
-        // TODO: test based on `cat public/data/test-bucket/test.txt.0.part.0 |
-        // openssl sha256 -binary | base64`
-        let mut hasher = Sha256::new();
-
-        let stream = tokio_util::io::ReaderStream::new(content);
-        let mut ar = tokio_util::io::StreamReader::new(stream.map_ok(|x| {
-            hasher.update(&*x);
-            x
-        }));
-
-        let size = tokio::io::copy(&mut ar, &mut file_content).await?;
-        let hash = Base64::encode_string(&hasher.finalize());
-
-        let elt = self.load_file_metadata(db, name_elt).await?;
-        let elt = ElementInfo {
+        let operation = Operation::InsertElement {
+            db: db.to_string(),
             name: name_elt.to_string(),
-            size,
-            created_at: elt.map(|x| x.created_at).unwrap_or(now),
-            last_modified: now,
-            checksum: hash,
-            metadatas,
         };
-        tokio::fs::write(metadata_path, serde_json::to_string(&elt)?).await?;
-
-        if let Some(DatabaseInfo {
-            name,
-            number_element,
-            created_at,
-        }) = self.database_metadata(db).await?
-        {
-            self.update_database(DatabaseInfo {
-                name,
-                number_element: number_element + 1,
-                created_at,
+        self.journaled(operation, async {
+            if self.database_metadata(db).await?.is_none() {
+                return Err(FSError::NoDatabase);
+            }
+
+            let _lock = self.lock_for_element(db, name_elt).await?;
+
+            let writer =
+                ContentWriter::new(properties, self.master_key.as_deref())?;
+
+            let index = self.element_index(db).await?;
+            let previous = index.get(name_elt).await?;
+
+            // The content is uploaded aside, the element is only replaced once
+            // its new content is complete and on the disk. An interrupted
+            // upload leaves the previous element as it was.
+            let upload_path = self.file_path_upload(db, name_elt);
+            let mut upload = File::create(&upload_path).await?;
+            let elt = match writer.write(name_elt, content, &mut upload).await {
+                Ok(elt) => elt,
+                Err(err) => {
+                    drop(upload);
+                    remove_if_exists(&upload_path).await?;
+                    return Err(match err {
+                        ContentError::Io(err) => FSError::Content(err),
+                        err => err.into(),
+                    });
+                }
+            };
+
+            let mut added = ChunkStats::default();
+            let mut db_lock = None;
+            let in_segment =
+                self.segment_object_size.is_some_and(|x| elt.size <= x);
+            let elt = match self.chunk_size {
+                _ if in_segment => {
+                    // Appended while the database is locked, so its segment
+                    // is not compacted before the index references it.
+                    drop(upload);
+                    let lock = self.lock_for_write_db(db).await?;
+                    let segment = match File::open(&upload_path).await {
+                        Ok(mut upload) => {
+                            self.segment_store(db).append(&mut upload).await
+                        }
+                        Err(err) => Err(err),
+                    };
+                    remove_if_exists(&upload_path).await?;
+                    db_lock = Some(lock);
+                    ElementInfo {
+                        segment: Some(segment?),
+                        ..elt
+                    }
+                }
+                Some(chunk_size) => {
+                    // The content is split once it's checked, so a rejected
+                    // element doesn't reference any chunk.
+                    drop(upload);
+                    let chunks = self
+                        .chunk_store(db)
+                        .store(&upload_path, chunk_size, &mut added)
+                        .await;
+                    remove_if_exists(&upload_path).await?;
+                    ElementInfo {
+                        chunks: Some(chunks?),
+                        ..elt
+                    }
+                }
+                None => {
+                    // A new file by version, the previous one is still read
+                    // until the index references the new one.
+                    let version = Ulid::new().to_string();
+                    let path = self.file_path_version(db, name_elt, &version);
+                    let moved = match path.parent() {
+                        Some(shard) => create_dir(shard).await,
+                        None => Ok(()),
+                    };
+                    let moved = match moved {
+                        Ok(()) => persist(upload, &upload_path, &path).await,
+                        Err(err) => Err(err),
+                    };
+                    if let Err(err) = moved {
+                        remove_if_exists(&upload_path).await?;
+                        return Err(err.into());
+                    }
+                    ElementInfo {
+                        version: Some(version),
+                        ..elt
+                    }
+                }
+            };
+
+            let elt = ElementInfo {
+                created_at: previous
+                    .as_ref()
+                    .map(|x| x.created_at)
+                    .unwrap_or(elt.created_at),
+                ..elt
+            };
+            let _db_lock = match db_lock {
+                Some(db_lock) => db_lock,
+                None => self.lock_for_write_db(db).await?,
+            };
+            index.insert(vec![elt.clone()]).await?;
+
+            // The content of the previous element is not referenced anymore.
+            let mut removed = ChunkStats::default();
+            let previous_size = previous.as_ref().map(|x| x.size);
+            if let Some(previous) = previous {
+                self.remove_content(db, &previous, &mut removed).await?;
+            }
+
+            self.update_database_with(db, |db_info| {
+                db_info.insert_element(elt.size, previous_size);
+                db_info.chunks = db_info.chunks.update(&added, &removed);
             })
             .await?;
-        }
-        Ok(elt)
+            Ok(elt)
+        })
+        .await
     }
 
     async fn get_element_metadata_in_database(
@@ -287,64 +997,77 @@ impl BackendStorage for FSStorage {
         db: &str,
         key: &str,
     ) -> Result<Option<ElementInfo>, Self::Error> {
-        let ressource_path = self.file_meta(db, key);
-
-        if let Err(err) = tokio::fs::metadata(&ressource_path).await {
-            warn!("{err:?}");
-            return Ok(None);
-        }
-
-        let content = tokio::fs::read_to_string(ressource_path).await?;
-        let data_info = serde_json::from_str(&content)?;
-        Ok(Some(data_info))
+        self.load_file_metadata(db, key).await
     }
 
-    async fn get_element_in_database<T: AsyncWrite + Send + Unpin>(
+    async fn get_element_in_database(
         &self,
         db: &str,
         key: &str,
-        mut writer: &mut T,
-    ) -> Result<u64, Self::Error> {
-        let ressource_path = self.file_path(db, key);
-        let metadata_path = self.file_meta(db, key);
+        customer_key: Option<&CustomerKey>,
+        range: Option<Range<u64>>,
+    ) -> Result<ElementContent<Self::Reader>, Self::Error> {
+        let mut elt = self
+            .load_file_metadata(db, key)
+            .await?
+            .ok_or(FSError::NoElement)?;
+
+        let mut file_content = self.open_content(db, &elt).await;
+        // Its segment was compacted since its metadata were read, it was
+        // moved to another one.
+        let not_found = |x: &std::io::Result<_>| {
+            x.as_ref().is_err_and(|x| x.kind() == ErrorKind::NotFound)
+        };
+        if elt.segment.is_some() && not_found(&file_content) {
+            if let Some(moved) = self.load_file_metadata(db, key).await? {
+                file_content = self.open_content(db, &moved).await;
+                elt = moved;
+            }
+        }
+
+        let cipher =
+            element_cipher(&elt, customer_key, self.master_key.as_deref())?;
+        let file_content = match file_content {
+            Ok(file_content) => file_content,
+            // Deleted since its metadata were read.
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                return Err(FSError::NoElement);
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let mut reader = ContentReader::new(file_content, cipher);
 
-        tokio::fs::metadata(&metadata_path).await?;
-        let mut file_content = tokio::fs::File::open(ressource_path).await?;
+        let Range { start, end } = clamp_range(range, elt.size);
+        if start > 0 {
+            reader.seek(SeekFrom::Start(start)).await?;
+        }
 
-        let size = tokio::io::copy(&mut file_content, &mut writer).await?;
-        Ok(size)
+        Ok(ElementContent {
+            info: elt,
+            reader,
+            len: end - start,
+        })
     }
 
     async fn list_element_in_database(
         &self,
         db: &str,
-        _start_after: Option<&str>,
+        prefix: Option<&str>,
+        start_after: Option<&str>,
+        limit: Option<usize>,
     ) -> Result<
         Pin<Box<dyn Stream<Item = Result<ElementInfo, Self::Error>> + Send>>,
         Self::Error,
     > {
-        let ressource_path = self.database_path(db);
-
-        // We do a read_dir for now, it would be better to instead have an index
-        // IMO
-        let mut read_dir = tokio::fs::read_dir(ressource_path).await?;
-        let a = async_stream::stream! {
-            while let Ok(Some(entry)) = read_dir.next_entry().await {
-                let name = entry
-                    .file_name()
-                    .into_string()
-                    .map_err(|_err| FSError::Weird)?;
-
-                if let Some(_name) = name.strip_suffix(".meta") {
-                    let content = tokio::fs::read_to_string(entry.path()).await?;
-                    let data_info = serde_json::from_str(&content)?;
-
-                    yield Ok(data_info);
-                }
-            }
-        };
+        let elements = self
+            .element_index(db)
+            .await?
+            .list(prefix, start_after, limit)
+            .await?;
 
-        Ok(Box::pin(a))
+        Ok(Box::pin(futures::stream::iter(
+            elements.into_iter().map(Ok),
+        )))
     }
 
     async fn delete_element_in_database(
@@ -352,31 +1075,33 @@ impl BackendStorage for FSStorage {
         db: &str,
         key: &str,
     ) -> Result<(), Self::Error> {
-        let _lock = self.lock_for_element(db, key).await?;
-        let ressource_path = self.file_path(db, key);
-        let metadata_path = self.file_meta(db, key);
-
-        let a = tokio::fs::remove_file(ressource_path);
-        let b = tokio::fs::remove_file(metadata_path);
-        let (a, b) = join(a, b).await;
-        a?;
-        b?;
-
-        if let Some(DatabaseInfo {
-            name,
-            number_element,
-            created_at,
-        }) = self.database_metadata(db).await?
-        {
-            self.update_database(DatabaseInfo {
-                name,
-                number_element: number_element - 1,
-                created_at,
+        let operation = Operation::DeleteElement {
+            db: db.to_string(),
+            name: key.to_string(),
+        };
+        self.journaled(operation, async {
+            let _lock = self.lock_for_element(db, key).await?;
+            let _db_lock = self.lock_for_write_db(db).await?;
+            let elt = self
+                .element_index(db)
+                .await?
+                .remove(key)
+                .await?
+                .ok_or(FSError::NoElement)?;
+
+            let mut removed = ChunkStats::default();
+            self.remove_content(db, &elt, &mut removed).await?;
+
+            self.update_database_with(db, |db_info| {
+                db_info.delete_element(elt.size);
+                db_info.chunks =
+                    db_info.chunks.update(&ChunkStats::default(), &removed);
             })
             .await?;
-        }
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 }
 
@@ -384,194 +1109,799 @@ impl BackendStorage for FSStorage {
 mod tests {
     use futures::StreamExt;
     use tempfile::tempdir;
+    use tokio::io::AsyncReadExt;
 
     use super::*;
+    use crate::infrastructure::storage::suite::{
+        backend_suite, interrupted, list_names,
+    };
+
+    backend_suite!({
+        let temp_dir = tempdir().expect("Failed to create temporary directory");
+        (FSStorage::new(temp_dir.path().to_path_buf()), temp_dir)
+    });
+
+    mod chunked {
+        use super::*;
+
+        // Small chunks, so the elements of the suite have many chunks.
+        backend_suite!({
+            let temp_dir =
+                tempdir().expect("Failed to create temporary directory");
+            let storage = FSStorage::new(temp_dir.path().to_path_buf())
+                .with_chunk_size(4);
+            (storage, temp_dir)
+        });
+    }
+
+    mod segmented {
+        use super::*;
+
+        // The elements of the suite are stored in segments.
+        backend_suite!({
+            let temp_dir =
+                tempdir().expect("Failed to create temporary directory");
+            let storage = FSStorage::new(temp_dir.path().to_path_buf())
+                .with_segments(4096);
+            (storage, temp_dir)
+        });
+    }
+
+    /// The chunk files of a database.
+    fn stored_chunks(storage: &FSStorage, db: &str) -> Vec<PathBuf> {
+        let dir = storage.database_path(db).join(".chunks");
+        let mut chunks = Vec::new();
+        for shard in std::fs::read_dir(dir).unwrap() {
+            let shard = shard.unwrap().path();
+            if !shard.is_dir() {
+                continue;
+            }
+            for chunk in std::fs::read_dir(shard).unwrap() {
+                let chunk = chunk.unwrap().path();
+                if chunk.extension().is_none() {
+                    chunks.push(chunk);
+                }
+            }
+        }
+        chunks
+    }
 
     #[tokio::test]
-    async fn simple_db_with_fs() {
-        let temp = tempdir().expect("Failed to create temporary directory");
-        let fs = FSStorage::new(temp.path().to_path_buf());
+    async fn test_chunks_are_deduplicated() {
+        let temp_dir = tempdir().expect("Failed to create temporary directory");
+        let storage =
+            FSStorage::new(temp_dir.path().to_path_buf()).with_chunk_size(4);
 
-        let result = fs.new_database("test_db").await;
-        assert!(result.is_ok());
+        let db_name = "test_db";
+        storage.new_database(db_name, None).await.unwrap();
+
+        for name in ["first", "second"] {
+            let elt = storage
+                .insert_element_in_database(
+                    db_name,
+                    name,
+                    Default::default(),
+                    &mut std::io::Cursor::new(b"aaaabbbbaaaacc"),
+                )
+                .await
+                .unwrap();
+            assert_eq!(elt.chunks.map(|x| x.len()), Some(4));
+        }
+        // The chunks `aaaa`, `bbbb` and `cc` are stored once.
+        assert_eq!(stored_chunks(&storage, db_name).len(), 3);
+        let db_info =
+            storage.database_metadata(db_name).await.unwrap().unwrap();
+        assert_eq!(
+            db_info.chunks,
+            ChunkStats {
+                referenced_bytes: 28,
+                stored_bytes: 10,
+            }
+        );
+        assert_eq!(db_info.chunks.dedup_ratio(), 2.8);
+        assert!(!storage.file_path(db_name, "first").unwrap().exists());
 
-        let check_metadata_info = fs.database_metadata("test_db").await;
-        assert!(check_metadata_info.is_ok());
-        let check_metadata_info = check_metadata_info.unwrap();
-        assert!(check_metadata_info.is_some());
+        // Replacing an element releases the chunks it doesn't use anymore.
+        storage
+            .insert_element_in_database(
+                db_name,
+                "second",
+                Default::default(),
+                &mut std::io::Cursor::new(b"aaaadd"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(stored_chunks(&storage, db_name).len(), 4);
+
+        storage
+            .delete_element_in_database(db_name, "first")
+            .await
+            .unwrap();
+        assert_eq!(stored_chunks(&storage, db_name).len(), 2);
+
+        storage
+            .delete_element_in_database(db_name, "second")
+            .await
+            .unwrap();
+        assert!(stored_chunks(&storage, db_name).is_empty());
+        let db_info =
+            storage.database_metadata(db_name).await.unwrap().unwrap();
+        assert_eq!(db_info.chunks, ChunkStats::default());
+        assert_eq!(db_info.chunks.dedup_ratio(), 1.0);
     }
 
     #[tokio::test]
-    async fn test_new_database() {
+    async fn test_chunked_element_replaces_a_file() {
         let temp_dir = tempdir().expect("Failed to create temporary directory");
         let storage = FSStorage::new(temp_dir.path().to_path_buf());
 
         let db_name = "test_db";
-        let db_info = storage.new_database(db_name).await.unwrap();
+        storage.new_database(db_name, None).await.unwrap();
+        let elt = storage
+            .insert_element_in_database(
+                db_name,
+                "element",
+                Default::default(),
+                &mut std::io::Cursor::new(b"in a file"),
+            )
+            .await
+            .unwrap();
+        assert!(storage.element_path(db_name, &elt).unwrap().exists());
 
-        assert_eq!(db_info.name, db_name);
-        assert_eq!(db_info.number_element, 0);
+        // The elements stored before are still readable once the chunks are
+        // enabled, and their file is removed when they are replaced.
+        let storage = storage.with_chunk_size(4);
+        let ElementContent { len, .. } = storage
+            .get_element_in_database(db_name, "element", None, None)
+            .await
+            .unwrap();
+        assert_eq!(len, 9);
+        storage
+            .insert_element_in_database(
+                db_name,
+                "element",
+                Default::default(),
+                &mut std::io::Cursor::new(b"in chunks"),
+            )
+            .await
+            .unwrap();
+        assert!(!storage.element_path(db_name, &elt).unwrap().exists());
+        assert_eq!(stored_chunks(&storage, db_name).len(), 3);
+    }
 
-        let metadata_path = temp_dir.path().join(format!("{}.meta", db_name));
-        assert!(metadata_path.exists());
+    /// The files of a database and of its shards, without the locks, by
+    /// their path in the database.
+    fn stored_files(storage: &FSStorage, db: &str) -> Vec<String> {
+        let root = storage.database_path(db);
+        let mut files = Vec::new();
+        let mut dirs = vec![root.clone()];
+        while let Some(dir) = dirs.pop() {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if !path.to_str().unwrap().ends_with(".lock") {
+                    let path = path.strip_prefix(&root).unwrap();
+                    files.push(path.to_str().unwrap().to_string());
+                }
+            }
+        }
+        files.sort();
+        files
     }
 
     #[tokio::test]
-    async fn test_database_metadata() {
+    async fn test_small_elements_in_segments() {
         let temp_dir = tempdir().expect("Failed to create temporary directory");
-        let storage = FSStorage::new(temp_dir.path().to_path_buf());
+        let storage = FSStorage::new(temp_dir.path().to_path_buf())
+            .with_segments(8)
+            .with_chunk_size(4);
 
         let db_name = "test_db";
-        let db_info = storage.new_database(db_name).await.unwrap();
+        storage.new_database(db_name, None).await.unwrap();
+        for index in 0..100 {
+            storage
+                .insert_element_in_database(
+                    db_name,
+                    &format!("element-{index}"),
+                    Default::default(),
+                    &mut std::io::Cursor::new(format!("{index:08}")),
+                )
+                .await
+                .unwrap();
+        }
+        let segments = storage.segment_store(db_name).segments().await.unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].size, 800);
+        assert_eq!(stored_files(&storage, db_name).len(), 2);
 
-        let retrieved_db_info =
-            storage.database_metadata(db_name).await.unwrap().unwrap();
-        assert_eq!(db_info, retrieved_db_info);
+        // The larger elements are not stored in segments.
+        let elt = storage
+            .insert_element_in_database(
+                db_name,
+                "large",
+                Default::default(),
+                &mut std::io::Cursor::new(b"larger than 8"),
+            )
+            .await
+            .unwrap();
+        assert!(elt.segment.is_none());
+        assert_eq!(elt.chunks.map(|x| x.len()), Some(4));
+
+        let ElementContent {
+            mut reader, len, ..
+        } = storage
+            .get_element_in_database(db_name, "element-42", None, Some(6..8))
+            .await
+            .unwrap();
+        let mut content = Vec::new();
+        (&mut reader)
+            .take(len)
+            .read_to_end(&mut content)
+            .await
+            .unwrap();
+        assert_eq!(content, b"42");
     }
 
     #[tokio::test]
-    async fn test_insert_and_get_element_in_database() {
+    async fn test_compaction_only_with_segments() {
         let temp_dir = tempdir().expect("Failed to create temporary directory");
+        let interval = Duration::from_secs(600);
         let storage = FSStorage::new(temp_dir.path().to_path_buf());
+        storage.new_database("test_db", None).await.unwrap();
+        assert!(storage.spawn_compaction(interval).await.unwrap().is_none());
+
+        let segmented = storage.clone().with_segments(8);
+        assert!(segmented
+            .spawn_compaction(interval)
+            .await
+            .unwrap()
+            .is_some());
+        segmented
+            .insert_element_in_database(
+                "test_db",
+                "element",
+                Default::default(),
+                &mut std::io::Cursor::new("content"),
+            )
+            .await
+            .unwrap();
+
+        // The segments stored are compacted once they are disabled.
+        assert!(storage.has_segments().await.unwrap());
+        assert!(storage.spawn_compaction(interval).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_compact_segments() {
+        let temp_dir = tempdir().expect("Failed to create temporary directory");
+        let storage =
+            FSStorage::new(temp_dir.path().to_path_buf()).with_segments(8);
+
+        let db_name = "test_db";
+        storage.new_database(db_name, None).await.unwrap();
+        let insert = |name: String, content: String| {
+            let storage = storage.clone();
+            async move {
+                storage
+                    .insert_element_in_database(
+                        db_name,
+                        &name,
+                        Default::default(),
+                        &mut std::io::Cursor::new(content),
+                    )
+                    .await
+                    .unwrap()
+            }
+        };
+        for index in 0..10 {
+            insert(format!("element-{index}"), format!("{index:08}")).await;
+        }
+        // Nothing to reclaim yet.
+        assert_eq!(storage.compact().await.unwrap(), 0);
+
+        for index in 0..5 {
+            storage
+                .delete_element_in_database(
+                    db_name,
+                    &format!("element-{index}"),
+                )
+                .await
+                .unwrap();
+        }
+        insert("element-5".to_string(), "replaced".to_string()).await;
+        let store = storage.segment_store(db_name);
+        assert_eq!(store.stored_bytes().await.unwrap(), 88);
+
+        // 5 of the 11 elements of the segment are referenced.
+        assert_eq!(storage.compact().await.unwrap(), 48);
+        assert_eq!(store.stored_bytes().await.unwrap(), 40);
+        assert_eq!(store.segments().await.unwrap().len(), 1);
+        let names = list_names(&storage, db_name, None, None).await;
+        assert_eq!(names.len(), 5);
+        for name in names {
+            let ElementContent {
+                mut reader,
+                len,
+                info,
+            } = storage
+                .get_element_in_database(db_name, &name, None, None)
+                .await
+                .unwrap();
+            let mut content = Vec::new();
+            (&mut reader)
+                .take(len)
+                .read_to_end(&mut content)
+                .await
+                .unwrap();
+            let expected = match name.as_str() {
+                "element-5" => "replaced".to_string(),
+                name => format!("{:08}", name[8..].parse::<u32>().unwrap()),
+            };
+            assert_eq!(content, expected.as_bytes(), "{}", info.name);
+        }
+
+        // A compaction interrupted before the index is updated leaves copies
+        // which are not referenced.
+        let copy = store
+            .append_new(&mut std::io::Cursor::new(b"12345678"))
+            .await
+            .unwrap();
+        assert_eq!(storage.compact().await.unwrap(), 8);
+        assert!(store.open(&copy).await.is_err());
+        assert_eq!(store.stored_bytes().await.unwrap(), 40);
+    }
+
+    #[tokio::test]
+    async fn test_keys_stay_in_database() {
+        let temp_dir = tempdir().expect("Failed to create temporary directory");
+        let base = temp_dir.path().join("base");
+        let storage = FSStorage::new(base.clone());
 
         let db_name = "test_db";
-        storage.new_database(db_name).await.unwrap();
+        storage.new_database(db_name, None).await.unwrap();
+        for key in ["../escape", "../../escape", "dir/../../escape"] {
+            storage
+                .insert_element_in_database(
+                    db_name,
+                    key,
+                    Default::default(),
+                    &mut std::io::Cursor::new(b"content"),
+                )
+                .await
+                .unwrap();
+        }
 
-        let element_name = "test_element";
-        let element_content = b"test_content";
+        assert!(!base.join("escape").exists());
+        assert!(!temp_dir.path().join("escape").exists());
+        let files = stored_files(&storage, db_name);
+        assert_eq!(files.len(), 4);
+        for file in &files[1..] {
+            // `{shard}/{digest}.{version}.part`
+            let (shard, name) = file.split_once('/').unwrap();
+            assert!(is_shard(shard));
+            assert!(name.starts_with(shard));
+            assert_eq!(name.split('.').next().map(str::len), Some(64));
+        }
+    }
 
-        let mut element_reader = std::io::Cursor::new(element_content);
+    #[tokio::test]
+    async fn test_interrupted_upload_leaves_no_file() {
+        let temp_dir = tempdir().expect("Failed to create temporary directory");
+        let storage = FSStorage::new(temp_dir.path().to_path_buf());
 
-        let element_info = storage
+        let db_name = "test_db";
+        storage.new_database(db_name, None).await.unwrap();
+        let elt = storage
             .insert_element_in_database(
                 db_name,
-                element_name,
+                "element",
                 Default::default(),
-                &mut element_reader,
+                &mut std::io::Cursor::new(b"previous"),
             )
             .await
             .unwrap();
+        let part = storage
+            .element_path(db_name, &elt)
+            .unwrap()
+            .strip_prefix(storage.database_path(db_name))
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(stored_files(&storage, db_name), [".index", &part]);
+
+        for name in ["element", "other"] {
+            storage
+                .insert_element_in_database(
+                    db_name,
+                    name,
+                    Default::default(),
+                    &mut interrupted(b"new"),
+                )
+                .await
+                .unwrap_err();
+        }
+        assert_eq!(stored_files(&storage, db_name), [".index", &part]);
+
+        // The process stopped while the new content was uploaded, then
+        // before it was referenced by the index.
+        std::fs::write(storage.file_path_upload(db_name, "element"), b"n")
+            .unwrap();
+        std::fs::write(
+            storage.file_path_version(db_name, "element", "crashed"),
+            b"new",
+        )
+        .unwrap();
+        let ElementContent { info, len, .. } = storage
+            .get_element_in_database(db_name, "element", None, None)
+            .await
+            .unwrap();
+        assert_eq!(info.version, elt.version);
+        assert_eq!(len, 8);
 
-        let mut retrieved_content = Vec::new();
-        let size = storage
-            .get_element_in_database(
+        // The next upload replaces what was left.
+        let new = storage
+            .insert_element_in_database(
                 db_name,
-                element_name,
-                &mut retrieved_content,
+                "element",
+                Default::default(),
+                &mut std::io::Cursor::new(b"new"),
             )
             .await
             .unwrap();
-
-        assert_eq!(size, element_content.len() as u64);
-        assert_eq!(retrieved_content, element_content);
-
-        assert_eq!(element_info.name, element_name);
-        assert_eq!(element_info.size, size);
+        assert!(!storage.element_path(db_name, &elt).unwrap().exists());
+        assert_eq!(
+            std::fs::read(storage.element_path(db_name, &new).unwrap())
+                .unwrap(),
+            b"new"
+        );
+        assert!(!storage.file_path_upload(db_name, "element").exists());
     }
 
     #[tokio::test]
-    async fn test_list_element_in_database() {
+    async fn test_recover_interrupted_insert() {
         let temp_dir = tempdir().expect("Failed to create temporary directory");
         let storage = FSStorage::new(temp_dir.path().to_path_buf());
 
         let db_name = "test_db";
-        storage.new_database(db_name).await.unwrap();
+        storage.new_database(db_name, None).await.unwrap();
+        let elt = storage
+            .insert_element_in_database(
+                db_name,
+                "element",
+                Default::default(),
+                &mut std::io::Cursor::new(b"previous"),
+            )
+            .await
+            .unwrap();
+        let files = stored_files(&storage, db_name);
 
-        let element_name = "test_element";
+        // The process stopped while an element was inserted, what was
+        // written is not referenced and the database wasn't updated.
+        storage
+            .journal
+            .begin(&Operation::InsertElement {
+                db: db_name.to_string(),
+                name: "element".to_string(),
+            })
+            .await
+            .unwrap();
+        std::fs::write(storage.file_path_upload(db_name, "element"), b"n")
+            .unwrap();
+        std::fs::write(
+            storage.file_path_version(db_name, "element", "crashed"),
+            b"new",
+        )
+        .unwrap();
+        let db_info =
+            storage.database_metadata(db_name).await.unwrap().unwrap();
+        storage
+            .update_database(DatabaseInfo {
+                number_element: 2,
+                ..db_info
+            })
+            .await
+            .unwrap();
+        drop(storage);
+
+        let storage = FSStorage::new(temp_dir.path().to_path_buf());
+        storage.recover().await.unwrap();
+        assert_eq!(stored_files(&storage, db_name), files);
+        assert!(storage.journal.pending().await.unwrap().is_empty());
+        let db_info =
+            storage.database_metadata(db_name).await.unwrap().unwrap();
+        assert_eq!(db_info.number_element, 1);
+        let ElementContent { info, len, .. } = storage
+            .get_element_in_database(db_name, "element", None, None)
+            .await
+            .unwrap();
+        assert_eq!(info.version, elt.version);
+        assert_eq!(len, 8);
+    }
+
+    #[tokio::test]
+    async fn test_recover_chunks() {
+        let temp_dir = tempdir().expect("Failed to create temporary directory");
+        let storage =
+            FSStorage::new(temp_dir.path().to_path_buf()).with_chunk_size(4);
+
+        let db_name = "test_db";
+        storage.new_database(db_name, None).await.unwrap();
         storage
             .insert_element_in_database(
                 db_name,
-                element_name,
+                "element",
                 Default::default(),
-                &mut std::io::Cursor::new(b""),
+                &mut std::io::Cursor::new(b"aaaabb"),
             )
             .await
             .unwrap();
 
-        let mut element_list_stream = storage
-            .list_element_in_database(db_name, None)
+        // The process stopped once the chunks of an element were stored,
+        // before the element was in the index.
+        storage
+            .journal
+            .begin(&Operation::InsertElement {
+                db: db_name.to_string(),
+                name: "other".to_string(),
+            })
             .await
             .unwrap();
-
-        let mut found_element = false;
-        while let Some(result) = element_list_stream.next().await {
-            match result {
-                Ok(name) => {
-                    if name.name == element_name {
-                        found_element = true;
-                        break;
-                    }
-                }
-                Err(_) => {
-                    panic!("Error occurred while listing elements.");
-                }
+        let upload_path = storage.file_path_upload(db_name, "other");
+        std::fs::write(&upload_path, b"aaaacc").unwrap();
+        storage
+            .chunk_store(db_name)
+            .store(&upload_path, 4, &mut ChunkStats::default())
+            .await
+            .unwrap();
+        assert_eq!(stored_chunks(&storage, db_name).len(), 3);
+        drop(storage);
+
+        let storage =
+            FSStorage::new(temp_dir.path().to_path_buf()).with_chunk_size(4);
+        storage.recover().await.unwrap();
+        assert_eq!(stored_chunks(&storage, db_name).len(), 2);
+        assert!(!upload_path.exists());
+        let db_info =
+            storage.database_metadata(db_name).await.unwrap().unwrap();
+        assert_eq!(
+            db_info.chunks,
+            ChunkStats {
+                referenced_bytes: 6,
+                stored_bytes: 6,
             }
-        }
+        );
 
-        assert!(found_element);
+        // The chunk `aaaa` is only referenced by the element left, it's
+        // removed with it.
+        storage
+            .delete_element_in_database(db_name, "element")
+            .await
+            .unwrap();
+        assert!(stored_chunks(&storage, db_name).is_empty());
     }
 
     #[tokio::test]
-    async fn test_delete_element_in_database() {
+    async fn test_recompute_database_stats() {
         let temp_dir = tempdir().expect("Failed to create temporary directory");
-        let storage = FSStorage::new(temp_dir.path().to_path_buf());
+        let storage =
+            FSStorage::new(temp_dir.path().to_path_buf()).with_chunk_size(4);
 
         let db_name = "test_db";
-        storage.new_database(db_name).await.unwrap();
+        storage.new_database(db_name, None).await.unwrap();
+        for name in ["first", "second"] {
+            storage
+                .insert_element_in_database(
+                    db_name,
+                    name,
+                    Default::default(),
+                    &mut std::io::Cursor::new(b"aaaabb"),
+                )
+                .await
+                .unwrap();
+        }
+        let db_info =
+            storage.database_metadata(db_name).await.unwrap().unwrap();
 
-        let element_name = "test_element";
+        // The statistics drifted, they were written by a previous version.
         storage
+            .write_database(&DatabaseInfo {
+                number_element: 7,
+                total_size: 0,
+                chunks: ChunkStats::default(),
+                ..db_info.clone()
+            })
+            .await
+            .unwrap();
+
+        let recomputed =
+            storage.recompute_database_stats(db_name).await.unwrap();
+        assert_eq!(recomputed, db_info);
+        assert_eq!(recomputed.number_element, 2);
+        assert_eq!(recomputed.total_size, 12);
+        assert_eq!(
+            recomputed.chunks,
+            ChunkStats {
+                referenced_bytes: 12,
+                stored_bytes: 6,
+            }
+        );
+    }
+
+    /// A content which fails after some bytes, like a client disconnected.
+    struct Disconnected;
+
+    impl AsyncRead for Disconnected {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            _buf: &mut ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Err(ErrorKind::ConnectionReset.into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_content_is_not_journaled() {
+        let temp_dir = tempdir().expect("Failed to create temporary directory");
+        let storage = FSStorage::new(temp_dir.path().to_path_buf());
+        let db_name = "test_db";
+        storage.new_database(db_name, None).await.unwrap();
+
+        let mut content = std::io::Cursor::new(b"partial").chain(Disconnected);
+        let result = storage
             .insert_element_in_database(
                 db_name,
-                element_name,
+                "element",
                 Default::default(),
-                &mut std::io::Cursor::new(b""),
+                &mut content,
             )
+            .await;
+        assert!(matches!(result, Err(FSError::Content(_))));
+
+        assert!(storage.journal.pending().await.unwrap().is_empty());
+        // The content uploaded aside is removed.
+        assert_eq!(stored_files(&storage, db_name), [".index"]);
+    }
+
+    #[tokio::test]
+    async fn test_recover_new_database() {
+        let temp_dir = tempdir().expect("Failed to create temporary directory");
+        let storage = FSStorage::new(temp_dir.path().to_path_buf());
+
+        // The process stopped before the metadata of the database were
+        // written.
+        storage
+            .journal
+            .begin(&Operation::NewDatabase {
+                db: "test_db".to_string(),
+            })
             .await
             .unwrap();
+        std::fs::create_dir(storage.database_path("test_db")).unwrap();
 
-        let delete_result = storage
-            .delete_element_in_database(db_name, element_name)
+        storage.recover().await.unwrap();
+        assert!(!storage.database_path("test_db").exists());
+        storage.new_database("test_db", None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_file_metadata_are_migrated() {
+        let temp_dir = tempdir().expect("Failed to create temporary directory");
+        let storage = FSStorage::new(temp_dir.path().to_path_buf());
+
+        // A database stored before the index, with a `.meta` by element.
+        let db_name = "test_db";
+        let db_info = DatabaseInfo::new_database(db_name.to_string(), None);
+        std::fs::write(
+            storage.database_path_meta(db_name),
+            serde_json::to_string(&db_info).unwrap(),
+        )
+        .unwrap();
+        std::fs::create_dir(storage.database_path(db_name)).unwrap();
+        for name in ["b", "a"] {
+            let elt = ElementInfo {
+                name: name.to_string(),
+                size: 7,
+                ..Default::default()
+            };
+            std::fs::write(
+                storage.file_meta(db_name, name),
+                serde_json::to_string(&elt).unwrap(),
+            )
+            .unwrap();
+            std::fs::write(
+                storage.file_path(db_name, name).unwrap(),
+                b"content",
+            )
+            .unwrap();
+        }
+
+        let names = storage
+            .list_element_in_database(db_name, None, None, None)
+            .await
+            .unwrap()
+            .map(|x| x.unwrap().name)
+            .collect::<Vec<_>>()
             .await;
+        assert_eq!(names, ["a", "b"]);
+        assert!(!storage.file_meta(db_name, "a").exists());
+        assert!(storage.database_path_index(db_name).exists());
+        // The content is moved with the files named by the digest of the key.
+        assert!(!storage.file_path(db_name, "a").unwrap().exists());
+        assert_eq!(stored_files(&storage, db_name).len(), 3);
+
+        let ElementContent { len, .. } = storage
+            .get_element_in_database(db_name, "b", None, None)
+            .await
+            .unwrap();
+        assert_eq!(len, 7);
+    }
 
-        assert!(delete_result.is_ok());
+    #[tokio::test]
+    async fn test_legacy_path_stays_in_database() {
+        let temp_dir = tempdir().expect("Failed to create temporary directory");
+        let storage = FSStorage::new(temp_dir.path().to_path_buf());
+        let db_name = "test_db";
+        storage.new_database(db_name, None).await.unwrap();
+
+        // An element indexed before it was versioned, its key is out of the
+        // database.
+        let name = "../outside";
+        assert!(storage.file_path(db_name, name).is_none());
+        let outside = temp_dir.path().join("outside.part");
+        std::fs::write(&outside, b"content").unwrap();
+        let elt = ElementInfo {
+            name: name.to_string(),
+            size: 7,
+            ..Default::default()
+        };
+        storage
+            .element_index(db_name)
+            .await
+            .unwrap()
+            .insert(vec![elt])
+            .await
+            .unwrap();
 
-        let mut element_list_stream = storage
-            .list_element_in_database(db_name, None)
+        assert!(storage
+            .get_element_in_database(db_name, name, None, None)
+            .await
+            .is_err());
+        storage
+            .delete_element_in_database(db_name, name)
             .await
             .unwrap();
+        assert_eq!(std::fs::read(&outside).unwrap(), b"content");
+    }
 
-        let mut found_element = false;
-        while let Some(result) = element_list_stream.next().await {
-            match result {
-                Ok(name) => {
-                    if name.name == element_name {
-                        found_element = true;
-                        break;
-                    }
-                }
-                Err(_) => {
-                    panic!("Error occurred while listing elements.");
-                }
-            }
-        }
+    #[tokio::test]
+    async fn simple_db_with_fs() {
+        let temp = tempdir().expect("Failed to create temporary directory");
+        let fs = FSStorage::new(temp.path().to_path_buf());
+
+        let result = fs.new_database("test_db", None).await;
+        assert!(result.is_ok());
 
-        assert!(!found_element);
+        let check_metadata_info = fs.database_metadata("test_db").await;
+        assert!(check_metadata_info.is_ok());
+        let check_metadata_info = check_metadata_info.unwrap();
+        assert!(check_metadata_info.is_some());
     }
 
     #[tokio::test]
-    async fn test_list_empty_database() {
+    async fn test_new_database() {
         let temp_dir = tempdir().expect("Failed to create temporary directory");
         let storage = FSStorage::new(temp_dir.path().to_path_buf());
 
-        let db_name = "empty_db";
-        storage.new_database(db_name).await.unwrap();
+        let db_name = "test_db";
+        let db_info = storage.new_database(db_name, None).await.unwrap();
 
-        let mut element_list_stream = storage
-            .list_element_in_database(db_name, None)
-            .await
-            .unwrap();
+        assert_eq!(db_info.name, db_name);
+        assert_eq!(db_info.number_element, 0);
 
-        assert!(element_list_stream.next().await.is_none());
+        let metadata_path = temp_dir.path().join(format!("{}.meta", db_name));
+        assert!(metadata_path.exists());
     }
 }
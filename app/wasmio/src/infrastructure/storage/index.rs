@@ -0,0 +1,281 @@
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use redb::{Database, ReadableTableMetadata, TableDefinition};
+
+use super::ElementInfo;
+
+/// The metadata of the elements, serialized in JSON, by element name.
+const ELEMENTS: TableDefinition<&str, &[u8]> = TableDefinition::new("elements");
+
+#[derive(Debug, thiserror::Error)]
+pub enum IndexError {
+    #[error("index: {0}")]
+    Redb(Box<redb::Error>),
+    #[error("index serde: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("index task: {0}")]
+    Task(#[from] tokio::task::JoinError),
+}
+
+macro_rules! from_redb {
+    ($($err:ty),*) => {
+        $(
+            impl From<$err> for IndexError {
+                fn from(value: $err) -> Self {
+                    Self::Redb(Box::new(value.into()))
+                }
+            }
+        )*
+    };
+}
+
+from_redb!(
+    redb::Error,
+    redb::DatabaseError,
+    redb::TransactionError,
+    redb::TableError,
+    redb::StorageError,
+    redb::CommitError
+);
+
+/// The metadata of the elements of a database, ordered by their name.
+///
+/// It's an embedded key-value store in a single file, so an element is found
+/// without opening any other file and a listing only reads the elements it
+/// returns. Its transactions are short, they are done in place.
+///
+/// The file can't be opened twice, the clones share it.
+///
+/// The transactions wait for the disk, they are run on the blocking threads
+/// so the other tasks are not stopped.
+#[derive(Debug, Clone)]
+pub struct ElementIndex {
+    db: Arc<Database>,
+}
+
+/// Run `f` on the blocking threads.
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, IndexError> + Send + 'static,
+) -> Result<T, IndexError> {
+    tokio::task::spawn_blocking(f).await?
+}
+
+impl ElementIndex {
+    /// Open the index stored at `path`, it's created when it doesn't exist.
+    pub async fn open(path: PathBuf) -> Result<Self, IndexError> {
+        blocking(move || Self::open_blocking(&path)).await
+    }
+
+    pub async fn get(
+        &self,
+        name: &str,
+    ) -> Result<Option<ElementInfo>, IndexError> {
+        let (index, name) = (self.clone(), name.to_string());
+        blocking(move || index.get_blocking(&name)).await
+    }
+
+    /// Insert or replace the metadata of the elements, all at once.
+    pub async fn insert(
+        &self,
+        elements: Vec<ElementInfo>,
+    ) -> Result<(), IndexError> {
+        let index = self.clone();
+        blocking(move || index.insert_blocking(&elements)).await
+    }
+
+    /// Remove the metadata of an element, they are returned when it existed.
+    pub async fn remove(
+        &self,
+        name: &str,
+    ) -> Result<Option<ElementInfo>, IndexError> {
+        let (index, name) = (self.clone(), name.to_string());
+        blocking(move || index.remove_blocking(&name)).await
+    }
+
+    /// The first `limit` elements whose name starts with `prefix`, after
+    /// `start_after`, in order.
+    ///
+    /// The scan starts at the first name which could match and stops at the
+    /// first one without the prefix or once `limit` elements are read, the
+    /// other elements are not read.
+    pub async fn list(
+        &self,
+        prefix: Option<&str>,
+        start_after: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<ElementInfo>, IndexError> {
+        let index = self.clone();
+        let prefix = prefix.map(str::to_string);
+        let start_after = start_after.map(str::to_string);
+        blocking(move || {
+            index.list_blocking(
+                prefix.as_deref(),
+                start_after.as_deref(),
+                limit,
+            )
+        })
+        .await
+    }
+
+    pub async fn len(&self) -> Result<u64, IndexError> {
+        let index = self.clone();
+        blocking(move || index.len_blocking()).await
+    }
+
+    fn open_blocking(path: &Path) -> Result<Self, IndexError> {
+        let db = Database::create(path)?;
+        // The table is created right away, so it can always be read.
+        let txn = db.begin_write()?;
+        txn.open_table(ELEMENTS)?;
+        txn.commit()?;
+
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    fn get_blocking(
+        &self,
+        name: &str,
+    ) -> Result<Option<ElementInfo>, IndexError> {
+        let txn = self.db.begin_read()?;
+        let table = txn.open_table(ELEMENTS)?;
+        let elt = match table.get(name)? {
+            Some(elt) => Some(serde_json::from_slice(elt.value())?),
+            None => None,
+        };
+        Ok(elt)
+    }
+
+    fn insert_blocking(
+        &self,
+        elements: &[ElementInfo],
+    ) -> Result<(), IndexError> {
+        let txn = self.db.begin_write()?;
+        {
+            let mut table = txn.open_table(ELEMENTS)?;
+            for elt in elements {
+                table.insert(
+                    elt.name.as_str(),
+                    serde_json::to_vec(elt)?.as_slice(),
+                )?;
+            }
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn remove_blocking(
+        &self,
+        name: &str,
+    ) -> Result<Option<ElementInfo>, IndexError> {
+        let txn = self.db.begin_write()?;
+        let elt = {
+            let mut table = txn.open_table(ELEMENTS)?;
+            let elt = table.remove(name)?;
+            match elt {
+                Some(elt) => Some(serde_json::from_slice(elt.value())?),
+                None => None,
+            }
+        };
+        txn.commit()?;
+        Ok(elt)
+    }
+
+    fn list_blocking(
+        &self,
+        prefix: Option<&str>,
+        start_after: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<ElementInfo>, IndexError> {
+        let prefix = prefix.unwrap_or_default();
+        let start = match start_after {
+            Some(start_after) if start_after >= prefix => {
+                Bound::Excluded(start_after)
+            }
+            _ => Bound::Included(prefix),
+        };
+
+        let txn = self.db.begin_read()?;
+        let table = txn.open_table(ELEMENTS)?;
+        let mut elements = Vec::new();
+        let entries = table
+            .range::<&str>((start, Bound::Unbounded))?
+            .take(limit.unwrap_or(usize::MAX));
+        for entry in entries {
+            let (name, elt) = entry?;
+            if !name.value().starts_with(prefix) {
+                break;
+            }
+            elements.push(serde_json::from_slice(elt.value())?);
+        }
+
+        Ok(elements)
+    }
+
+    fn len_blocking(&self) -> Result<u64, IndexError> {
+        let txn = self.db.begin_read()?;
+        let table = txn.open_table(ELEMENTS)?;
+        Ok(table.len()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_list_range() {
+        let temp_dir = tempdir().expect("Failed to create temporary directory");
+        let index = ElementIndex::open(temp_dir.path().join("index"))
+            .await
+            .unwrap();
+
+        let elements = ["b/2", "a", "b/1", "c", "b"]
+            .map(|name| ElementInfo {
+                name: name.to_string(),
+                ..Default::default()
+            })
+            .to_vec();
+        index.insert(elements).await.unwrap();
+        assert_eq!(index.len().await.unwrap(), 5);
+
+        let names = |prefix, start_after, limit| {
+            let index = index.clone();
+            async move {
+                index
+                    .list(prefix, start_after, limit)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|x| x.name)
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(
+            names(None, None, None).await,
+            ["a", "b", "b/1", "b/2", "c"]
+        );
+        assert_eq!(names(Some("b/"), None, None).await, ["b/1", "b/2"]);
+        assert_eq!(names(Some("b"), Some("b/1"), None).await, ["b/2"]);
+        assert_eq!(names(Some("b/"), Some("a"), None).await, ["b/1", "b/2"]);
+        assert!(names(Some("b"), Some("c"), None).await.is_empty());
+        assert_eq!(names(None, Some("b/2"), None).await, ["c"]);
+        assert_eq!(names(None, None, Some(2)).await, ["a", "b"]);
+        assert_eq!(names(Some("b"), Some("b"), Some(1)).await, ["b/1"]);
+        assert!(names(None, None, Some(0)).await.is_empty());
+
+        assert_eq!(
+            index.remove("b").await.unwrap().map(|x| x.name),
+            Some("b".into())
+        );
+        assert!(index.remove("b").await.unwrap().is_none());
+        assert!(index.get("b").await.unwrap().is_none());
+        assert_eq!(
+            index.get("a").await.unwrap().map(|x| x.name),
+            Some("a".into())
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use super::atomic::write_atomic;
+use super::chunk::remove_if_exists;
+
+/// An operation changing several files of a database, they can't all be
+/// changed at once.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+pub enum Operation {
+    NewDatabase { db: String },
+    InsertElement { db: String, name: String },
+    DeleteElement { db: String, name: String },
+}
+
+impl Operation {
+    pub fn database(&self) -> &str {
+        match self {
+            Self::NewDatabase { db }
+            | Self::InsertElement { db, .. }
+            | Self::DeleteElement { db, .. } => db,
+        }
+    }
+}
+
+/// The operations in progress, an operation is recorded before it changes
+/// anything and it's removed once it's done.
+///
+/// The operations left when the process stopped were interrupted, what they
+/// changed has to be recovered before the storage is used again.
+#[derive(Debug, Clone)]
+pub struct Journal {
+    dir: PathBuf,
+}
+
+/// An operation recorded in the journal.
+#[derive(Debug)]
+pub struct JournalEntry {
+    path: PathBuf,
+}
+
+impl Journal {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Record an operation before it's started.
+    pub async fn begin(
+        &self,
+        operation: &Operation,
+    ) -> std::io::Result<JournalEntry> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let path = self.dir.join(format!("{}.json", Ulid::new()));
+        write_atomic(&path, &serde_json::to_vec(operation)?).await?;
+        Ok(JournalEntry { path })
+    }
+
+    /// Remove an operation which is done.
+    pub async fn end(&self, entry: JournalEntry) -> std::io::Result<()> {
+        remove_if_exists(&entry.path).await
+    }
+
+    /// The operations which are not done, by the time they were started.
+    pub async fn pending(
+        &self,
+    ) -> std::io::Result<Vec<(JournalEntry, Operation)>> {
+        let mut read_dir = match tokio::fs::read_dir(&self.dir).await {
+            Ok(read_dir) => read_dir,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Vec::new());
+            }
+            Err(err) => return Err(err),
+        };
+
+        let mut pending = Vec::new();
+        while let Some(entry) = read_dir.next_entry().await? {
+            let path = entry.path();
+            // An operation which was being recorded, it didn't start.
+            if path.extension().is_some_and(|x| x == "tmp") {
+                remove_if_exists(&path).await?;
+                continue;
+            }
+            let operation =
+                serde_json::from_slice(&tokio::fs::read(&path).await?)?;
+            pending.push((JournalEntry { path }, operation));
+        }
+        pending.sort_by(|(a, _), (b, _)| a.path.cmp(&b.path));
+
+        Ok(pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pending_operations() {
+        let temp_dir = tempdir().expect("Failed to create temporary directory");
+        let journal = Journal::new(temp_dir.path().join(".journal"));
+        assert!(journal.pending().await.unwrap().is_empty());
+
+        let operations = [
+            Operation::NewDatabase { db: "db".into() },
+            Operation::InsertElement {
+                db: "db".into(),
+                name: "first".into(),
+            },
+            Operation::DeleteElement {
+                db: "db".into(),
+                name: "second".into(),
+            },
+        ];
+        let mut entries = Vec::new();
+        for operation in &operations {
+            entries.push(journal.begin(operation).await.unwrap());
+            // The entries are ordered by the time they were started.
+            tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+        }
+        journal.end(entries.remove(1)).await.unwrap();
+
+        let pending = journal
+            .pending()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(_, operation)| operation)
+            .collect::<Vec<_>>();
+        assert_eq!(pending, [operations[0].clone(), operations[2].clone()]);
+    }
+}
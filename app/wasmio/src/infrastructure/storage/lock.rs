@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+#[cfg(not(target_os = "wasi"))]
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[cfg(not(target_os = "wasi"))]
+use libc::{flock, LOCK_EX, LOCK_UN};
+use parking_lot::Mutex;
+use tokio::sync::OwnedMutexGuard;
+
+/// Exclusive locks on files, so the same files are not changed at the same
+/// time. The clones share the same locks.
+///
+/// The tasks of the process wait for a lock on a mutex by file, so only the
+/// task holding it takes the `flock` of the file, which excludes the other
+/// processes. The `flock` is taken on a blocking thread, a thread of the
+/// runtime never waits for another process.
+#[derive(Debug, Clone, Default)]
+pub struct FileLocks {
+    /// The mutex of each file locked or waited for, it's removed once the
+    /// file is unlocked and nothing waits for it.
+    locks: Arc<Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>>,
+}
+
+impl FileLocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lock `path`, the file is created when it doesn't exist. It's unlocked
+    /// when the [FileLock] is dropped.
+    pub async fn lock(&self, path: &Path) -> std::io::Result<FileLock> {
+        let mutex = self
+            .locks
+            .lock()
+            .entry(path.to_path_buf())
+            .or_default()
+            .clone();
+        let guard = mutex.clone().lock_owned().await;
+        let mut lock = FileLock {
+            file: None,
+            guard: Some(guard),
+            mutex,
+            path: path.to_path_buf(),
+            locks: self.clone(),
+        };
+
+        let path = path.to_path_buf();
+        lock.file =
+            Some(tokio::task::spawn_blocking(move || lock_file(&path)).await??);
+        Ok(lock)
+    }
+
+    /// Forget the mutex of `path` when only `lock` holds it.
+    fn release(&self, lock: &mut FileLock) {
+        let mut locks = self.locks.lock();
+        // Dropped while the map is locked, so it's not taken in between.
+        lock.guard = None;
+        // Held by the map and by the lock.
+        if Arc::strong_count(&lock.mutex) == 2 {
+            locks.remove(&lock.path);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.locks.lock().len()
+    }
+}
+
+fn lock_file(path: &Path) -> std::io::Result<File> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
+    #[cfg(not(target_os = "wasi"))]
+    if unsafe { flock(file.as_raw_fd(), LOCK_EX) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(file)
+}
+
+/// A file locked by [FileLocks::lock].
+pub struct FileLock {
+    /// Not set until the `flock` is taken.
+    file: Option<File>,
+    /// Released once the file is unlocked.
+    guard: Option<OwnedMutexGuard<()>>,
+    mutex: Arc<tokio::sync::Mutex<()>>,
+    path: PathBuf,
+    locks: FileLocks,
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        #[cfg(not(target_os = "wasi"))]
+        if let Some(file) = &self.file {
+            unsafe { flock(file.as_raw_fd(), LOCK_UN) };
+        }
+        self.locks.clone().release(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_lock_is_exclusive() {
+        let temp_dir = tempdir().expect("Failed to create temporary directory");
+        let path = temp_dir.path().join("db.lock");
+        let locks = FileLocks::new();
+
+        let lock = locks.lock(&path).await.unwrap();
+        // The other tasks wait without taking a thread of the runtime.
+        let waiting = tokio::spawn({
+            let locks = locks.clone();
+            let path = path.clone();
+            async move { locks.lock(&path).await.map(|_| ()) }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiting.is_finished());
+
+        // Another file is not locked.
+        locks
+            .lock(&temp_dir.path().join("other.lock"))
+            .await
+            .unwrap();
+
+        drop(lock);
+        waiting.await.unwrap().unwrap();
+        locks.lock(&path).await.unwrap();
+        // Nothing is kept for the files unlocked.
+        assert_eq!(locks.len(), 0);
+    }
+}
@@ -0,0 +1,315 @@
+use std::collections::BTreeMap;
+use std::io::{Cursor, SeekFrom};
+use std::ops::{Bound, Range};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use axum::async_trait;
+use bytes::Bytes;
+use futures::Stream;
+use parking_lot::RwLock;
+use tokio::io::{AsyncRead, AsyncSeekExt};
+
+use super::content::{
+    clamp_range, element_cipher, ContentError, ContentWriter,
+};
+use super::encryption::{CustomerKey, MasterKey};
+use super::{
+    BackendStorage, ContentReader, DatabaseInfo, ElementContent, ElementInfo,
+    ElementProperties, StorageError, StorageErrorKind,
+};
+
+/// A storage keeping everything in memory, nothing is written on the disk.
+///
+/// It's meant for the tests and the ephemeral deployments, every database is
+/// lost when the process stops. The clones share the same databases.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    databases: Arc<RwLock<BTreeMap<String, MemoryDatabase>>>,
+    /// Encrypts the data keys of the elements stored with SSE-S3.
+    master_key: Option<Arc<MasterKey>>,
+}
+
+#[derive(Debug)]
+struct MemoryDatabase {
+    info: DatabaseInfo,
+    /// Sorted by name, so they are listed in order.
+    elements: BTreeMap<String, MemoryElement>,
+}
+
+#[derive(Debug, Clone)]
+struct MemoryElement {
+    info: ElementInfo,
+    /// Encrypted when the element is encrypted.
+    content: Bytes,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow to encrypt the elements at rest with this master key.
+    pub fn with_master_key(self, master_key: MasterKey) -> Self {
+        Self {
+            master_key: Some(Arc::new(master_key)),
+            ..self
+        }
+    }
+
+    /// The content of an element as it's stored.
+    pub fn stored_content(&self, db: &str, key: &str) -> Option<Bytes> {
+        self.databases
+            .read()
+            .get(db)?
+            .elements
+            .get(key)
+            .map(|x| x.content.clone())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MemoryError {
+    #[error("Database already exist")]
+    AlreadyExist,
+    #[error("No database")]
+    NoDatabase,
+    #[error("No element")]
+    NoElement,
+    #[error("IO: {0}")]
+    Other(#[from] std::io::Error),
+    #[error("The checksum doesn't match the content")]
+    BadDigest,
+    #[error("The encryption key doesn't match the element")]
+    EncryptionKey,
+    #[error("No master key is configured to encrypt the element")]
+    EncryptionNotConfigured,
+}
+
+impl StorageError for MemoryError {
+    fn kind(&self) -> StorageErrorKind {
+        match self {
+            Self::AlreadyExist => StorageErrorKind::AlreadyExist,
+            Self::NoDatabase => StorageErrorKind::NoDatabase,
+            Self::NoElement => StorageErrorKind::NoElement,
+            Self::BadDigest => StorageErrorKind::BadDigest,
+            Self::EncryptionKey => StorageErrorKind::EncryptionKey,
+            Self::EncryptionNotConfigured => {
+                StorageErrorKind::EncryptionNotConfigured
+            }
+            Self::Other(_) => StorageErrorKind::Other,
+        }
+    }
+}
+
+impl From<ContentError> for MemoryError {
+    fn from(value: ContentError) -> Self {
+        match value {
+            ContentError::BadDigest => Self::BadDigest,
+            ContentError::EncryptionKey => Self::EncryptionKey,
+            ContentError::EncryptionNotConfigured => {
+                Self::EncryptionNotConfigured
+            }
+            ContentError::Io(err) => Self::Other(err),
+        }
+    }
+}
+
+#[async_trait]
+impl BackendStorage for MemoryStorage {
+    type Error = MemoryError;
+    type Reader = ContentReader<Cursor<Bytes>>;
+
+    async fn new_database(
+        &self,
+        name: &str,
+        region: Option<String>,
+    ) -> Result<DatabaseInfo, Self::Error> {
+        let mut databases = self.databases.write();
+        if databases.contains_key(name) {
+            return Err(MemoryError::AlreadyExist);
+        }
+
+        let new_db = DatabaseInfo::new_database(name.to_string(), region);
+        databases.insert(
+            name.to_string(),
+            MemoryDatabase {
+                info: new_db.clone(),
+                elements: BTreeMap::new(),
+            },
+        );
+        Ok(new_db)
+    }
+
+    async fn database_metadata(
+        &self,
+        name: &str,
+    ) -> Result<Option<DatabaseInfo>, Self::Error> {
+        Ok(self.databases.read().get(name).map(|x| x.info.clone()))
+    }
+
+    async fn update_database(
+        &self,
+        db: DatabaseInfo,
+    ) -> Result<(), Self::Error> {
+        let mut databases = self.databases.write();
+        let database = databases
+            .get_mut(db.name())
+            .ok_or(MemoryError::NoDatabase)?;
+        database.info = db.with_stats_of(&database.info);
+        Ok(())
+    }
+
+    async fn recompute_database_stats(
+        &self,
+        name: &str,
+    ) -> Result<DatabaseInfo, Self::Error> {
+        let mut databases = self.databases.write();
+        let database =
+            databases.get_mut(name).ok_or(MemoryError::NoDatabase)?;
+        database.info.number_element = database.elements.len() as u64;
+        database.info.total_size =
+            database.elements.values().map(|x| x.info.size).sum();
+        Ok(database.info.clone())
+    }
+
+    async fn list_element_in_database(
+        &self,
+        db: &str,
+        prefix: Option<&str>,
+        start_after: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<ElementInfo, Self::Error>> + Send>>,
+        Self::Error,
+    > {
+        let databases = self.databases.read();
+        let database = databases.get(db).ok_or(MemoryError::NoDatabase)?;
+
+        let prefix = prefix.unwrap_or_default();
+        let start = match start_after {
+            Some(start_after) if start_after >= prefix => {
+                Bound::Excluded(start_after)
+            }
+            _ => Bound::Included(prefix),
+        };
+        // A snapshot, the database is not locked while it's listed.
+        let elements = database
+            .elements
+            .range::<str, _>((start, Bound::Unbounded))
+            .take_while(|(name, _)| name.starts_with(prefix))
+            .take(limit.unwrap_or(usize::MAX))
+            .map(|(_, elt)| Ok(elt.info.clone()))
+            .collect::<Vec<_>>();
+
+        Ok(Box::pin(futures::stream::iter(elements)))
+    }
+
+    async fn get_element_in_database(
+        &self,
+        db: &str,
+        key: &str,
+        customer_key: Option<&CustomerKey>,
+        range: Option<Range<u64>>,
+    ) -> Result<ElementContent<Self::Reader>, Self::Error> {
+        let MemoryElement { info, content } = self
+            .databases
+            .read()
+            .get(db)
+            .and_then(|x| x.elements.get(key))
+            .cloned()
+            .ok_or(MemoryError::NoElement)?;
+
+        let cipher =
+            element_cipher(&info, customer_key, self.master_key.as_deref())?;
+        let mut reader = ContentReader::new(Cursor::new(content), cipher);
+
+        let Range { start, end } = clamp_range(range, info.size);
+        if start > 0 {
+            reader.seek(SeekFrom::Start(start)).await?;
+        }
+
+        Ok(ElementContent {
+            info,
+            reader,
+            len: end - start,
+        })
+    }
+
+    async fn get_element_metadata_in_database(
+        &self,
+        db: &str,
+        key: &str,
+    ) -> Result<Option<ElementInfo>, Self::Error> {
+        Ok(self
+            .databases
+            .read()
+            .get(db)
+            .and_then(|x| x.elements.get(key))
+            .map(|x| x.info.clone()))
+    }
+
+    async fn insert_element_in_database<R: AsyncRead + Unpin + Send>(
+        &self,
+        db: &str,
+        name_elt: &str,
+        properties: ElementProperties,
+        content: &mut R,
+    ) -> Result<ElementInfo, Self::Error> {
+        if !self.databases.read().contains_key(db) {
+            return Err(MemoryError::NoDatabase);
+        }
+
+        let writer =
+            ContentWriter::new(properties, self.master_key.as_deref())?;
+        let mut stored = Vec::new();
+        let elt = writer.write(name_elt, content, &mut stored).await?;
+
+        // The element is replaced at once, like the element lock of
+        // `FSStorage` the last insertion wins.
+        let mut databases = self.databases.write();
+        let database = databases.get_mut(db).ok_or(MemoryError::NoDatabase)?;
+        let previous = database.elements.get(name_elt).map(|x| &x.info);
+        let elt = ElementInfo {
+            created_at: previous
+                .map(|x| x.created_at)
+                .unwrap_or(elt.created_at),
+            ..elt
+        };
+        let previous_size = previous.map(|x| x.size);
+        database.elements.insert(
+            name_elt.to_string(),
+            MemoryElement {
+                info: elt.clone(),
+                content: stored.into(),
+            },
+        );
+        database.info.insert_element(elt.size, previous_size);
+
+        Ok(elt)
+    }
+
+    async fn delete_element_in_database(
+        &self,
+        db: &str,
+        name_elt: &str,
+    ) -> Result<(), Self::Error> {
+        let mut databases = self.databases.write();
+        let database = databases.get_mut(db).ok_or(MemoryError::NoDatabase)?;
+        let elt = database
+            .elements
+            .remove(name_elt)
+            .ok_or(MemoryError::NoElement)?;
+        database.info.delete_element(elt.info.size);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::storage::suite::backend_suite;
+
+    backend_suite!((MemoryStorage::new(), ()));
+}
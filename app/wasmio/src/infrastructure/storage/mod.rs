@@ -1,19 +1,42 @@
 #![allow(dead_code)]
-use std::collections::HashMap;
+use std::ops::Range;
 use std::pin::Pin;
 
 use axum::async_trait;
 use futures::Stream;
-use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::io::{AsyncRead, AsyncSeek};
+
+mod error;
+pub use error::{StorageError, StorageErrorKind};
 
 mod fs_storage;
-pub use fs_storage::{FSError, FSStorage};
+pub use fs_storage::FSStorage;
+
+mod memory_storage;
+pub use memory_storage::MemoryStorage;
+
+#[cfg(test)]
+mod suite;
+
+mod atomic;
+pub mod checksum;
+mod chunk;
+pub use chunk::ChunkStats;
+mod content;
+pub mod encryption;
+mod index;
+mod journal;
+mod lock;
+mod segment;
+use encryption::CustomerKey;
 
 mod database;
 pub use database::DatabaseInfo;
 
 mod element;
-pub use element::ElementInfo;
+pub use element::{
+    ContentReader, ElementContent, ElementInfo, ElementProperties,
+};
 
 /// Implement this trait which define the backend storage used to store data
 ///
@@ -27,12 +50,15 @@ pub use element::ElementInfo;
 ///   metadata.
 #[async_trait]
 pub trait BackendStorage: Send + Sync {
-    type Error: std::error::Error + Send + Sync;
+    type Error: StorageError;
+    /// Reader of the content of an element.
+    type Reader: AsyncRead + AsyncSeek + Send + Unpin + 'static;
 
-    /// To create a new database
+    /// To create a new database, in `region` when it's not the default one.
     async fn new_database(
         &self,
         name: &str,
+        region: Option<String>,
     ) -> Result<DatabaseInfo, Self::Error>;
 
     /// To get database metadata, if None, database doesn't exist
@@ -41,23 +67,48 @@ pub trait BackendStorage: Send + Sync {
         name: &str,
     ) -> Result<Option<DatabaseInfo>, Self::Error>;
 
-    /// List elements from the database,
+    /// To replace the database metadata, the statistics of its elements are
+    /// kept as they are only changed with the elements.
+    async fn update_database(
+        &self,
+        db: DatabaseInfo,
+    ) -> Result<(), Self::Error>;
+
+    /// Compute the statistics of the elements of the database again, from
+    /// the elements stored.
+    async fn recompute_database_stats(
+        &self,
+        name: &str,
+    ) -> Result<DatabaseInfo, Self::Error>;
+
+    /// List elements from the database, in the order of their name.
+    ///
+    /// Only the elements whose name starts with `prefix` are listed, from the
+    /// first one after `start_after`, at most `limit` of them.
     async fn list_element_in_database(
         &self,
         db: &str,
+        prefix: Option<&str>,
         start_after: Option<&str>,
+        limit: Option<usize>,
     ) -> Result<
         Pin<Box<dyn Stream<Item = Result<ElementInfo, Self::Error>> + Send>>,
         Self::Error,
     >;
 
-    /// Get element from the database,
-    async fn get_element_in_database<T: AsyncWrite + Send + Unpin>(
+    /// Open an element of the database to read its content, so the errors
+    /// are known before anything is read. An element encrypted with SSE-C
+    /// is only readable with the same `customer_key`.
+    ///
+    /// The content is limited to `range` when it's set, the range is clamped
+    /// to the size of the element.
+    async fn get_element_in_database(
         &self,
         db: &str,
         key: &str,
-        writer: &mut T,
-    ) -> Result<u64, Self::Error>;
+        customer_key: Option<&CustomerKey>,
+        range: Option<Range<u64>>,
+    ) -> Result<ElementContent<Self::Reader>, Self::Error>;
 
     /// Get element from the database,
     async fn get_element_metadata_in_database(
@@ -71,7 +122,7 @@ pub trait BackendStorage: Send + Sync {
         &self,
         db: &str,
         name_elt: &str,
-        metadatas: HashMap<String, String>,
+        properties: ElementProperties,
         content: &mut R,
     ) -> Result<ElementInfo, Self::Error>;
 
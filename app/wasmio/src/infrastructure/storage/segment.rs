@@ -0,0 +1,312 @@
+use std::io::{ErrorKind, SeekFrom};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use serde::{Deserialize, Serialize};
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncSeek, AsyncSeekExt, ReadBuf};
+use ulid::Ulid;
+
+use super::atomic::{create_dir, sync_parent};
+use super::chunk::remove_if_exists;
+
+/// A new segment is started once the last one reaches this size in bytes.
+pub const SEGMENT_SIZE: u64 = 64 * 1024 * 1024;
+
+/// A segment is compacted once at least this part of its bytes is not
+/// referenced anymore.
+pub const COMPACTION_RATIO: f64 = 0.5;
+
+/// Where the content of an element is stored in a segment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ElementSegment {
+    /// The identifier of the segment file.
+    pub segment: String,
+    pub offset: u64,
+    pub size: u64,
+}
+
+/// A segment file and its size in bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub id: String,
+    pub size: u64,
+}
+
+/// The segments of a database, large files where the content of the small
+/// elements is appended one after the other, so an element doesn't need a
+/// file of its own.
+///
+/// A segment is never rewritten: the content of the elements deleted or
+/// replaced stays in it until the segment is compacted, its referenced
+/// content is then appended to a new segment and it's removed.
+///
+/// The segments are only appended to while the database is locked.
+#[derive(Debug, Clone)]
+pub struct SegmentStore {
+    dir: PathBuf,
+    segment_size: u64,
+}
+
+impl SegmentStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            segment_size: SEGMENT_SIZE,
+        }
+    }
+
+    /// Start a new segment once the last one reaches `segment_size` bytes.
+    pub fn with_segment_size(self, segment_size: u64) -> Self {
+        Self {
+            segment_size: segment_size.max(1),
+            ..self
+        }
+    }
+
+    /// The segments are named by a ULID, they are ordered by the time they
+    /// were started.
+    pub fn segment_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.seg"))
+    }
+
+    /// The segments stored, from the oldest to the last one.
+    pub async fn segments(&self) -> std::io::Result<Vec<Segment>> {
+        let mut read_dir = match tokio::fs::read_dir(&self.dir).await {
+            Ok(read_dir) => read_dir,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                return Ok(Vec::new());
+            }
+            Err(err) => return Err(err),
+        };
+
+        let mut segments = Vec::new();
+        while let Some(entry) = read_dir.next_entry().await? {
+            let name = entry.file_name();
+            let Some(id) = name.to_str().and_then(|x| x.strip_suffix(".seg"))
+            else {
+                continue;
+            };
+            segments.push(Segment {
+                id: id.to_string(),
+                size: entry.metadata().await?.len(),
+            });
+        }
+        segments.sort_by(|a, b| a.id.cmp(&b.id));
+
+        Ok(segments)
+    }
+
+    /// Append `content` to the last segment, a new one is started when it's
+    /// full. The content is on the disk once it returns.
+    pub async fn append<R: AsyncRead + Unpin>(
+        &self,
+        content: &mut R,
+    ) -> std::io::Result<ElementSegment> {
+        let last = self
+            .segments()
+            .await?
+            .pop()
+            .filter(|x| x.size < self.segment_size);
+        let id = match last {
+            Some(last) => last.id,
+            None => Ulid::new().to_string(),
+        };
+        self.append_to(&id, content).await
+    }
+
+    /// Append `content` to a new segment.
+    pub async fn append_new<R: AsyncRead + Unpin>(
+        &self,
+        content: &mut R,
+    ) -> std::io::Result<ElementSegment> {
+        self.append_to(&Ulid::new().to_string(), content).await
+    }
+
+    async fn append_to<R: AsyncRead + Unpin>(
+        &self,
+        id: &str,
+        content: &mut R,
+    ) -> std::io::Result<ElementSegment> {
+        create_dir(&self.dir).await?;
+        let path = self.segment_path(id);
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        // What an interrupted append wrote is left at the end, it's never
+        // referenced.
+        let offset = file.seek(SeekFrom::End(0)).await?;
+        let size = tokio::io::copy(content, &mut file).await?;
+        file.sync_data().await?;
+        if offset == 0 {
+            sync_parent(&path).await?;
+        }
+
+        Ok(ElementSegment {
+            segment: id.to_string(),
+            offset,
+            size,
+        })
+    }
+
+    pub async fn remove(&self, id: &str) -> std::io::Result<()> {
+        remove_if_exists(&self.segment_path(id)).await
+    }
+
+    /// Bytes of the segments stored.
+    pub async fn stored_bytes(&self) -> std::io::Result<u64> {
+        Ok(self.segments().await?.iter().map(|x| x.size).sum())
+    }
+
+    /// Open the content of an element stored in a segment.
+    pub async fn open(
+        &self,
+        location: &ElementSegment,
+    ) -> std::io::Result<SegmentReader> {
+        let mut file = File::open(self.segment_path(&location.segment)).await?;
+        file.seek(SeekFrom::Start(location.offset)).await?;
+
+        Ok(SegmentReader {
+            file,
+            offset: location.offset,
+            size: location.size,
+            position: 0,
+        })
+    }
+}
+
+/// Reads the content of an element in its segment, by its offset and size.
+pub struct SegmentReader {
+    file: File,
+    /// Offset of the content in the segment.
+    offset: u64,
+    size: u64,
+    /// Position in the content.
+    position: u64,
+}
+
+impl AsyncRead for SegmentReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        let remaining = this.size.saturating_sub(this.position);
+        if remaining == 0 {
+            return Poll::Ready(Ok(()));
+        }
+
+        // The next content of the segment is not read.
+        let max = remaining.min(buf.remaining() as u64) as usize;
+        let mut limited = buf.take(max);
+        ready!(Pin::new(&mut this.file).poll_read(cx, &mut limited))?;
+        let read = limited.filled().len();
+        if read == 0 && max > 0 {
+            return Poll::Ready(Err(ErrorKind::UnexpectedEof.into()));
+        }
+
+        // SAFETY: the `read` bytes were filled through `limited`, which
+        // shares the buffer of `buf`.
+        unsafe { buf.assume_init(read) };
+        buf.advance(read);
+        this.position += read as u64;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncSeek for SegmentReader {
+    fn start_seek(
+        self: Pin<&mut Self>,
+        position: SeekFrom,
+    ) -> std::io::Result<()> {
+        let this = self.get_mut();
+        let position = match position {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::Current(offset) => {
+                this.position.checked_add_signed(offset)
+            }
+            SeekFrom::End(offset) => this.size.checked_add_signed(offset),
+        }
+        .ok_or_else(|| {
+            std::io::Error::new(ErrorKind::InvalidInput, "invalid seek")
+        })?;
+
+        Pin::new(&mut this.file)
+            .start_seek(SeekFrom::Start(this.offset + position))
+    }
+
+    fn poll_complete(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<std::io::Result<u64>> {
+        let this = self.get_mut();
+        let position = ready!(Pin::new(&mut this.file).poll_complete(cx))?;
+        this.position = position - this.offset;
+        Poll::Ready(Ok(this.position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+    use tokio::io::AsyncReadExt;
+
+    use super::*;
+
+    async fn read(reader: &mut SegmentReader) -> Vec<u8> {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).await.unwrap();
+        content
+    }
+
+    #[tokio::test]
+    async fn test_append_and_read() {
+        let temp_dir = tempdir().expect("Failed to create temporary directory");
+        let store = SegmentStore::new(temp_dir.path().join(".segments"))
+            .with_segment_size(8);
+
+        let mut locations = Vec::new();
+        for content in [&b"first"[..], b"two", b"third"] {
+            locations.push(
+                store
+                    .append(&mut std::io::Cursor::new(content))
+                    .await
+                    .unwrap(),
+            );
+        }
+        // The first segment is full once `two` is appended.
+        let segments = store.segments().await.unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].size, 8);
+        assert_eq!(store.stored_bytes().await.unwrap(), 13);
+        assert_eq!(
+            locations[1],
+            ElementSegment {
+                segment: segments[0].id.clone(),
+                offset: 5,
+                size: 3,
+            }
+        );
+
+        let mut reader = store.open(&locations[0]).await.unwrap();
+        assert_eq!(read(&mut reader).await, b"first");
+        let mut reader = store.open(&locations[1]).await.unwrap();
+        assert_eq!(read(&mut reader).await, b"two");
+
+        // A range of the content, by its offset in the segment.
+        let mut reader = store.open(&locations[0]).await.unwrap();
+        assert_eq!(reader.seek(SeekFrom::Start(2)).await.unwrap(), 2);
+        assert_eq!(read(&mut reader).await, b"rst");
+        assert_eq!(reader.seek(SeekFrom::End(-1)).await.unwrap(), 4);
+        assert_eq!(read(&mut reader).await, b"t");
+
+        store.remove(&segments[0].id).await.unwrap();
+        assert!(store.open(&locations[0]).await.is_err());
+        let mut reader = store.open(&locations[2]).await.unwrap();
+        assert_eq!(read(&mut reader).await, b"third");
+    }
+}
@@ -0,0 +1,780 @@
+//! Tests shared by the backends, every backend must pass them with the same
+//! results. They are run in a backend with [backend_suite].
+use std::ops::Range;
+
+use axum::async_trait;
+use futures::StreamExt;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use super::checksum::{
+    ChecksumAlgorithm, ChecksumRequest, ElementChecksum, ExpectedChecksum,
+};
+use super::encryption::{CustomerKey, MasterKey, AES256};
+use super::{
+    BackendStorage, DatabaseInfo, ElementContent, ElementProperties, FSStorage,
+    MemoryStorage,
+};
+use crate::domain::storage::errors::BucketStorageError;
+
+/// A backend tested by the suite.
+#[async_trait]
+pub trait TestBackend: BackendStorage + Clone
+where
+    BucketStorageError: From<Self::Error>,
+{
+    fn with_master_key(self, master_key: MasterKey) -> Self;
+
+    /// The content of an element as it's stored.
+    async fn stored_content(&self, db: &str, key: &str) -> Vec<u8>;
+}
+
+#[async_trait]
+impl TestBackend for FSStorage {
+    fn with_master_key(self, master_key: MasterKey) -> Self {
+        FSStorage::with_master_key(self, master_key)
+    }
+
+    async fn stored_content(&self, db: &str, key: &str) -> Vec<u8> {
+        let elt = self.load_file_metadata(db, key).await.unwrap().unwrap();
+        if let Some(segment) = &elt.segment {
+            let mut reader =
+                self.segment_store(db).open(segment).await.unwrap();
+            let mut content = Vec::new();
+            reader.read_to_end(&mut content).await.unwrap();
+            return content;
+        }
+        match elt.chunks {
+            Some(chunks) => {
+                let mut content = Vec::new();
+                for chunk in chunks {
+                    let path = self.chunk_store(db).chunk_path(&chunk.hash);
+                    content.extend(tokio::fs::read(path).await.unwrap());
+                }
+                content
+            }
+            None => tokio::fs::read(self.element_path(db, &elt).unwrap())
+                .await
+                .unwrap(),
+        }
+    }
+}
+
+#[async_trait]
+impl TestBackend for MemoryStorage {
+    fn with_master_key(self, master_key: MasterKey) -> Self {
+        MemoryStorage::with_master_key(self, master_key)
+    }
+
+    async fn stored_content(&self, db: &str, key: &str) -> Vec<u8> {
+        MemoryStorage::stored_content(self, db, key)
+            .unwrap()
+            .to_vec()
+    }
+}
+
+/// Run the suite with the storage given by `$new`, alongside what must live
+/// as long as the storage.
+macro_rules! backend_suite {
+    ($new:expr) => {
+        mod suite {
+            use super::*;
+
+            backend_suite!(
+                $new;
+                new_database,
+                insert_and_get_element,
+                interrupted_insert_keeps_element,
+                insert_element_with_checksum,
+                insert_element_with_content_md5,
+                insert_and_get_encrypted_element,
+                insert_and_get_managed_encrypted_element,
+                list_element,
+                list_element_in_order,
+                any_key,
+                delete_element,
+                database_stats,
+                list_empty_database,
+            );
+        }
+    };
+    ($new:expr; $($name:ident),* $(,)?) => {
+        $(
+            #[tokio::test]
+            async fn $name() {
+                let (storage, _guard) = $new;
+                $crate::infrastructure::storage::suite::$name(storage).await;
+            }
+        )*
+    };
+}
+pub(crate) use backend_suite;
+
+/// Read the content of an element, limited to `range`.
+async fn read_element<S: TestBackend>(
+    storage: &S,
+    db: &str,
+    key: &str,
+    customer_key: Option<&CustomerKey>,
+    range: Option<Range<u64>>,
+) -> Result<Vec<u8>, BucketStorageError>
+where
+    BucketStorageError: From<S::Error>,
+{
+    let ElementContent { reader, len, .. } = storage
+        .get_element_in_database(db, key, customer_key, range)
+        .await?;
+    let mut content = Vec::new();
+    reader
+        .take(len)
+        .read_to_end(&mut content)
+        .await
+        .map_err(|_| BucketStorageError::Unknown)?;
+    Ok(content)
+}
+
+/// A content whose upload is interrupted once `content` is read.
+pub fn interrupted(content: &'static [u8]) -> impl AsyncRead + Unpin + Send {
+    let error = futures::stream::iter([Err::<bytes::Bytes, _>(
+        std::io::Error::from(std::io::ErrorKind::ConnectionReset),
+    )]);
+    std::io::Cursor::new(content)
+        .chain(tokio_util::io::StreamReader::new(error))
+}
+
+/// Names of the elements of a database, with `prefix` after `start_after`.
+pub async fn list_names<S: TestBackend>(
+    storage: &S,
+    db: &str,
+    prefix: Option<&str>,
+    start_after: Option<&str>,
+) -> Vec<String>
+where
+    BucketStorageError: From<S::Error>,
+{
+    let mut stream = storage
+        .list_element_in_database(db, prefix, start_after, None)
+        .await
+        .unwrap();
+    let mut names = Vec::new();
+    while let Some(result) = stream.next().await {
+        match result {
+            Ok(elt) => names.push(elt.name),
+            Err(_) => panic!("Error occurred while listing elements."),
+        }
+    }
+    names
+}
+
+pub async fn new_database<S: TestBackend>(storage: S)
+where
+    BucketStorageError: From<S::Error>,
+{
+    let db_name = "test_db";
+    let db_info = storage.new_database(db_name, None).await.unwrap();
+
+    assert_eq!(db_info.name, db_name);
+    assert_eq!(db_info.number_element, 0);
+
+    let retrieved_db_info =
+        storage.database_metadata(db_name).await.unwrap().unwrap();
+    assert_eq!(db_info, retrieved_db_info);
+
+    let result = storage.new_database(db_name, None).await;
+    assert!(matches!(
+        result.map_err(BucketStorageError::from),
+        Err(BucketStorageError::DatabaseAlreadyExist)
+    ));
+    assert!(storage
+        .database_metadata("missing")
+        .await
+        .unwrap()
+        .is_none());
+
+    // The region is stored with the database.
+    storage
+        .new_database("regional_db", Some("eu-west-1".to_string()))
+        .await
+        .unwrap();
+    let regional = storage.database_metadata("regional_db").await.unwrap();
+    assert_eq!(regional.unwrap().region.as_deref(), Some("eu-west-1"));
+}
+
+pub async fn insert_and_get_element<S: TestBackend>(storage: S)
+where
+    BucketStorageError: From<S::Error>,
+{
+    let db_name = "test_db";
+    storage.new_database(db_name, None).await.unwrap();
+
+    let element_name = "test_element";
+    let element_content = b"test_content";
+
+    let mut element_reader = std::io::Cursor::new(element_content);
+
+    let element_info = storage
+        .insert_element_in_database(
+            db_name,
+            element_name,
+            Default::default(),
+            &mut element_reader,
+        )
+        .await
+        .unwrap();
+
+    let retrieved_content =
+        read_element(&storage, db_name, element_name, None, None)
+            .await
+            .unwrap();
+    assert_eq!(retrieved_content, element_content);
+
+    assert_eq!(element_info.name, element_name);
+    assert_eq!(element_info.size, element_content.len() as u64);
+
+    // The range is clamped to the content.
+    let content =
+        read_element(&storage, db_name, element_name, None, Some(5..100))
+            .await
+            .unwrap();
+    assert_eq!(content, b"content");
+    let content =
+        read_element(&storage, db_name, element_name, None, Some(20..30))
+            .await
+            .unwrap();
+    assert!(content.is_empty());
+
+    let result = read_element(&storage, db_name, "missing", None, None).await;
+    assert!(matches!(result, Err(BucketStorageError::NoKey)));
+
+    // The creation date is kept when the element is replaced.
+    let replaced_info = storage
+        .insert_element_in_database(
+            db_name,
+            element_name,
+            Default::default(),
+            &mut std::io::Cursor::new(b"replaced"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(replaced_info.created_at, element_info.created_at);
+    let metadata = storage
+        .get_element_metadata_in_database(db_name, element_name)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(metadata.size, 8);
+    assert_eq!(metadata.md5, replaced_info.md5);
+
+    let result = storage
+        .insert_element_in_database(
+            "missing_db",
+            element_name,
+            Default::default(),
+            &mut std::io::Cursor::new(b""),
+        )
+        .await;
+    assert!(matches!(
+        result.map_err(BucketStorageError::from),
+        Err(BucketStorageError::NoBucket)
+    ));
+}
+
+pub async fn interrupted_insert_keeps_element<S: TestBackend>(storage: S)
+where
+    BucketStorageError: From<S::Error>,
+{
+    let db_name = "test_db";
+    storage.new_database(db_name, None).await.unwrap();
+
+    let element_name = "test_element";
+    let inserted = storage
+        .insert_element_in_database(
+            db_name,
+            element_name,
+            Default::default(),
+            &mut std::io::Cursor::new(b"previous content"),
+        )
+        .await
+        .unwrap();
+
+    // The upload of the new content stops half way, the previous element is
+    // still there as it was.
+    let result = storage
+        .insert_element_in_database(
+            db_name,
+            element_name,
+            Default::default(),
+            &mut interrupted(b"new"),
+        )
+        .await;
+    assert!(result.is_err());
+
+    let content = read_element(&storage, db_name, element_name, None, None)
+        .await
+        .unwrap();
+    assert_eq!(content, b"previous content");
+    let elt = storage
+        .get_element_metadata_in_database(db_name, element_name)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(elt.checksum, inserted.checksum);
+    assert_eq!(elt.size, inserted.size);
+
+    // Nothing is left of an interrupted upload of a new element.
+    let result = storage
+        .insert_element_in_database(
+            db_name,
+            "other_element",
+            Default::default(),
+            &mut interrupted(b"new"),
+        )
+        .await;
+    assert!(result.is_err());
+    assert!(storage
+        .get_element_metadata_in_database(db_name, "other_element")
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        list_names(&storage, db_name, None, None).await,
+        [element_name]
+    );
+}
+
+pub async fn insert_element_with_checksum<S: TestBackend>(storage: S)
+where
+    BucketStorageError: From<S::Error>,
+{
+    let db_name = "test_db";
+    storage.new_database(db_name, None).await.unwrap();
+
+    let properties = |expected: &str| ElementProperties {
+        checksum: Some(ChecksumRequest {
+            algorithm: ChecksumAlgorithm::CRC32,
+            expected: ExpectedChecksum::new(Some(expected.to_string())),
+        }),
+        ..Default::default()
+    };
+
+    let element_info = storage
+        .insert_element_in_database(
+            db_name,
+            "good",
+            properties("DUoRhQ=="),
+            &mut std::io::Cursor::new(b"hello world"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        element_info.additional_checksum,
+        Some(ElementChecksum {
+            algorithm: ChecksumAlgorithm::CRC32,
+            value: "DUoRhQ==".to_string(),
+        })
+    );
+
+    let result = storage
+        .insert_element_in_database(
+            db_name,
+            "bad",
+            properties("DUoRhQ=="),
+            &mut std::io::Cursor::new(b"hello world!"),
+        )
+        .await;
+    assert!(matches!(
+        result.map_err(BucketStorageError::from),
+        Err(BucketStorageError::BadDigest)
+    ));
+    assert!(storage
+        .get_element_metadata_in_database(db_name, "bad")
+        .await
+        .unwrap()
+        .is_none());
+}
+
+pub async fn insert_element_with_content_md5<S: TestBackend>(storage: S)
+where
+    BucketStorageError: From<S::Error>,
+{
+    let db_name = "test_db";
+    storage.new_database(db_name, None).await.unwrap();
+
+    let md5 = hex::decode("5eb63bbbe01eeed093cb22bb8f5acdc3").unwrap();
+    let properties = || ElementProperties {
+        content_md5: Some(md5.clone()),
+        ..Default::default()
+    };
+
+    let element_info = storage
+        .insert_element_in_database(
+            db_name,
+            "good",
+            properties(),
+            &mut std::io::Cursor::new(b"hello world"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(element_info.e_tag(), "\"5eb63bbbe01eeed093cb22bb8f5acdc3\"");
+
+    let result = storage
+        .insert_element_in_database(
+            db_name,
+            "bad",
+            properties(),
+            &mut std::io::Cursor::new(b"hello world!"),
+        )
+        .await;
+    assert!(matches!(
+        result.map_err(BucketStorageError::from),
+        Err(BucketStorageError::BadDigest)
+    ));
+}
+
+pub async fn insert_and_get_encrypted_element<S: TestBackend>(storage: S)
+where
+    BucketStorageError: From<S::Error>,
+{
+    let db_name = "test_db";
+    storage.new_database(db_name, None).await.unwrap();
+
+    let key = CustomerKey::parse(
+        AES256,
+        "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=",
+        "hRasmdxgYDKV3nvbahU1MA==",
+    )
+    .unwrap();
+    let element_info = storage
+        .insert_element_in_database(
+            db_name,
+            "secret",
+            ElementProperties {
+                customer_key: Some(key.clone()),
+                ..Default::default()
+            },
+            &mut std::io::Cursor::new(b"hello world"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        element_info
+            .encryption
+            .as_ref()
+            .and_then(|x| x.customer_key_md5()),
+        Some(key.key_md5())
+    );
+
+    let stored = storage.stored_content(db_name, "secret").await;
+    assert_eq!(stored.len(), 11);
+    assert_ne!(stored, b"hello world");
+
+    let result = read_element(&storage, db_name, "secret", None, None).await;
+    assert!(matches!(
+        result,
+        Err(BucketStorageError::CustomerKeyMismatch)
+    ));
+
+    let content = read_element(&storage, db_name, "secret", Some(&key), None)
+        .await
+        .unwrap();
+    assert_eq!(content, b"hello world");
+
+    // The content is decrypted from the start of the range.
+    let content =
+        read_element(&storage, db_name, "secret", Some(&key), Some(6..11))
+            .await
+            .unwrap();
+    assert_eq!(content, b"world");
+}
+
+pub async fn insert_and_get_managed_encrypted_element<S: TestBackend>(
+    storage: S,
+) where
+    BucketStorageError: From<S::Error>,
+{
+    let db_name = "test_db";
+    let properties = ElementProperties {
+        server_side_encryption: true,
+        ..Default::default()
+    };
+
+    // The insertion is refused without a master key.
+    storage.new_database(db_name, None).await.unwrap();
+    let result = storage
+        .insert_element_in_database(
+            db_name,
+            "secret",
+            properties.clone(),
+            &mut std::io::Cursor::new(b"hello world"),
+        )
+        .await;
+    assert!(matches!(
+        result.map_err(BucketStorageError::from),
+        Err(BucketStorageError::EncryptionNotConfigured)
+    ));
+
+    let other_storage =
+        storage.clone().with_master_key(MasterKey::new([8; 32]));
+    let storage = storage.with_master_key(MasterKey::new([7; 32]));
+    let element_info = storage
+        .insert_element_in_database(
+            db_name,
+            "secret",
+            properties,
+            &mut std::io::Cursor::new(b"hello world"),
+        )
+        .await
+        .unwrap();
+    assert!(element_info
+        .encryption
+        .as_ref()
+        .is_some_and(|x| x.is_managed()));
+    // The entity tag is still the digest of the content.
+    assert_eq!(element_info.md5, "5eb63bbbe01eeed093cb22bb8f5acdc3");
+
+    let stored = storage.stored_content(db_name, "secret").await;
+    assert_eq!(stored.len(), 11);
+    assert_ne!(stored, b"hello world");
+
+    let content = read_element(&storage, db_name, "secret", None, None)
+        .await
+        .unwrap();
+    assert_eq!(content, b"hello world");
+    let content = read_element(&storage, db_name, "secret", None, Some(2..5))
+        .await
+        .unwrap();
+    assert_eq!(content, b"llo");
+
+    let result =
+        read_element(&other_storage, db_name, "secret", None, None).await;
+    assert!(matches!(
+        result,
+        Err(BucketStorageError::CustomerKeyMismatch)
+    ));
+}
+
+pub async fn list_element<S: TestBackend>(storage: S)
+where
+    BucketStorageError: From<S::Error>,
+{
+    let db_name = "test_db";
+    storage.new_database(db_name, None).await.unwrap();
+
+    let element_name = "test_element";
+    storage
+        .insert_element_in_database(
+            db_name,
+            element_name,
+            Default::default(),
+            &mut std::io::Cursor::new(b""),
+        )
+        .await
+        .unwrap();
+
+    let names = list_names(&storage, db_name, None, None).await;
+    assert!(names.iter().any(|x| x == element_name));
+}
+
+pub async fn list_element_in_order<S: TestBackend>(storage: S)
+where
+    BucketStorageError: From<S::Error>,
+{
+    let db_name = "test_db";
+    storage.new_database(db_name, None).await.unwrap();
+
+    for name in ["b/2", "a", "b/1", "c", "b"] {
+        storage
+            .insert_element_in_database(
+                db_name,
+                name,
+                Default::default(),
+                &mut std::io::Cursor::new(b""),
+            )
+            .await
+            .unwrap();
+    }
+
+    let names = |prefix, start_after| {
+        list_names(&storage, db_name, prefix, start_after)
+    };
+    assert_eq!(names(None, None).await, ["a", "b", "b/1", "b/2", "c"]);
+    assert_eq!(names(None, Some("b/1")).await, ["b/2", "c"]);
+    assert_eq!(names(Some("b/"), None).await, ["b/1", "b/2"]);
+    assert_eq!(names(Some("b"), Some("b/1")).await, ["b/2"]);
+    assert_eq!(names(Some("b/"), Some("a")).await, ["b/1", "b/2"]);
+    assert!(names(Some("d"), None).await.is_empty());
+
+    // Only the first elements are read.
+    let mut stream = storage
+        .list_element_in_database(db_name, Some("b"), None, Some(2))
+        .await
+        .unwrap();
+    let mut limited = Vec::new();
+    while let Some(elt) = stream.next().await {
+        limited.push(elt.map_err(|_| ()).unwrap().name);
+    }
+    assert_eq!(limited, ["b", "b/1"]);
+}
+
+/// The keys are not names of files: they can have slashes, `..`, many
+/// prefixes or up to 1024 bytes of unicode.
+pub async fn any_key<S: TestBackend>(storage: S)
+where
+    BucketStorageError: From<S::Error>,
+{
+    let db_name = "test_db";
+    storage.new_database(db_name, None).await.unwrap();
+    storage.new_database("other_db", None).await.unwrap();
+
+    let long = "é".repeat(512);
+    let deep = "a/".repeat(200) + "z";
+    let mut keys = vec![
+        "../other_db/element",
+        "../../escape",
+        "/root",
+        "dir/",
+        "dir/.",
+        "dir/..",
+        "dir//element",
+        "日本語/ファイル.txt",
+        "element.part",
+        ".element.lock",
+        &long,
+        &deep,
+    ];
+    assert_eq!(long.len(), 1024);
+    for key in &keys {
+        storage
+            .insert_element_in_database(
+                db_name,
+                key,
+                Default::default(),
+                &mut std::io::Cursor::new(key.as_bytes()),
+            )
+            .await
+            .unwrap();
+    }
+
+    for key in &keys {
+        let content = read_element(&storage, db_name, key, None, None)
+            .await
+            .unwrap();
+        assert_eq!(content, key.as_bytes());
+    }
+    keys.sort();
+    assert_eq!(list_names(&storage, db_name, None, None).await, keys);
+    assert_eq!(
+        list_names(&storage, db_name, Some("dir/"), None).await,
+        ["dir/", "dir/.", "dir/..", "dir//element"]
+    );
+    // Nothing was written in the other database.
+    assert!(list_names(&storage, "other_db", None, None)
+        .await
+        .is_empty());
+
+    for key in &keys {
+        storage
+            .delete_element_in_database(db_name, key)
+            .await
+            .unwrap();
+    }
+    assert!(list_names(&storage, db_name, None, None).await.is_empty());
+}
+
+pub async fn delete_element<S: TestBackend>(storage: S)
+where
+    BucketStorageError: From<S::Error>,
+{
+    let db_name = "test_db";
+    storage.new_database(db_name, None).await.unwrap();
+
+    let element_name = "test_element";
+    storage
+        .insert_element_in_database(
+            db_name,
+            element_name,
+            Default::default(),
+            &mut std::io::Cursor::new(b""),
+        )
+        .await
+        .unwrap();
+    let db_info = storage.database_metadata(db_name).await.unwrap().unwrap();
+    assert_eq!(db_info.number_element, 1);
+
+    let delete_result = storage
+        .delete_element_in_database(db_name, element_name)
+        .await;
+    assert!(delete_result.is_ok());
+
+    let names = list_names(&storage, db_name, None, None).await;
+    assert!(!names.iter().any(|x| x == element_name));
+    assert!(storage
+        .get_element_metadata_in_database(db_name, element_name)
+        .await
+        .unwrap()
+        .is_none());
+    let db_info = storage.database_metadata(db_name).await.unwrap().unwrap();
+    assert_eq!(db_info.number_element, 0);
+}
+
+pub async fn database_stats<S: TestBackend>(storage: S)
+where
+    BucketStorageError: From<S::Error>,
+{
+    let db_name = "test_db";
+    storage.new_database(db_name, None).await.unwrap();
+
+    let stats =
+        |db_info: DatabaseInfo| (db_info.number_element, db_info.total_size);
+    let insert = |name, content: &'static [u8]| {
+        let storage = storage.clone();
+        async move {
+            storage
+                .insert_element_in_database(
+                    db_name,
+                    name,
+                    Default::default(),
+                    &mut std::io::Cursor::new(content),
+                )
+                .await
+                .unwrap();
+            storage.database_metadata(db_name).await.unwrap().unwrap()
+        }
+    };
+    assert_eq!(stats(insert("first", b"first").await), (1, 5));
+    assert_eq!(stats(insert("second", b"two").await), (2, 8));
+    // Replacing an element doesn't count as a new one.
+    assert_eq!(stats(insert("first", b"1").await), (2, 4));
+
+    storage
+        .delete_element_in_database(db_name, "second")
+        .await
+        .unwrap();
+    let db_info = storage.database_metadata(db_name).await.unwrap().unwrap();
+    assert_eq!(stats(db_info.clone()), (1, 1));
+
+    // The statistics are only changed with the elements.
+    storage
+        .update_database(DatabaseInfo {
+            number_element: 10,
+            total_size: 10,
+            ..db_info
+        })
+        .await
+        .unwrap();
+    let db_info = storage.database_metadata(db_name).await.unwrap().unwrap();
+    assert_eq!(stats(db_info), (1, 1));
+
+    let db_info = storage.recompute_database_stats(db_name).await.unwrap();
+    assert_eq!(stats(db_info), (1, 1));
+    assert!(storage.recompute_database_stats("no_db").await.is_err());
+}
+
+pub async fn list_empty_database<S: TestBackend>(storage: S)
+where
+    BucketStorageError: From<S::Error>,
+{
+    let db_name = "empty_db";
+    storage.new_database(db_name, None).await.unwrap();
+
+    assert!(list_names(&storage, db_name, None, None).await.is_empty());
+}
@@ -12,9 +12,17 @@ use application::Application;
 mod domain;
 
 mod infrastructure;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use infrastructure::config::StorageConfig;
 pub use infrastructure::config::{self, Cfg};
+pub use infrastructure::constant::VERSION;
 use infrastructure::instrumentation::Instruments;
-use infrastructure::storage::FSStorage;
+use infrastructure::notification::Notifier;
+use infrastructure::storage::encryption::MasterKey;
+use infrastructure::storage::{FSStorage, MemoryStorage};
 use tracing::info;
 
 pub async fn launch_wasmio(cfg: Cfg) -> anyhow::Result<()> {
@@ -22,12 +30,68 @@ pub async fn launch_wasmio(cfg: Cfg) -> anyhow::Result<()> {
     let _ = Instruments::new();
     info!("Starting the process");
 
-    // Initiate the storage, we only support FS for now
-    let storage = FSStorage::new(cfg.storage.path);
+    let master_key = cfg
+        .encryption
+        .as_ref()
+        .map(|encryption| MasterKey::from_file(&encryption.master_key_file))
+        .transpose()?;
+
+    let notifier = match &cfg.notification {
+        Some(notification) => {
+            Some(Notifier::start(notification, &cfg.region).await?)
+        }
+        None => None,
+    };
 
-    // Server
-    let app = Application::new(storage).serve(cfg.bind_addr);
-    app.await??;
+    // Server, with the storage backend of the configuration
+    let cfg = Arc::new(cfg);
+    match &cfg.storage {
+        StorageConfig::Fs {
+            path,
+            chunk_size,
+            segment_object_size,
+            compaction_interval,
+        } => {
+            let mut storage = FSStorage::new(path.clone());
+            if let Some(chunk_size) = chunk_size {
+                storage = storage.with_chunk_size(*chunk_size);
+            }
+            if let Some(object_size) = segment_object_size {
+                storage = storage.with_segments(*object_size);
+            }
+            if let Some(master_key) = master_key {
+                storage = storage.with_master_key(master_key);
+            }
+            // The interrupted operations are recovered before any request
+            // is accepted.
+            storage.recover().await?;
+            // The segments written before are compacted even when they are
+            // not enabled anymore.
+            let compaction = storage
+                .spawn_compaction(Duration::from_secs(*compaction_interval))
+                .await?;
+            let app = Application::new(storage, notifier, cfg.clone()).run();
+            match compaction {
+                Some(compaction) => {
+                    tokio::try_join!(app, async {
+                        compaction
+                            .await
+                            .context("The compaction of the segments stopped")
+                    })?;
+                }
+                None => app.await?,
+            }
+        }
+        StorageConfig::Memory => {
+            let mut storage = MemoryStorage::new();
+            if let Some(master_key) = master_key {
+                storage = storage.with_master_key(master_key);
+            }
+            Application::new(storage, notifier, cfg.clone())
+                .run()
+                .await?;
+        }
+    }
 
     info!("Ending the process");
     Ok(())
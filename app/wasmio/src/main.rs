@@ -5,17 +5,7 @@
 )]
 #![cfg_attr(all(target_arch = "wasm32", target_os = "wasi"), feature(stdsimd))]
 
-mod application;
-use application::Application;
-
-mod domain;
-
-mod infrastructure;
-use infrastructure::config::Cfg;
-use infrastructure::constant::VERSION;
-use infrastructure::instrumentation::Instruments;
-use infrastructure::storage::FSStorage;
-use tracing::info;
+use wasmio::{launch_wasmio, Cfg, VERSION};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -47,17 +37,5 @@ by @miaxos https://github.com/miaxos
         addr = config.bind_addr.ip(),
     );
 
-    // Instrumentation
-    let _ = Instruments::new();
-    info!("Starting the process");
-
-    // Initiate the storage, we only support FS for now
-    let storage = FSStorage::new(config.storage.path);
-
-    // Server
-    let app = Application::new(storage).serve(config.bind_addr);
-    app.await??;
-
-    info!("Ending the process");
-    Ok(())
+    launch_wasmio(config).await
 }
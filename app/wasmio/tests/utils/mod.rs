@@ -9,7 +9,7 @@ mod port_picker;
 static CACHE: OnceCell<String> = OnceCell::const_new();
 /// Start a server if needed
 pub async fn start_simple_server() -> anyhow::Result<String> {
-    use wasmio::config::{Cfg, StorageConfig};
+    use wasmio::config::{AccessLogConfig, Cfg, StorageConfig};
     use wasmio::launch_wasmio;
 
     use crate::utils::port_picker::pick_unused_port;
@@ -33,7 +33,19 @@ pub async fn start_simple_server() -> anyhow::Result<String> {
             std::fs::create_dir_all(&path).expect("shouldn't fail");
             let cfg = Cfg {
                 bind_addr: addr,
-                storage: StorageConfig { path },
+                domain: None,
+                region: "us-east-1".to_string(),
+                storage: StorageConfig::Fs {
+                    path,
+                    chunk_size: None,
+                    segment_object_size: None,
+                    compaction_interval: 600,
+                },
+                website: None,
+                auth: None,
+                encryption: None,
+                notification: None,
+                access_log: AccessLogConfig::default(),
             };
             tokio::spawn(async move {
                 std::env::set_var("RUST_LOG", "info");
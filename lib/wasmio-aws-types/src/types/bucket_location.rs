@@ -0,0 +1,46 @@
+use derivative::Derivative;
+use derive_builder::Builder;
+use serde::Serialize;
+
+#[derive(Derivative, Default, Builder)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+pub struct GetBucketLocationRequest {
+    /// The name of the bucket for which to get the location.
+    pub bucket: String,
+    /// The account ID of the expected bucket owner. If the bucket is owned by
+    /// a different account, the request will fail with an HTTP `403 (Access
+    /// Denied)` error.
+    pub expected_bucket_owner: Option<String>,
+}
+
+#[derive(Derivative, Default, Builder, Serialize)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename = "LocationConstraint")]
+pub struct GetBucketLocationOutput {
+    /// Specifies the Region where the bucket resides, buckets in Region
+    /// `us-east-1` have a location constraint of `null`.
+    #[serde(rename = "$value", skip_serializing_if = "Option::is_none")]
+    pub location_constraint: Option<String>,
+}
+
+#[derive(Derivative, Default, Builder)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+pub struct HeadBucketRequest {
+    /// The bucket name.
+    pub bucket: String,
+    /// The account ID of the expected bucket owner. If the bucket is owned by
+    /// a different account, the request will fail with an HTTP `403 (Access
+    /// Denied)` error.
+    pub expected_bucket_owner: Option<String>,
+}
+
+#[derive(Derivative, Default, Builder)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+pub struct HeadBucketOutput {
+    /// The Region that the bucket is located.
+    pub bucket_region: Option<String>,
+}
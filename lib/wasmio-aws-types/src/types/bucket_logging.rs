@@ -0,0 +1,67 @@
+use derivative::Derivative;
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+
+/// Container for logging status information. If this element is empty,
+/// logging is disabled for the bucket.
+#[derive(
+    Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize,
+)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename = "BucketLoggingStatus")]
+#[serde(rename_all = "PascalCase")]
+pub struct BucketLoggingStatus {
+    /// Describes where logs are stored and the prefix that Amazon S3 assigns
+    /// to all log object keys for a bucket.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logging_enabled: Option<LoggingEnabled>,
+}
+
+/// Describes where logs are stored and the prefix that Amazon S3 assigns to
+/// all log object keys for a bucket.
+#[derive(
+    Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize,
+)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct LoggingEnabled {
+    /// Specifies the bucket where you want Amazon S3 to store server access
+    /// logs. You can have your logs delivered to any bucket that you own,
+    /// including the same bucket that is being logged.
+    pub target_bucket: String,
+    /// A prefix for all log object keys. If you store log files from
+    /// multiple Amazon S3 buckets in a single bucket, you can use a prefix to
+    /// distinguish which log files came from which bucket.
+    #[serde(default)]
+    pub target_prefix: String,
+}
+
+#[derive(Derivative, Default, Builder)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+pub struct PutBucketLoggingRequest {
+    /// The name of the bucket for which to set the logging parameters.
+    pub bucket: String,
+    /// Container for logging status information.
+    pub bucket_logging_status: BucketLoggingStatus,
+    /// The MD5 hash of the `PutBucketLogging` request body.
+    pub content_md5: Option<String>,
+    /// The account ID of the expected bucket owner. If the bucket is owned by
+    /// a different account, the request will fail with an HTTP `403 (Access
+    /// Denied)` error.
+    pub expected_bucket_owner: Option<String>,
+}
+
+#[derive(Derivative, Default, Builder)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+pub struct GetBucketLoggingRequest {
+    /// The bucket name for which to get the logging information.
+    pub bucket: String,
+    /// The account ID of the expected bucket owner. If the bucket is owned by
+    /// a different account, the request will fail with an HTTP `403 (Access
+    /// Denied)` error.
+    pub expected_bucket_owner: Option<String>,
+}
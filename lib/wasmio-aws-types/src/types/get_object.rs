@@ -10,6 +10,8 @@ use derive_builder::Builder;
 pub struct GetObjectRequest {
     /// The bucket name containing the object.  When using this action with an access point, you must direct requests to the access point hostname. The access point hostname takes the form *AccessPointName*-*AccountId*.s3-accesspoint.*Region*.amazonaws.com. When using this action with an access point through the AWS SDKs, you provide the access point ARN in place of the bucket name. For more information about access point ARNs, see <a href="https://docs.aws.amazon.com/AmazonS3/latest/userguide/using-access-points.html">Using access points</a> in the *Amazon S3 User Guide*. When using this action with Amazon S3 on Outposts, you must direct requests to the S3 on Outposts hostname. The S3 on Outposts hostname takes the form *AccessPointName*-*AccountId*.*outpostID*.s3-outposts.*Region*.amazonaws.com. When using this action using S3 on Outposts through the AWS SDKs, you provide the Outposts bucket ARN in place of the bucket name. For more information about S3 on Outposts ARNs, see <a href="https://docs.aws.amazon.com/AmazonS3/latest/userguide/S3onOutposts.html">Using S3 on Outposts</a> in the *Amazon S3 User Guide*.
     pub bucket: String,
+    /// To retrieve the checksum of the object, this mode must be `ENABLED`.
+    pub checksum_mode: Option<String>,
     /// The account ID of the expected bucket owner. If the bucket is owned by
     /// a different account, the request will fail with an HTTP <code>403
     /// (Access Denied)</code> error.
@@ -156,6 +158,14 @@ pub struct GetObjectOutput {
     pub tag_count: Option<i64>,
     /// Version of the object.
     pub version_id: Option<String>,
+    /// The base64-encoded, 32-bit CRC32 checksum of the object.
+    pub checksum_crc32: Option<String>,
+    /// The base64-encoded, 32-bit CRC32C checksum of the object.
+    pub checksum_crc32c: Option<String>,
+    /// The base64-encoded, 160-bit SHA-1 digest of the object.
+    pub checksum_sha1: Option<String>,
+    /// The base64-encoded, 256-bit SHA-256 digest of the object.
+    pub checksum_sha256: Option<String>,
     /// If the bucket is configured as a website, redirects requests for this
     /// object to another object in the same bucket or to an external URL.
     /// Amazon S3 stores the value of this header in the object metadata.
@@ -1,13 +1,17 @@
-use std::collections::HashMap;
-
-use axum::body::{Body, BodyDataStream};
 use derivative::Derivative;
 use derive_builder::Builder;
+use serde::Serialize;
 
 #[derive(Derivative, Default, Builder)]
 #[derivative(Debug)]
 #[builder(pattern = "owned", setter(into), default)]
 pub struct GetObjectAttributesRequest {
+    /// The name of the bucket that contains the object.
+    pub bucket: String,
+    /// The object key.
+    pub key: String,
+    /// The version ID used to reference a specific version of the object.
+    pub version_id: Option<String>,
     /// <p>Maximum number of parts that were allowed in the response.</p>
     pub max_parts: Option<i64>,
     /// <p>Part number of the object being read. This is a positive integer
@@ -40,7 +44,7 @@ pub struct GetObjectAttributesRequest {
     pub expected_attributes: Vec<String>,
 }
 
-#[derive(Derivative, Default, Builder)]
+#[derive(Derivative, Default, Builder, Serialize)]
 #[derivative(Debug)]
 #[builder(pattern = "owned", setter(into), default)]
 #[serde(rename = "GetObjectAttributesOutput")]
@@ -50,16 +54,55 @@ pub struct GetObjectAttributesOutput {
     /// to the contents of an object, not its metadata. The ETag may or may not
     /// be an MD5 digest of the object data. Whether or not it is depends on
     /// how the object was created and how it is encrypted as described below:
-    /// - Objects created by the PUT Object, POST Object, or Copy
-    /// operation, or through the AWS Management Console, and are encrypted by
-    /// SSE-S3 or plaintext, have ETags that are an MD5 digest of their object
-    /// data.
-    /// - Objects created by the PUT Object, POST Object, or
-    /// Copy operation, or through the AWS Management Console, and are
-    /// encrypted by SSE-C or SSE-KMS, have ETags that are not an MD5 digest of
-    /// their object data.
-    /// - If an object is created by either the
-    /// Multipart Upload or Part Copy operation, the ETag is not an MD5 digest,
-    /// regardless of the method of encryption.
+    /// - Objects created by the PUT Object, POST Object, or Copy operation, or
+    ///   through the AWS Management Console, and are encrypted by SSE-S3 or
+    ///   plaintext, have ETags that are an MD5 digest of their object data.
+    /// - Objects created by the PUT Object, POST Object, or Copy operation, or
+    ///   through the AWS Management Console, and are encrypted by SSE-C or
+    ///   SSE-KMS, have ETags that are not an MD5 digest of their object data.
+    /// - If an object is created by either the Multipart Upload or Part Copy
+    ///   operation, the ETag is not an MD5 digest, regardless of the method of
+    ///   encryption.
+    #[serde(rename = "ETag", skip_serializing_if = "Option::is_none")]
     pub e_tag: Option<String>,
+    /// The checksum or digest of the object.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<Checksum>,
+    /// Provides the storage class information of the object.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_class: Option<String>,
+    /// The size of the object in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_size: Option<i64>,
+    /// The creation date of the object, sent as the `Last-Modified` header.
+    #[serde(skip)]
+    pub last_modified: Option<String>,
+    /// The version ID of the object, sent as the `x-amz-version-id` header.
+    #[serde(skip)]
+    pub version_id: Option<String>,
+}
+
+/// Contains all the possible checksum or digest values for an object.
+#[derive(Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+pub struct Checksum {
+    /// The base64-encoded, 32-bit CRC32 checksum of the object.
+    #[serde(rename = "ChecksumCRC32", skip_serializing_if = "Option::is_none")]
+    pub checksum_crc32: Option<String>,
+    /// The base64-encoded, 32-bit CRC32C checksum of the object.
+    #[serde(
+        rename = "ChecksumCRC32C",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub checksum_crc32c: Option<String>,
+    /// The base64-encoded, 160-bit SHA-1 digest of the object.
+    #[serde(rename = "ChecksumSHA1", skip_serializing_if = "Option::is_none")]
+    pub checksum_sha1: Option<String>,
+    /// The base64-encoded, 256-bit SHA-256 digest of the object.
+    #[serde(
+        rename = "ChecksumSHA256",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub checksum_sha256: Option<String>,
 }
@@ -64,38 +64,47 @@ pub struct ListObjectsV2Output {
     /// prefix is <code>notes/summer/</code>. All of the keys that roll up into
     /// a common prefix count as a single return when calculating the number of
     /// returns.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub common_prefixes: Option<Vec<CommonPrefix>>,
     /// Metadata about each object returned.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub contents: Option<Vec<Object>>,
     ///  If ContinuationToken was sent with the request, it is included in
     /// the response.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub continuation_token: Option<String>,
     /// Causes keys that contain the same string between the prefix and the
     /// first occurrence of the delimiter to be rolled up into a single result
     /// element in the CommonPrefixes collection. These rolled-up keys are not
     /// returned elsewhere in the response. Each rolled-up result counts as
     /// only one return against the <code>MaxKeys</code> value.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub delimiter: Option<String>,
     /// Encoding type used by Amazon S3 to encode object key names in the
     /// XML response. If you specify the encoding-type request
     /// parameter, Amazon S3 includes this element in the response, and returns
     /// encoded key name values in the following response elements:
     /// <code>Delimiter, Prefix, Key,</code> and <code>StartAfter</code>.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub encoding_type: Option<String>,
     /// Set to false if all of the results were returned. Set to true if
     /// more keys are available to return. If the number of results exceeds
     /// that specified by MaxKeys, all of the results might not be
     /// returned.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub is_truncated: Option<bool>,
     /// KeyCount is the number of keys returned with this request. KeyCount
     /// will always be less than or equals to MaxKeys field. Say you ask for 50
     /// keys, your result will include less than equals 50 keys
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub key_count: Option<i64>,
     /// Sets the maximum number of keys returned in the response. By default
     /// the action returns up to 1,000 key names. The response might contain
     /// fewer keys but will never contain more.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_keys: Option<i64>,
     /// The bucket name. When using this action with an access point, you must direct requests to the access point hostname. The access point hostname takes the form *AccessPointName*-*AccountId*.s3-accesspoint.*Region*.amazonaws.com. When using this action with an access point through the AWS SDKs, you provide the access point ARN in place of the bucket name. For more information about access point ARNs, see <a href="https://docs.aws.amazon.com/AmazonS3/latest/userguide/using-access-points.html">Using access points</a> in the *Amazon S3 User Guide*. When using this action with Amazon S3 on Outposts, you must direct requests to the S3 on Outposts hostname. The S3 on Outposts hostname takes the form *AccessPointName*-*AccountId*.*outpostID*.s3-outposts.*Region*.amazonaws.com. When using this action using S3 on Outposts through the AWS SDKs, you provide the Outposts bucket ARN in place of the bucket name. For more information about S3 on Outposts ARNs, see <a href="https://docs.aws.amazon.com/AmazonS3/latest/userguide/S3onOutposts.html">Using S3 on Outposts</a> in the *Amazon S3 User Guide*.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
     ///  <code>NextContinuationToken</code> is sent when
     /// <code>isTruncated</code> is true, which means there are more keys in
@@ -103,10 +112,13 @@ pub struct ListObjectsV2Output {
     /// be continued with this <code>NextContinuationToken</code>.
     /// <code>NextContinuationToken</code> is obfuscated and is not a real
     /// key
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub next_continuation_token: Option<String>,
     ///  Keys that begin with the indicated prefix.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub prefix: Option<String>,
     /// If StartAfter was sent with the request, it is included in the
     /// response.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub start_after: Option<String>,
 }
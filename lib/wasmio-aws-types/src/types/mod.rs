@@ -5,12 +5,26 @@ pub use create_bucket_configuration::{
     CreateBucketRequestBuilder, CreateBucketRequestBuilderError,
 };
 
+mod bucket_location;
+pub use bucket_location::{
+    GetBucketLocationOutput, GetBucketLocationOutputBuilder,
+    GetBucketLocationOutputBuilderError, GetBucketLocationRequest,
+    GetBucketLocationRequestBuilder, GetBucketLocationRequestBuilderError,
+    HeadBucketOutput, HeadBucketOutputBuilder, HeadBucketOutputBuilderError,
+    HeadBucketRequest, HeadBucketRequestBuilder, HeadBucketRequestBuilderError,
+};
+
 mod put_object;
 pub use put_object::{
     PutObjectOutput, PutObjectOutputBuilder, PutObjectOutputBuilderError,
     PutObjectRequest, PutObjectRequestBuilder, PutObjectRequestBuilderError,
 };
 
+mod post_object;
+pub use post_object::{
+    PostResponse, PostResponseBuilder, PostResponseBuilderError,
+};
+
 mod delete_object;
 pub use delete_object::{
     DeleteObjectOutput, DeleteObjectOutputBuilder,
@@ -42,4 +56,84 @@ pub use get_object::{
     GetObjectRequest, GetObjectRequestBuilder, GetObjectRequestBuilderError,
 };
 
-// mod get_object_attributes;
+mod get_object_attributes;
+pub use get_object_attributes::{
+    Checksum, ChecksumBuilder, ChecksumBuilderError, GetObjectAttributesOutput,
+    GetObjectAttributesOutputBuilder, GetObjectAttributesOutputBuilderError,
+    GetObjectAttributesRequest, GetObjectAttributesRequestBuilder,
+    GetObjectAttributesRequestBuilderError,
+};
+
+mod server_side_encryption;
+pub use server_side_encryption::{
+    DeleteBucketEncryptionRequest, DeleteBucketEncryptionRequestBuilder,
+    DeleteBucketEncryptionRequestBuilderError, GetBucketEncryptionRequest,
+    GetBucketEncryptionRequestBuilder, GetBucketEncryptionRequestBuilderError,
+    PutBucketEncryptionRequest, PutBucketEncryptionRequestBuilder,
+    PutBucketEncryptionRequestBuilderError, ServerSideEncryptionByDefault,
+    ServerSideEncryptionByDefaultBuilder,
+    ServerSideEncryptionByDefaultBuilderError,
+    ServerSideEncryptionConfiguration,
+    ServerSideEncryptionConfigurationBuilder,
+    ServerSideEncryptionConfigurationBuilderError, ServerSideEncryptionRule,
+    ServerSideEncryptionRuleBuilder, ServerSideEncryptionRuleBuilderError,
+};
+
+mod bucket_logging;
+pub use bucket_logging::{
+    BucketLoggingStatus, BucketLoggingStatusBuilder,
+    BucketLoggingStatusBuilderError, GetBucketLoggingRequest,
+    GetBucketLoggingRequestBuilder, GetBucketLoggingRequestBuilderError,
+    LoggingEnabled, LoggingEnabledBuilder, LoggingEnabledBuilderError,
+    PutBucketLoggingRequest, PutBucketLoggingRequestBuilder,
+    PutBucketLoggingRequestBuilderError,
+};
+
+mod notification_configuration;
+pub use notification_configuration::{
+    FilterRule, FilterRuleBuilder, FilterRuleBuilderError,
+    GetBucketNotificationConfigurationRequest,
+    GetBucketNotificationConfigurationRequestBuilder,
+    GetBucketNotificationConfigurationRequestBuilderError,
+    NotificationConfiguration, NotificationConfigurationBuilder,
+    NotificationConfigurationBuilderError, NotificationConfigurationFilter,
+    NotificationConfigurationFilterBuilder,
+    NotificationConfigurationFilterBuilderError,
+    PutBucketNotificationConfigurationRequest,
+    PutBucketNotificationConfigurationRequestBuilder,
+    PutBucketNotificationConfigurationRequestBuilderError, QueueConfiguration,
+    QueueConfigurationBuilder, QueueConfigurationBuilderError, S3KeyFilter,
+    S3KeyFilterBuilder, S3KeyFilterBuilderError,
+};
+
+mod website_configuration;
+pub use website_configuration::{
+    Condition, ConditionBuilder, ConditionBuilderError,
+    DeleteBucketWebsiteRequest, DeleteBucketWebsiteRequestBuilder,
+    DeleteBucketWebsiteRequestBuilderError, ErrorDocument,
+    ErrorDocumentBuilder, ErrorDocumentBuilderError, GetBucketWebsiteRequest,
+    GetBucketWebsiteRequestBuilder, GetBucketWebsiteRequestBuilderError,
+    IndexDocument, IndexDocumentBuilder, IndexDocumentBuilderError,
+    PutBucketWebsiteRequest, PutBucketWebsiteRequestBuilder,
+    PutBucketWebsiteRequestBuilderError, Redirect, RedirectAllRequestsTo,
+    RedirectAllRequestsToBuilder, RedirectAllRequestsToBuilderError,
+    RedirectBuilder, RedirectBuilderError, RoutingRule, RoutingRuleBuilder,
+    RoutingRuleBuilderError, RoutingRules, RoutingRulesBuilder,
+    RoutingRulesBuilderError, WebsiteConfiguration,
+    WebsiteConfigurationBuilder, WebsiteConfigurationBuilderError,
+};
+
+mod select_object_content;
+pub use select_object_content::{
+    CSVInput, CSVInputBuilder, CSVInputBuilderError, CSVOutput,
+    CSVOutputBuilder, CSVOutputBuilderError, InputSerialization,
+    InputSerializationBuilder, InputSerializationBuilderError, JSONInput,
+    JSONInputBuilder, JSONInputBuilderError, JSONOutput, JSONOutputBuilder,
+    JSONOutputBuilderError, OutputSerialization, OutputSerializationBuilder,
+    OutputSerializationBuilderError, ParquetInput, ParquetInputBuilder,
+    ParquetInputBuilderError, RequestProgress, RequestProgressBuilder,
+    RequestProgressBuilderError, ScanRange, ScanRangeBuilder,
+    ScanRangeBuilderError, SelectObjectContentRequest,
+    SelectObjectContentRequestBuilder, SelectObjectContentRequestBuilderError,
+    Stats, StatsBuilder, StatsBuilderError,
+};
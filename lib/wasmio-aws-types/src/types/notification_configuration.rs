@@ -0,0 +1,114 @@
+use derivative::Derivative;
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+
+/// A container for specifying the notification configuration of the bucket.
+/// If this element is empty, notifications are turned off for the bucket.
+#[derive(
+    Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize,
+)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename = "NotificationConfiguration")]
+pub struct NotificationConfiguration {
+    /// The queues to publish messages to and the events for which to publish
+    /// messages.
+    #[serde(rename = "QueueConfiguration", default)]
+    pub queue_configurations: Vec<QueueConfiguration>,
+}
+
+/// Specifies the configuration for publishing messages to a queue when
+/// Amazon S3 detects specified events.
+#[derive(
+    Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize,
+)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct QueueConfiguration {
+    /// An optional unique identifier for configurations in a notification
+    /// configuration. If you don't provide one, Amazon S3 will assign an ID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The Amazon Resource Name (ARN) of the queue to which Amazon S3
+    /// publishes a message when it detects events of the specified type.
+    #[serde(rename = "Queue")]
+    pub queue_arn: String,
+    /// The bucket events for which to send notifications.
+    #[serde(rename = "Event", default)]
+    pub events: Vec<String>,
+    /// Specifies object key name filtering rules.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<NotificationConfigurationFilter>,
+}
+
+/// Specifies object key name filtering rules.
+#[derive(
+    Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize,
+)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+pub struct NotificationConfigurationFilter {
+    /// A container for object key name prefix and suffix filtering rules.
+    #[serde(rename = "S3Key", skip_serializing_if = "Option::is_none")]
+    pub key: Option<S3KeyFilter>,
+}
+
+/// A container for object key name prefix and suffix filtering rules.
+#[derive(
+    Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize,
+)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+pub struct S3KeyFilter {
+    #[serde(rename = "FilterRule", default)]
+    pub filter_rules: Vec<FilterRule>,
+}
+
+/// Specifies the Amazon S3 object key name to filter on. An object key name
+/// is the name assigned to an object in your Amazon S3 bucket.
+#[derive(
+    Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize,
+)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct FilterRule {
+    /// The object key name prefix or suffix identifying one or more objects
+    /// to which the filtering rule applies, either `prefix` or `suffix`.
+    pub name: String,
+    /// The value that the filter searches for in object key names.
+    pub value: String,
+}
+
+#[derive(Derivative, Default, Builder)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+pub struct PutBucketNotificationConfigurationRequest {
+    /// The name of the bucket.
+    pub bucket: String,
+    /// The account ID of the expected bucket owner. If the bucket is owned by
+    /// a different account, the request will fail with an HTTP `403 (Access
+    /// Denied)` error.
+    pub expected_bucket_owner: Option<String>,
+    /// A container for specifying the notification configuration of the
+    /// bucket. If this element is empty, notifications are turned off for
+    /// the bucket.
+    pub notification_configuration: NotificationConfiguration,
+    /// Skips validation of Amazon SQS, Amazon SNS, and Lambda destinations.
+    /// True or false value.
+    pub skip_destination_validation: Option<bool>,
+}
+
+#[derive(Derivative, Default, Builder)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+pub struct GetBucketNotificationConfigurationRequest {
+    /// The name of the bucket for which to get the notification
+    /// configuration.
+    pub bucket: String,
+    /// The account ID of the expected bucket owner. If the bucket is owned by
+    /// a different account, the request will fail with an HTTP `403 (Access
+    /// Denied)` error.
+    pub expected_bucket_owner: Option<String>,
+}
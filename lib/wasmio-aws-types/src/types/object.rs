@@ -25,16 +25,22 @@ pub struct Object {
     /// - If an object is created by either the
     /// Multipart Upload or Part Copy operation, the ETag is not an MD5 digest,
     /// regardless of the method of encryption.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub e_tag: Option<String>,
     /// The name that you assign to an object. You use the object key to
     /// retrieve the object.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub key: Option<String>,
     /// Creation date of the object.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub last_modified: Option<String>,
     /// The owner of the object
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub owner: Option<Owner>,
     /// Size in bytes of the object
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub size: Option<i64>,
     /// The class of storage used to store the object.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub storage_class: Option<String>,
 }
@@ -0,0 +1,22 @@
+use derivative::Derivative;
+use derive_builder::Builder;
+use serde::Serialize;
+
+/// Body of the response of a browser upload when `success_action_status` is
+/// `201`.
+#[derive(Derivative, Default, Builder, Serialize)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename = "PostResponse")]
+#[serde(rename_all = "PascalCase")]
+pub struct PostResponse {
+    /// The URL of the uploaded object.
+    pub location: String,
+    /// The name of the bucket the object was uploaded to.
+    pub bucket: String,
+    /// The key of the uploaded object.
+    pub key: String,
+    /// The entity tag of the uploaded object.
+    #[serde(rename = "ETag")]
+    pub e_tag: String,
+}
@@ -26,6 +26,18 @@ pub struct PutObjectRequest {
     pub bucket_key_enabled: Option<bool>,
     ///  Can be used to specify caching behavior along the request/reply chain. For more information, see <a href="http://www.w3.org/Protocols/rfc2616/rfc2616-sec14.html#sec14.9">http://www.w3.org/Protocols/rfc2616/rfc2616-sec14.html#sec14.9</a>.
     pub cache_control: Option<String>,
+    /// Indicates the algorithm used to create the checksum for the object,
+    /// sent with `x-amz-sdk-checksum-algorithm`: CRC32, CRC32C, SHA1 or
+    /// SHA256.
+    pub checksum_algorithm: Option<String>,
+    /// The base64-encoded, 32-bit CRC32 checksum of the object.
+    pub checksum_crc32: Option<String>,
+    /// The base64-encoded, 32-bit CRC32C checksum of the object.
+    pub checksum_crc32c: Option<String>,
+    /// The base64-encoded, 160-bit SHA-1 digest of the object.
+    pub checksum_sha1: Option<String>,
+    /// The base64-encoded, 256-bit SHA-256 digest of the object.
+    pub checksum_sha256: Option<String>,
     /// Specifies presentational information for the object. For more information, see <a href="http://www.w3.org/Protocols/rfc2616/rfc2616-sec19.html#sec19.5.1">http://www.w3.org/Protocols/rfc2616/rfc2616-sec19.html#sec19.5.1</a>.
     pub content_disposition: Option<String>,
     /// Specifies what content encodings have been applied to the object and thus what decoding mechanisms must be applied to obtain the media-type referenced by the Content-Type header field. For more information, see <a href="http://www.w3.org/Protocols/rfc2616/rfc2616-sec14.html#sec14.11">http://www.w3.org/Protocols/rfc2616/rfc2616-sec14.html#sec14.11</a>.
@@ -143,6 +155,14 @@ pub struct PutObjectOutput {
     pub server_side_encryption: Option<String>,
     /// Version of the object.
     pub version_id: Option<String>,
-    /// TODO
-    pub checksum: Option<String>,
+    /// The size of the object in bytes.
+    pub size: Option<i64>,
+    /// The base64-encoded, 32-bit CRC32 checksum of the object.
+    pub checksum_crc32: Option<String>,
+    /// The base64-encoded, 32-bit CRC32C checksum of the object.
+    pub checksum_crc32c: Option<String>,
+    /// The base64-encoded, 160-bit SHA-1 digest of the object.
+    pub checksum_sha1: Option<String>,
+    /// The base64-encoded, 256-bit SHA-256 digest of the object.
+    pub checksum_sha256: Option<String>,
 }
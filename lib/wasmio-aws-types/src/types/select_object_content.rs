@@ -0,0 +1,202 @@
+use derivative::Derivative;
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+
+/// Request to filter the contents of an object based on a simple structured
+/// query language (SQL) statement.
+///
+/// The body of the request is deserialized into this struct, the fields
+/// which are not part of it are filled from the path and the headers.
+#[derive(Derivative, Default, Clone, Builder, Deserialize)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename = "SelectObjectContentRequest")]
+#[serde(rename_all = "PascalCase")]
+pub struct SelectObjectContentRequest {
+    /// The S3 bucket.
+    #[serde(skip)]
+    pub bucket: String,
+    /// The object key.
+    #[serde(skip)]
+    pub key: String,
+    /// The server-side encryption (SSE) algorithm used to encrypt the object.
+    #[serde(skip)]
+    pub sse_customer_algorithm: Option<String>,
+    /// The server-side encryption (SSE) customer managed key.
+    #[serde(skip)]
+    pub sse_customer_key: Option<String>,
+    /// The MD5 server-side encryption (SSE) customer managed key.
+    #[serde(skip)]
+    pub sse_customer_key_md5: Option<String>,
+    /// The account ID of the expected bucket owner. If the bucket is owned by
+    /// a different account, the request will fail with an HTTP `403 (Access
+    /// Denied)` error.
+    #[serde(skip)]
+    pub expected_bucket_owner: Option<String>,
+    /// The expression that is used to query the object.
+    pub expression: String,
+    /// The type of the provided expression (for example, SQL).
+    pub expression_type: String,
+    /// Specifies if periodic request progress information should be enabled.
+    pub request_progress: Option<RequestProgress>,
+    /// Describes the format of the data in the object that is being queried.
+    pub input_serialization: InputSerialization,
+    /// Describes the format of the data that you want Amazon S3 to return in
+    /// response.
+    pub output_serialization: OutputSerialization,
+    /// Specifies the byte range of the object to get the records from.
+    pub scan_range: Option<ScanRange>,
+}
+
+/// Container for specifying if periodic `QueryProgress` messages should be
+/// sent.
+#[derive(Derivative, Default, Clone, Builder, Serialize, Deserialize)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct RequestProgress {
+    /// Specifies whether periodic QueryProgress frames should be sent.
+    pub enabled: Option<bool>,
+}
+
+/// Specifies the byte range of the object to get the records from.
+#[derive(Derivative, Default, Clone, Builder, Serialize, Deserialize)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct ScanRange {
+    /// Specifies the start of the byte range.
+    pub start: Option<i64>,
+    /// Specifies the end of the byte range.
+    pub end: Option<i64>,
+}
+
+/// Describes the serialization format of the object.
+#[derive(Derivative, Default, Clone, Builder, Serialize, Deserialize)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct InputSerialization {
+    /// Describes the serialization of a CSV-encoded object.
+    #[serde(rename = "CSV")]
+    pub csv: Option<CSVInput>,
+    /// Specifies JSON as object's input serialization format.
+    #[serde(rename = "JSON")]
+    pub json: Option<JSONInput>,
+    /// Specifies Parquet as object's input serialization format.
+    pub parquet: Option<ParquetInput>,
+    /// Specifies object's compressed format. Valid values: NONE, GZIP, BZIP2.
+    /// Default Value: NONE.
+    pub compression_type: Option<String>,
+}
+
+/// Describes how an uncompressed comma-separated values (CSV)-formatted input
+/// object is formatted.
+#[derive(Derivative, Default, Clone, Builder, Serialize, Deserialize)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct CSVInput {
+    /// Describes the first line of input. Valid values are:
+    /// - `NONE`: First line is not a header.
+    /// - `IGNORE`: First line is a header, but you can't use the header values
+    ///   to indicate the column in an expression.
+    /// - `USE`: First line is a header, and you can use the header value to
+    ///   identify a column in an expression.
+    pub file_header_info: Option<String>,
+    /// A single character used to indicate that a row should be ignored when
+    /// the character is present at the start of that row.
+    pub comments: Option<String>,
+    /// A single character used for escaping the quotation mark character
+    /// inside an already escaped value.
+    pub quote_escape_character: Option<String>,
+    /// A single character used to separate individual records in the input.
+    pub record_delimiter: Option<String>,
+    /// A single character used to separate individual fields in a record.
+    pub field_delimiter: Option<String>,
+    /// A single character used for escaping when the field delimiter is part
+    /// of the value.
+    pub quote_character: Option<String>,
+    /// Specifies that CSV field values may contain quoted record delimiters
+    /// and such records should be allowed. Default value is FALSE.
+    pub allow_quoted_record_delimiter: Option<bool>,
+}
+
+/// Specifies JSON as object's input serialization format.
+#[derive(Derivative, Default, Clone, Builder, Serialize, Deserialize)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct JSONInput {
+    /// The type of JSON. Valid values: Document, Lines.
+    #[serde(rename = "Type")]
+    pub type_: Option<String>,
+}
+
+/// Container for Parquet.
+#[derive(Derivative, Default, Clone, Builder, Serialize, Deserialize)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+pub struct ParquetInput {}
+
+/// Describes how results of the Select job are serialized.
+#[derive(Derivative, Default, Clone, Builder, Serialize, Deserialize)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct OutputSerialization {
+    /// Describes the serialization of CSV-encoded Select results.
+    #[serde(rename = "CSV")]
+    pub csv: Option<CSVOutput>,
+    /// Specifies JSON as request's output serialization format.
+    #[serde(rename = "JSON")]
+    pub json: Option<JSONOutput>,
+}
+
+/// Describes how uncompressed comma-separated values (CSV)-formatted results
+/// are formatted.
+#[derive(Derivative, Default, Clone, Builder, Serialize, Deserialize)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct CSVOutput {
+    /// Indicates whether to use quotation marks around output fields:
+    /// - `ALWAYS`: Always use quotation marks for output fields.
+    /// - `ASNEEDED`: Use quotation marks for output fields when needed.
+    pub quote_fields: Option<String>,
+    /// The single character used for escaping the quote character inside an
+    /// already escaped value.
+    pub quote_escape_character: Option<String>,
+    /// A single character used to separate individual records in the output.
+    pub record_delimiter: Option<String>,
+    /// The value used to separate individual fields in a record.
+    pub field_delimiter: Option<String>,
+    /// A single character used for escaping when the field delimiter is part
+    /// of the value.
+    pub quote_character: Option<String>,
+}
+
+/// Specifies JSON as request's output serialization format.
+#[derive(Derivative, Default, Clone, Builder, Serialize, Deserialize)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct JSONOutput {
+    /// The value used to separate individual records in the output. If no
+    /// value is specified, Amazon S3 uses a newline character ('\n').
+    pub record_delimiter: Option<String>,
+}
+
+/// Container for the stats details, sent at the end of the query.
+#[derive(Derivative, Default, Clone, Copy, Builder, Serialize, Deserialize)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct Stats {
+    /// The total number of object bytes scanned.
+    pub bytes_scanned: i64,
+    /// The total number of uncompressed object bytes processed.
+    pub bytes_processed: i64,
+    /// The total number of bytes of records payload data returned.
+    pub bytes_returned: i64,
+}
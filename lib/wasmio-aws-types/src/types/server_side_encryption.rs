@@ -0,0 +1,107 @@
+use derivative::Derivative;
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+
+/// Specifies the default server-side-encryption configuration.
+#[derive(
+    Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize,
+)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename = "ServerSideEncryptionConfiguration")]
+#[serde(rename_all = "PascalCase")]
+pub struct ServerSideEncryptionConfiguration {
+    /// Container for information about a particular server-side encryption
+    /// configuration rule.
+    #[serde(rename = "Rule", default)]
+    pub rules: Vec<ServerSideEncryptionRule>,
+}
+
+/// Specifies the default server-side encryption configuration.
+#[derive(
+    Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize,
+)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct ServerSideEncryptionRule {
+    /// Specifies the default server-side encryption to apply to new objects
+    /// in the bucket. If a PUT Object request doesn't specify any server-side
+    /// encryption, this default encryption will be applied.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apply_server_side_encryption_by_default:
+        Option<ServerSideEncryptionByDefault>,
+    /// Specifies whether Amazon S3 should use an S3 Bucket Key with
+    /// server-side encryption using KMS (SSE-KMS) for new objects in the
+    /// bucket.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bucket_key_enabled: Option<bool>,
+}
+
+/// Describes the default server-side encryption to apply to new objects in
+/// the bucket.
+#[derive(
+    Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize,
+)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct ServerSideEncryptionByDefault {
+    /// Server-side encryption algorithm to use for the default encryption.
+    ///
+    /// Valid Values: AES256 | aws:kms | aws:kms:dsse
+    #[serde(rename = "SSEAlgorithm")]
+    pub sse_algorithm: String,
+    /// Amazon Web Services Key Management Service (KMS) customer Amazon Web
+    /// Services KMS key ID to use for the default encryption. This parameter
+    /// is allowed if and only if `SSEAlgorithm` is set to `aws:kms`.
+    #[serde(
+        rename = "KMSMasterKeyID",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub kms_master_key_id: Option<String>,
+}
+
+#[derive(Derivative, Default, Builder)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+pub struct PutBucketEncryptionRequest {
+    /// Specifies default encryption for a bucket using server-side
+    /// encryption with different key options.
+    pub bucket: String,
+    /// The base64-encoded 128-bit MD5 digest of the server-side encryption
+    /// configuration.
+    pub content_md5: Option<String>,
+    /// The account ID of the expected bucket owner. If the bucket is owned by
+    /// a different account, the request will fail with an HTTP `403 (Access
+    /// Denied)` error.
+    pub expected_bucket_owner: Option<String>,
+    /// Specifies the default server-side-encryption configuration.
+    pub server_side_encryption_configuration: ServerSideEncryptionConfiguration,
+}
+
+#[derive(Derivative, Default, Builder)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+pub struct GetBucketEncryptionRequest {
+    /// The name of the bucket from which the server-side encryption
+    /// configuration is retrieved.
+    pub bucket: String,
+    /// The account ID of the expected bucket owner. If the bucket is owned by
+    /// a different account, the request will fail with an HTTP `403 (Access
+    /// Denied)` error.
+    pub expected_bucket_owner: Option<String>,
+}
+
+#[derive(Derivative, Default, Builder)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+pub struct DeleteBucketEncryptionRequest {
+    /// The name of the bucket containing the server-side encryption
+    /// configuration to delete.
+    pub bucket: String,
+    /// The account ID of the expected bucket owner. If the bucket is owned by
+    /// a different account, the request will fail with an HTTP `403 (Access
+    /// Denied)` error.
+    pub expected_bucket_owner: Option<String>,
+}
@@ -0,0 +1,199 @@
+use derivative::Derivative;
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+
+/// Specifies website configuration parameters for an Amazon S3 bucket.
+#[derive(
+    Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize,
+)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename = "WebsiteConfiguration")]
+#[serde(rename_all = "PascalCase")]
+pub struct WebsiteConfiguration {
+    /// The name of the error document for the website.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_document: Option<ErrorDocument>,
+    /// The name of the index document for the website.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_document: Option<IndexDocument>,
+    /// The redirect behavior for every request to this bucket's website
+    /// endpoint. If you specify this property, you can't specify any other
+    /// property.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirect_all_requests_to: Option<RedirectAllRequestsTo>,
+    /// Rules that define when a redirect is applied and the redirect
+    /// behavior.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub routing_rules: Option<RoutingRules>,
+}
+
+/// The error information.
+#[derive(
+    Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize,
+)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct ErrorDocument {
+    /// The object key name to use when a 4XX class error occurs.
+    pub key: String,
+}
+
+/// Container for the `Suffix` element.
+#[derive(
+    Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize,
+)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct IndexDocument {
+    /// A suffix that is appended to a request that is for a directory on the
+    /// website endpoint (for example, if the suffix is index.html and you
+    /// make a request to samplebucket/images/ the data that is returned will
+    /// be for the object with the key name images/index.html) The suffix
+    /// must not be empty and must not include a slash character.
+    pub suffix: String,
+}
+
+/// Specifies the redirect behavior of all requests to a website endpoint of
+/// an Amazon S3 bucket.
+#[derive(
+    Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize,
+)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct RedirectAllRequestsTo {
+    /// Name of the host where requests are redirected.
+    pub host_name: String,
+    /// Protocol to use when redirecting requests. The default is the protocol
+    /// that is used in the original request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+}
+
+/// Container for the list of routing rules.
+#[derive(
+    Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize,
+)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct RoutingRules {
+    #[serde(default)]
+    pub routing_rule: Vec<RoutingRule>,
+}
+
+/// Specifies the redirect behavior and when a redirect is applied.
+#[derive(
+    Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize,
+)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct RoutingRule {
+    /// A container for describing a condition that must be met for the
+    /// specified redirect to apply. For example, 1. If request is for pages
+    /// in the `/docs` folder, redirect to the `/documents` folder. 2. If
+    /// request results in HTTP error 4xx, redirect request to another host
+    /// where you might process the error.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<Condition>,
+    /// Container for redirect information. You can redirect requests to
+    /// another host, to another page, or with another protocol. In the event
+    /// of an error, you can specify a different error code to return.
+    pub redirect: Redirect,
+}
+
+/// A container for describing a condition that must be met for the specified
+/// redirect to apply.
+#[derive(
+    Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize,
+)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct Condition {
+    /// The HTTP error code when the redirect is applied. In the event of an
+    /// error, if the error code equals this value, then the specified
+    /// redirect is applied.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_error_code_returned_equals: Option<String>,
+    /// The object key name prefix when the redirect is applied.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_prefix_equals: Option<String>,
+}
+
+/// Specifies how requests are redirected. In the event of an error, you can
+/// specify a different error code to return.
+#[derive(
+    Derivative, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize,
+)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+#[serde(rename_all = "PascalCase")]
+pub struct Redirect {
+    /// The host name to use in the redirect request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host_name: Option<String>,
+    /// The HTTP redirect code to use on the response. Not required if one of
+    /// the siblings is present.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_redirect_code: Option<String>,
+    /// Protocol to use when redirecting requests. The default is the protocol
+    /// that is used in the original request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    /// The object key prefix to use in the redirect request. Not required if
+    /// one of the siblings is present. Can be present only if
+    /// `ReplaceKeyWith` is not provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replace_key_prefix_with: Option<String>,
+    /// The specific object key to use in the redirect request. Not required
+    /// if one of the siblings is present. Can be present only if
+    /// `ReplaceKeyPrefixWith` is not provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replace_key_with: Option<String>,
+}
+
+#[derive(Derivative, Default, Builder)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+pub struct PutBucketWebsiteRequest {
+    /// The bucket name.
+    pub bucket: String,
+    /// The base64-encoded 128-bit MD5 digest of the data.
+    pub content_md5: Option<String>,
+    /// The account ID of the expected bucket owner. If the bucket is owned by
+    /// a different account, the request will fail with an HTTP `403 (Access
+    /// Denied)` error.
+    pub expected_bucket_owner: Option<String>,
+    /// Container for the request.
+    pub website_configuration: WebsiteConfiguration,
+}
+
+#[derive(Derivative, Default, Builder)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+pub struct GetBucketWebsiteRequest {
+    /// The bucket name for which to get the website configuration.
+    pub bucket: String,
+    /// The account ID of the expected bucket owner. If the bucket is owned by
+    /// a different account, the request will fail with an HTTP `403 (Access
+    /// Denied)` error.
+    pub expected_bucket_owner: Option<String>,
+}
+
+#[derive(Derivative, Default, Builder)]
+#[derivative(Debug)]
+#[builder(pattern = "owned", setter(into), default)]
+pub struct DeleteBucketWebsiteRequest {
+    /// The bucket name for which you want to remove the website
+    /// configuration.
+    pub bucket: String,
+    /// The account ID of the expected bucket owner. If the bucket is owned by
+    /// a different account, the request will fail with an HTTP `403 (Access
+    /// Denied)` error.
+    pub expected_bucket_owner: Option<String>,
+}